@@ -1,21 +1,29 @@
-use common::{ApiInfo, Error};
+use common::{ApiInfo, Error, OauthToken};
 use log::{error, info};
 use reqwest::RequestBuilder;
 use serde::Deserialize;
 use serde_json::json;
 use url::Url;
 
-use crate::{get_api_client, http};
+use crate::{get_api_client, http, http::RetryPolicy, instance, retry_policy};
 
 #[derive(Deserialize)]
 struct TokenResponse {
-    token: String,
+    token: OauthToken,
     user: String,
 }
 
-async fn handle_auth_response(instance: Url, builder: RequestBuilder) -> Result<ApiInfo, String> {
-    match http::send_json::<TokenResponse>(builder).await {
-        Ok(resp) => Ok(ApiInfo::new(instance, Some(resp.user), Some(resp.token))),
+async fn handle_auth_response(
+    instance: Url,
+    builder: RequestBuilder,
+    retry_policy: RetryPolicy,
+) -> Result<ApiInfo, String> {
+    match http::send_json_with_retry::<TokenResponse>(builder, &retry_policy).await {
+        Ok(resp) => {
+            let capabilities = instance::negotiate_capabilities(&instance).await;
+            Ok(ApiInfo::new(instance, Some(resp.user), Some(resp.token))
+                .with_capabilities(capabilities))
+        }
         Err(err) => {
             error!("Authorization failed: {err}");
             Err(format_auth_error(&err))
@@ -36,7 +44,7 @@ fn format_auth_error(err: &Error) -> String {
         "Invalid credentials. Please check your email/password or OAuth token and try again.";
     const GENERIC_AUTH_FAILED: &str = "Authentication failed. Please try again.";
     match err {
-        Error::Unauthorized(_) => INVALID_CREDENTIALS.to_string(),
+        Error::Unauthorized(_) | Error::Forbidden(_) => INVALID_CREDENTIALS.to_string(),
         Error::Api { status: 400, .. } => INVALID_CREDENTIALS.to_string(),
         Error::NotFound(_) => {
             "Authentication endpoint not found. Please verify the instance URL is correct."
@@ -58,13 +66,11 @@ fn format_auth_error(err: &Error) -> String {
     }
 }
 
-pub async fn authorize_with_token(instance: Url, oauth: &str) -> Result<ApiInfo, String> {
+pub async fn authorize_with_token(instance: Url, oauth: &OauthToken) -> Result<ApiInfo, String> {
     info!("Attempting to authorize with: {instance} using OAuth token");
     let url = http::v2_url(&instance, "user/auth-token/");
-    let builder = get_api_client()
-        .get(url)
-        .header("Authorization", format!("Token {oauth}"));
-    handle_auth_response(instance, builder).await
+    let builder = http::with_token(get_api_client().get(url), oauth);
+    handle_auth_response(instance, builder, retry_policy()).await
 }
 
 pub async fn authorize_with_email(
@@ -76,15 +82,32 @@ pub async fn authorize_with_email(
     let url = http::v2_url(&instance, "user/auth-token/");
     let body = json!({"email": email, "password": password});
     let builder = get_api_client().post(url).json(&body);
-    handle_auth_response(instance, builder).await
+    handle_auth_response(instance, builder, RetryPolicy::NEVER).await
+}
+
+/// Invalidate `oauth` on the server, e.g. when signing out. A token the
+/// server no longer accepts comes back as `Error::Unauthorized`.
+pub async fn revoke_token(instance: Url, oauth: &OauthToken) -> Result<(), Error> {
+    info!("Revoking OAuth token on: {instance}");
+    let url = http::v2_url(&instance, "user/auth-token/");
+    let builder = http::with_token(get_api_client().delete(url), oauth);
+    http::send_raw_with_retry(builder, &retry_policy()).await?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_support::{ensure_test_env_vars, test_instance};
+    use crate::test_support::{
+        MOCK_OAUTH_TOKEN, MOCK_USER_EMAIL, MOCK_USER_PASSWORD, MockSpeleoDb, ensure_test_env_vars,
+        test_instance,
+    };
     use serial_test::serial;
 
+    fn mock_token() -> OauthToken {
+        OauthToken::new(MOCK_OAUTH_TOKEN).unwrap()
+    }
+
     #[test]
     fn format_auth_error_unauthorized_uses_user_friendly_text() {
         let msg = format_auth_error(&Error::Unauthorized("bad token".to_string()));
@@ -171,7 +194,7 @@ mod tests {
             return;
         }
         let instance = test_instance();
-        let oauth = std::env::var("TEST_SPELEODB_OAUTH").unwrap();
+        let oauth = OauthToken::new(std::env::var("TEST_SPELEODB_OAUTH").unwrap()).unwrap();
         let api_info = authorize_with_token(instance, &oauth)
             .await
             .expect("real OAuth token must succeed");
@@ -186,7 +209,7 @@ mod tests {
             return;
         }
         let instance = test_instance();
-        let bogus = "0".repeat(40);
+        let bogus = OauthToken::new("0".repeat(40)).unwrap();
         let err = authorize_with_token(instance, &bogus)
             .await
             .expect_err("bogus token must fail");
@@ -231,4 +254,74 @@ mod tests {
             "expected friendly invalid-credentials message, got: {err}"
         );
     }
+
+    // ─── MockSpeleoDb: hermetic auth coverage ──────────────────────────────
+
+    #[tokio::test]
+    async fn mock_authorize_with_token_success() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = authorize_with_token(mock.instance(), &mock_token())
+            .await
+            .expect("mock token must be accepted");
+        assert_eq!(api_info.email(), Some(MOCK_USER_EMAIL));
+        assert_eq!(api_info.oauth_token(), Some(&mock_token()));
+        assert_eq!(api_info.instance(), &mock.instance());
+        assert_eq!(api_info.capabilities().features, ["project_history"]);
+    }
+
+    #[tokio::test]
+    async fn mock_authorize_with_token_invalid_returns_friendly_message() {
+        let mock = MockSpeleoDb::start().await;
+        let err = authorize_with_token(mock.instance(), &OauthToken::new("0".repeat(40)).unwrap())
+            .await
+            .expect_err("bogus token must fail");
+        assert!(err.contains("Invalid credentials"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn mock_authorize_with_email_success() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = authorize_with_email(mock.instance(), MOCK_USER_EMAIL, MOCK_USER_PASSWORD)
+            .await
+            .expect("mock credentials must be accepted");
+        assert_eq!(api_info.email(), Some(MOCK_USER_EMAIL));
+        assert_eq!(api_info.oauth_token(), Some(&mock_token()));
+    }
+
+    #[tokio::test]
+    async fn mock_authorize_with_email_invalid_returns_friendly_message() {
+        let mock = MockSpeleoDb::start().await;
+        let err = authorize_with_email(mock.instance(), MOCK_USER_EMAIL, "wrong")
+            .await
+            .expect_err("wrong password must fail");
+        assert!(err.contains("Invalid credentials"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn mock_revoke_token_deletes_the_token() {
+        let mock = MockSpeleoDb::start().await;
+        revoke_token(mock.instance(), &mock_token())
+            .await
+            .expect("mock token must be revoked");
+        assert_eq!(mock.request_count("DELETE", "/api/v2/user/auth-token/"), 1);
+    }
+
+    #[tokio::test]
+    async fn mock_revoke_token_rejected_token_is_unauthorized() {
+        let mock = MockSpeleoDb::start().await;
+        let err = revoke_token(mock.instance(), &OauthToken::new("0".repeat(40)).unwrap())
+            .await
+            .expect_err("bogus token must be rejected");
+        assert!(matches!(err, Error::Unauthorized(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_authorize_server_error_mentions_status() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(500, "<html>internal error</html>");
+        let err = authorize_with_token(mock.instance(), &mock_token())
+            .await
+            .expect_err("500 must fail");
+        assert!(err.contains("500"), "got: {err}");
+    }
 }
@@ -0,0 +1,344 @@
+//! The `SpeleoDbApi` trait covers every operation in [`crate::auth`],
+//! [`crate::instance`] and [`crate::project`], so callers can hold "a SpeleoDB" rather than calling
+//! the free functions directly. `SpeleoDbClient` is the real implementation;
+//! tests substitute a fake to exercise sync and status logic without a
+//! network.
+//!
+//! Methods return boxed futures so the trait stays object safe and can be
+//! shared as `Arc<dyn SpeleoDbApi>`. Progress callbacks and download
+//! destinations are trait objects for the same reason.
+
+use std::{future::Future, path::Path, pin::Pin};
+
+use common::{
+    ApiInfo, Error, OauthToken,
+    api_types::{
+        CommitInfo, InstanceProbe, Page, ProjectInfo, ProjectMetadata, ProjectPermissions,
+        ProjectSaveResult,
+    },
+};
+use tokio::io::AsyncWrite;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    auth, instance, project,
+    project::{CancellationToken, DownloadedZip},
+};
+
+/// Future returned by every `SpeleoDbApi` method.
+pub type ApiFuture<'a, T, E = Error> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
+
+/// Progress callback of the ZIP transfers: chunk length, then total size
+/// when known.
+pub type TransferProgress<'a> = dyn FnMut(usize, Option<u64>) + Send + 'a;
+
+/// Every SpeleoDB operation the sidecar performs. See the free functions of
+/// the same name for the behavior and error mapping of each.
+pub trait SpeleoDbApi: Send + Sync {
+    /// Never fails: every outcome is a variant of `InstanceProbe`.
+    fn probe_instance(
+        &self,
+        instance: Url,
+    ) -> Pin<Box<dyn Future<Output = InstanceProbe> + Send + '_>>;
+
+    fn authorize_with_token<'a>(
+        &'a self,
+        instance: Url,
+        oauth: &'a OauthToken,
+    ) -> ApiFuture<'a, ApiInfo, String>;
+
+    fn authorize_with_email<'a>(
+        &'a self,
+        instance: Url,
+        email: &'a str,
+        password: &'a str,
+    ) -> ApiFuture<'a, ApiInfo, String>;
+
+    fn revoke_token<'a>(&'a self, instance: Url, oauth: &'a OauthToken) -> ApiFuture<'a, ()>;
+
+    fn create_project<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        name: String,
+        description: String,
+        country: String,
+        latitude: Option<String>,
+        longitude: Option<String>,
+    ) -> ApiFuture<'a, ProjectInfo>;
+
+    fn update_project<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        metadata: &'a ProjectMetadata,
+    ) -> ApiFuture<'a, ProjectInfo>;
+
+    fn acquire_project_mutex<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo>;
+
+    fn release_project_mutex<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo>;
+
+    fn fetch_projects<'a>(&'a self, api_info: &'a ApiInfo) -> ApiFuture<'a, Vec<ProjectInfo>>;
+
+    fn fetch_projects_if_modified<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+    ) -> ApiFuture<'a, Option<Vec<ProjectInfo>>>;
+
+    /// Make the next `fetch_projects_if_modified` return the listing even if
+    /// it is unchanged, after the last one could not be applied.
+    fn forget_projects_listing(&self, api_info: &ApiInfo);
+
+    fn fetch_project_info<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo>;
+
+    fn fetch_project_commits<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        page: u32,
+    ) -> ApiFuture<'a, Page<CommitInfo>>;
+
+    fn fetch_project_permissions<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectPermissions>;
+
+    fn download_project_zip<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        dest: &'a mut (dyn AsyncWrite + Send + Unpin),
+        on_progress: &'a mut TransferProgress<'_>,
+    ) -> ApiFuture<'a, DownloadedZip>;
+
+    fn download_project_revision_zip<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        commit_id: &'a str,
+        dest: &'a mut (dyn AsyncWrite + Send + Unpin),
+        on_progress: &'a mut TransferProgress<'_>,
+    ) -> ApiFuture<'a, DownloadedZip>;
+
+    fn upload_project_zip<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        commit_message: String,
+        zip_path: &'a Path,
+        on_progress: Box<TransferProgress<'static>>,
+        cancel: &'a CancellationToken,
+    ) -> ApiFuture<'a, ProjectSaveResult>;
+}
+
+/// `SpeleoDbApi` over HTTP, sharing the crate's client and retry policy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpeleoDbClient;
+
+impl SpeleoDbApi for SpeleoDbClient {
+    fn probe_instance(
+        &self,
+        instance: Url,
+    ) -> Pin<Box<dyn Future<Output = InstanceProbe> + Send + '_>> {
+        Box::pin(instance::probe_instance(instance))
+    }
+
+    fn authorize_with_token<'a>(
+        &'a self,
+        instance: Url,
+        oauth: &'a OauthToken,
+    ) -> ApiFuture<'a, ApiInfo, String> {
+        Box::pin(auth::authorize_with_token(instance, oauth))
+    }
+
+    fn authorize_with_email<'a>(
+        &'a self,
+        instance: Url,
+        email: &'a str,
+        password: &'a str,
+    ) -> ApiFuture<'a, ApiInfo, String> {
+        Box::pin(auth::authorize_with_email(instance, email, password))
+    }
+
+    fn revoke_token<'a>(&'a self, instance: Url, oauth: &'a OauthToken) -> ApiFuture<'a, ()> {
+        Box::pin(auth::revoke_token(instance, oauth))
+    }
+
+    fn create_project<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        name: String,
+        description: String,
+        country: String,
+        latitude: Option<String>,
+        longitude: Option<String>,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(project::create_project(
+            api_info,
+            name,
+            description,
+            country,
+            latitude,
+            longitude,
+        ))
+    }
+
+    fn update_project<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        metadata: &'a ProjectMetadata,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(project::update_project(api_info, project_id, metadata))
+    }
+
+    fn acquire_project_mutex<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(project::acquire_project_mutex(api_info, project_id))
+    }
+
+    fn release_project_mutex<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(project::release_project_mutex(api_info, project_id))
+    }
+
+    fn fetch_projects<'a>(&'a self, api_info: &'a ApiInfo) -> ApiFuture<'a, Vec<ProjectInfo>> {
+        Box::pin(project::fetch_projects(api_info))
+    }
+
+    fn fetch_projects_if_modified<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+    ) -> ApiFuture<'a, Option<Vec<ProjectInfo>>> {
+        Box::pin(project::fetch_projects_if_modified(api_info))
+    }
+
+    fn forget_projects_listing(&self, api_info: &ApiInfo) {
+        project::forget_projects_listing(api_info);
+    }
+
+    fn fetch_project_info<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(project::fetch_project_info(api_info, project_id))
+    }
+
+    fn fetch_project_commits<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        page: u32,
+    ) -> ApiFuture<'a, Page<CommitInfo>> {
+        Box::pin(project::fetch_project_commits(api_info, project_id, page))
+    }
+
+    fn fetch_project_permissions<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectPermissions> {
+        Box::pin(project::fetch_project_permissions(api_info, project_id))
+    }
+
+    fn download_project_zip<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        mut dest: &'a mut (dyn AsyncWrite + Send + Unpin),
+        on_progress: &'a mut TransferProgress<'_>,
+    ) -> ApiFuture<'a, DownloadedZip> {
+        Box::pin(async move {
+            project::download_project_zip(api_info, project_id, &mut dest, on_progress).await
+        })
+    }
+
+    fn download_project_revision_zip<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        commit_id: &'a str,
+        mut dest: &'a mut (dyn AsyncWrite + Send + Unpin),
+        on_progress: &'a mut TransferProgress<'_>,
+    ) -> ApiFuture<'a, DownloadedZip> {
+        Box::pin(async move {
+            project::download_project_revision_zip(
+                api_info,
+                project_id,
+                commit_id,
+                &mut dest,
+                on_progress,
+            )
+            .await
+        })
+    }
+
+    fn upload_project_zip<'a>(
+        &'a self,
+        api_info: &'a ApiInfo,
+        project_id: Uuid,
+        commit_message: String,
+        zip_path: &'a Path,
+        on_progress: Box<TransferProgress<'static>>,
+        cancel: &'a CancellationToken,
+    ) -> ApiFuture<'a, ProjectSaveResult> {
+        Box::pin(project::upload_project_zip(
+            api_info,
+            project_id,
+            commit_message,
+            zip_path,
+            on_progress,
+            cancel,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockSpeleoDb;
+    use common::api_types::ProjectType;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn mock_client_is_usable_as_trait_object() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project_with_zip("Compass", b"zip".to_vec());
+        mock.add_project("Ariane", ProjectType::Ignored);
+        let api: Arc<dyn SpeleoDbApi> = Arc::new(SpeleoDbClient);
+
+        let projects = api.fetch_projects(&api_info).await.unwrap();
+        assert_eq!(projects.len(), 1);
+
+        let mut dest = Vec::new();
+        let mut reported = 0;
+        let written = api
+            .download_project_zip(&api_info, id, &mut dest, &mut |chunk, _| reported += chunk)
+            .await
+            .unwrap();
+        assert_eq!(written.size, 3);
+        assert_eq!(reported, 3);
+        assert_eq!(dest, b"zip");
+    }
+}
@@ -5,12 +5,46 @@
 //! Status codes map to typed `Error` variants in a single place; success
 //! bodies deserialize directly into the caller's target type without any
 //! `data:` wrapper, matching the v2 contract.
+//!
+//! Idempotent requests use the `*_with_retry` variants, which re-send on
+//! transient failures according to a [`RetryPolicy`]. Non-idempotent
+//! requests (project creation, ZIP upload) must never be retried.
+//!
+//! Every request carries a fresh `X-Request-ID` and is logged as one
+//! `RequestRecord` (method, path, status, latency, size) under the
+//! `api::http` target; the sidecar forwards those records to Sentry as
+//! breadcrumbs. Headers, query strings and bodies are never logged.
+//!
+//! Listing endpoints go through `send_listing_with_retry`, which follows
+//! `next` links when the server paginates. Polled listings use
+//! `send_listing_if_modified`, which remembers the `ETag` and
+//! `Last-Modified` validators of the last response per page and user and
+//! sends them back, so an unchanged listing costs one bodyless 304 per page.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
 
-use common::{ApiInfo, Error};
-use log::error;
-use reqwest::{RequestBuilder, Response, StatusCode};
-use serde::{Deserialize, de::DeserializeOwned};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use common::{ApiInfo, Error, OauthToken, api_types::Page};
+use log::{debug, error, info, warn};
+use reqwest::{
+    Client, Method, Request, RequestBuilder, Response, StatusCode,
+    header::{
+        AUTHORIZATION, ETAG, HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED, RETRY_AFTER,
+    },
+};
+use serde::{
+    Deserialize,
+    de::{DeserializeOwned, IgnoredAny},
+};
 use url::Url;
+use uuid::Uuid;
 
 /// V2 path prefix appended to the configured `instance` URL.
 const API_V2_PREFIX: &str = "api/v2/";
@@ -43,7 +77,37 @@ pub(crate) fn authenticated(
     api_info: &ApiInfo,
 ) -> Result<RequestBuilder, Error> {
     let token = api_info.oauth_token().ok_or(Error::NoAuthToken)?;
-    Ok(builder.header("Authorization", format!("Token {token}")))
+    Ok(with_token(builder, token))
+}
+
+/// Authenticate `builder` with `token`, for requests made before there is
+/// an `ApiInfo` (signing in) or after it is gone (revoking).
+pub(crate) fn with_token(builder: RequestBuilder, token: &OauthToken) -> RequestBuilder {
+    builder.header(AUTHORIZATION, format!("Token {}", token.as_ref()))
+}
+
+/// SHA-256 the server announces for a response body, from `Repr-Digest`
+/// (RFC 9530, `sha-256=:<base64>:`) or, failing that, the older `Digest`
+/// (RFC 3230, `SHA-256=<base64>`). Other algorithms and malformed values
+/// are ignored, so a download without a usable digest is simply not
+/// verified.
+pub(crate) fn expected_sha256(headers: &HeaderMap) -> Option<[u8; 32]> {
+    let digest_in = |name: &str| -> Option<[u8; 32]> {
+        let value = headers.get(name)?.to_str().ok()?;
+        let encoded = value.split(',').find_map(|member| {
+            let (algorithm, encoded) = member.trim().split_once('=')?;
+            algorithm
+                .trim()
+                .eq_ignore_ascii_case("sha-256")
+                .then(|| encoded.trim().trim_matches(':'))
+        })?;
+        let digest = BASE64_STANDARD.decode(encoded).ok()?.try_into().ok();
+        if digest.is_none() {
+            warn!("Ignoring malformed {name} header: {value}");
+        }
+        digest
+    };
+    digest_in("repr-digest").or_else(|| digest_in("digest"))
 }
 
 /// Send a request and deserialize a JSON success body into `T`.
@@ -51,18 +115,374 @@ pub(crate) fn authenticated(
 /// On non-2xx responses, the body is parsed for the v2 `{"error": "..."}`
 /// shape and dispatched to the typed error variant matching the status.
 pub(crate) async fn send_json<T: DeserializeOwned>(builder: RequestBuilder) -> Result<T, Error> {
-    let resp = builder
-        .send()
-        .await
+    deserialize_json(send_raw(builder).await?).await
+}
+
+/// `send_json` for idempotent requests: transient failures are retried
+/// according to `policy` before the final error is returned.
+pub(crate) async fn send_json_with_retry<T: DeserializeOwned>(
+    builder: RequestBuilder,
+    policy: &RetryPolicy,
+) -> Result<T, Error> {
+    deserialize_json(send_raw_with_retry(builder, policy).await?).await
+}
+
+/// A listing endpoint's body: a bare JSON array, or one page of it when the
+/// server paginates the endpoint.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Listing<T> {
+    Paged(Page<T>),
+    Flat(Vec<T>),
+}
+
+impl<T> Listing<T> {
+    fn into_parts(self) -> (Vec<T>, Option<String>) {
+        match self {
+            Self::Paged(page) => (page.results, page.next),
+            Self::Flat(items) => (items, None),
+        }
+    }
+}
+
+fn parse_listing<T: DeserializeOwned>(body: &[u8]) -> Result<Listing<T>, Error> {
+    serde_json::from_slice(body).map_err(|e| {
+        error!("Failed to deserialize listing: {e}");
+        Error::Deserialization(e.to_string())
+    })
+}
+
+/// Request for the page a `next` link points to, with the same headers as
+/// `current`.
+///
+/// Links are resolved against the current URL, and only their path and
+/// query are kept: a server behind a TLS-terminating proxy may link to its
+/// own `http://` origin, which the request must not follow. A link to
+/// another host is refused, since the request carries the user's token, and
+/// so is a link to an already visited page, which would never terminate.
+fn next_page_request(
+    current: &Request,
+    next: &str,
+    visited: &mut HashSet<Url>,
+) -> Result<Request, Error> {
+    let link = current
+        .url()
+        .join(next)
+        .map_err(|e| Error::Deserialization(format!("Invalid next page link {next}: {e}")))?;
+    let mut url = current.url().clone();
+    url.set_path(link.path());
+    url.set_query(link.query());
+    visited.insert(current.url().clone());
+    if link.host() != current.url().host() || visited.contains(&url) {
+        error!("Refusing to follow next page link: {link}");
+        return Err(Error::Deserialization(format!(
+            "Invalid next page link {link}"
+        )));
+    }
+    let mut request = clone_get(current);
+    *request.url_mut() = url;
+    Ok(request)
+}
+
+fn clone_get(request: &Request) -> Request {
+    request
+        .try_clone()
+        .expect("listing requests are GETs without a streamed body")
+}
+
+/// Fetch every page of a listing and concatenate the items, following `next`
+/// links. A bare-array response is taken as the whole listing, so callers
+/// need not know whether the server paginates the endpoint.
+pub(crate) async fn send_listing_with_retry<T: DeserializeOwned>(
+    builder: RequestBuilder,
+    policy: &RetryPolicy,
+) -> Result<Vec<T>, Error> {
+    let (client, request) = builder.build_split();
+    let mut request = request.map_err(|e| Error::NetworkRequest(e.to_string()))?;
+    let mut visited = HashSet::new();
+    let mut items = Vec::new();
+    loop {
+        let page = RequestBuilder::from_parts(client.clone(), clone_get(&request));
+        let body = send_raw_with_retry(page, policy)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| Error::NetworkRequest(e.to_string()))?;
+        let (page_items, next) = parse_listing::<T>(&body)?.into_parts();
+        items.extend(page_items);
+        match next {
+            Some(next) => request = next_page_request(&request, &next, &mut visited)?,
+            None => return Ok(items),
+        }
+    }
+}
+
+/// Cache validators of a response to a conditional GET.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Validators {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+}
+
+impl Validators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            etag: headers.get(ETAG).cloned(),
+            last_modified: headers.get(LAST_MODIFIED).cloned(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Prefer `If-None-Match`; `If-Modified-Since` is only a fallback for
+    /// servers that send no `ETag`.
+    fn apply(&self, headers: &mut HeaderMap) {
+        if let Some(etag) = &self.etag {
+            headers.insert(IF_NONE_MATCH, etag.clone());
+        } else if let Some(last_modified) = &self.last_modified {
+            headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+    }
+}
+
+/// Last body returned by a conditional GET, with its validators. The body
+/// is kept so that an unchanged page of a paginated listing can still be
+/// returned when another page changed.
+struct CachedResponse {
+    validators: Validators,
+    body: Vec<u8>,
+}
+
+/// Cached responses keyed by `cache_key`. Grows by one entry per polled page
+/// and signed-in user.
+static RESPONSE_CACHE: LazyLock<Mutex<HashMap<String, CachedResponse>>> =
+    LazyLock::new(Default::default);
+
+/// URL plus a hash of the `Authorization` header: listings differ per user,
+/// so a validator from one account must never be sent for another. The
+/// token itself is not kept.
+fn cache_key(request: &Request) -> String {
+    let mut hasher = DefaultHasher::new();
+    request.headers().get(AUTHORIZATION).hash(&mut hasher);
+    format!("{:016x} {}", hasher.finish(), request.url())
+}
+
+/// One page fetched by `send_cached`.
+struct CachedPage {
+    key: String,
+    body: Vec<u8>,
+    /// Validators of a fresh (200) response, `None` when the server answered
+    /// 304 and `body` came from the cache.
+    fresh: Option<Validators>,
+}
+
+impl CachedPage {
+    fn remember(self, cache: &mut HashMap<String, CachedResponse>) {
+        match self.fresh {
+            Some(validators) if validators.is_empty() => {
+                cache.remove(&self.key);
+            }
+            Some(validators) => {
+                cache.insert(
+                    self.key,
+                    CachedResponse {
+                        validators,
+                        body: self.body,
+                    },
+                );
+            }
+            None => {}
+        }
+    }
+}
+
+/// Send `request` with the validators of its cached response, if any, and
+/// return either the fresh body or, on 304, the cached one. The cache is not
+/// updated here; see `CachedPage::remember`.
+async fn send_cached(
+    client: &Client,
+    mut request: Request,
+    policy: &RetryPolicy,
+) -> Result<CachedPage, Error> {
+    let key = cache_key(&request);
+    let cached = RESPONSE_CACHE
+        .lock()
+        .unwrap()
+        .get(&key)
+        .map(|cached| (cached.validators.clone(), cached.body.clone()));
+    if let Some((validators, _)) = &cached {
+        validators.apply(request.headers_mut());
+    }
+    let builder = RequestBuilder::from_parts(client.clone(), request);
+    match send_raw_with_retry(builder, policy).await {
+        Ok(resp) => {
+            let validators = Validators::from_headers(resp.headers());
+            let body = resp
+                .bytes()
+                .await
+                .map_err(|e| Error::NetworkRequest(e.to_string()))?;
+            Ok(CachedPage {
+                key,
+                body: body.to_vec(),
+                fresh: Some(validators),
+            })
+        }
+        Err(Error::Api { status: 304, .. }) if cached.is_some() => {
+            let (_, body) = cached.expect("checked by the match guard");
+            Ok(CachedPage {
+                key,
+                body,
+                fresh: None,
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// `send_listing_with_retry` for polling. Every page is requested with the
+/// validators of its last response; returns `Ok(None)` when all of them
+/// answer 304 Not Modified, i.e. the listing is identical to the last one
+/// this function returned for the same URL and user. The caller keeps
+/// using that.
+pub(crate) async fn send_listing_if_modified<T: DeserializeOwned>(
+    builder: RequestBuilder,
+    policy: &RetryPolicy,
+) -> Result<Option<Vec<T>>, Error> {
+    let (client, request) = builder.build_split();
+    let mut request = request.map_err(|e| Error::NetworkRequest(e.to_string()))?;
+    let mut visited = HashSet::new();
+    let mut pages = Vec::new();
+    loop {
+        let page = send_cached(&client, clone_get(&request), policy).await?;
+        // Only the `next` link is needed to walk the pages.
+        let next = parse_listing::<IgnoredAny>(&page.body)?.into_parts().1;
+        pages.push(page);
+        match next {
+            Some(next) => request = next_page_request(&request, &next, &mut visited)?,
+            None => break,
+        }
+    }
+    if pages.iter().all(|page| page.fresh.is_none()) {
+        debug!("Listing not modified since last fetch");
+        return Ok(None);
+    }
+    let mut items = Vec::new();
+    for page in &pages {
+        items.extend(parse_listing::<T>(&page.body)?.into_parts().0);
+    }
+    // Only remember responses once the items they describe made it to the
+    // caller.
+    let mut cache = RESPONSE_CACHE.lock().unwrap();
+    for page in pages {
+        page.remember(&mut cache);
+    }
+    Ok(Some(items))
+}
+
+/// Drop the cached first page of the listing `builder` requests, so that
+/// the next `send_listing_if_modified` for it returns the listing even if
+/// nothing changed. For callers that failed to apply the last one.
+pub(crate) fn forget_listing(builder: RequestBuilder) -> Result<(), Error> {
+    let request = builder
+        .build()
         .map_err(|e| Error::NetworkRequest(e.to_string()))?;
-    let status = resp.status();
-    if status.is_success() {
-        return resp.json::<T>().await.map_err(|e| {
-            error!("Failed to deserialize success response: {e}");
-            Error::Deserialization(e.to_string())
-        });
+    RESPONSE_CACHE.lock().unwrap().remove(&cache_key(&request));
+    Ok(())
+}
+
+async fn deserialize_json<T: DeserializeOwned>(resp: Response) -> Result<T, Error> {
+    resp.json::<T>().await.map_err(|e| {
+        error!("Failed to deserialize success response: {e}");
+        Error::Deserialization(e.to_string())
+    })
+}
+
+/// Header carrying the id given to every request, so that a failure in the
+/// sidecar's log can be found in the server's.
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Log target of the per-request records.
+const HTTP_LOG_TARGET: &str = "api::http";
+
+/// What is logged about one request. It deliberately holds no headers,
+/// query string or body, so neither the token nor project data can reach
+/// the logs or Sentry.
+struct RequestRecord {
+    request_id: Uuid,
+    method: Method,
+    path: String,
+    /// `None` when no response arrived (connection failure, timeout).
+    status: Option<StatusCode>,
+    /// Time until the response headers arrived; bodies are streamed later.
+    latency: Duration,
+    /// Announced body size, when the server sent a `Content-Length`.
+    size: Option<u64>,
+}
+
+impl RequestRecord {
+    fn new(request_id: Uuid, request: &Request) -> Self {
+        Self {
+            request_id,
+            method: request.method().clone(),
+            path: request.url().path().to_string(),
+            status: None,
+            latency: Duration::ZERO,
+            size: None,
+        }
     }
-    Err(error_from_response(status, resp).await)
+
+    fn finish(mut self, response: Option<&Response>, latency: Duration) -> Self {
+        self.status = response.map(Response::status);
+        self.size = response.and_then(Response::content_length);
+        self.latency = latency;
+        self
+    }
+
+    fn log(&self) {
+        info!(
+            target: HTTP_LOG_TARGET,
+            request_id:% = self.request_id,
+            method:% = self.method,
+            path = self.path.as_str(),
+            status:? = self.status.map(|status| status.as_u16()),
+            latency_ms = self.latency.as_millis() as u64,
+            size:? = self.size;
+            "{self}"
+        );
+    }
+}
+
+impl fmt::Display for RequestRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} -> ", self.method, self.path)?;
+        match self.status {
+            Some(status) => write!(f, "{}", status.as_u16())?,
+            None => f.write_str("no response")?,
+        }
+        write!(f, " in {} ms", self.latency.as_millis())?;
+        if let Some(size) = self.size {
+            write!(f, ", {size} bytes")?;
+        }
+        write!(f, " [request {}]", self.request_id)
+    }
+}
+
+/// Send one attempt of a request with a fresh request id and log it.
+pub(crate) async fn send_traced(builder: RequestBuilder) -> Result<Response, reqwest::Error> {
+    let (client, request) = builder.build_split();
+    let mut request = request?;
+    let request_id = Uuid::new_v4();
+    request.headers_mut().insert(
+        REQUEST_ID_HEADER,
+        HeaderValue::from_str(&request_id.to_string()).expect("a UUID is a valid header value"),
+    );
+    let record = RequestRecord::new(request_id, &request);
+    let started = Instant::now();
+    let result = client.execute(request).await;
+    record.finish(result.as_ref().ok(), started.elapsed()).log();
+    result
 }
 
 /// Send a request whose success body is consumed by the caller (e.g. raw bytes).
@@ -71,8 +491,7 @@ pub(crate) async fn send_json<T: DeserializeOwned>(builder: RequestBuilder) -> R
 /// `.bytes()`, stream, etc. Non-2xx responses go through the same status
 /// mapping as `send_json`.
 pub(crate) async fn send_raw(builder: RequestBuilder) -> Result<Response, Error> {
-    let resp = builder
-        .send()
+    let resp = send_traced(builder)
         .await
         .map_err(|e| Error::NetworkRequest(e.to_string()))?;
     let status = resp.status();
@@ -82,6 +501,134 @@ pub(crate) async fn send_raw(builder: RequestBuilder) -> Result<Response, Error>
     Err(error_from_response(status, resp).await)
 }
 
+/// `send_raw` for idempotent requests.
+///
+/// Connection failures, timeouts, 429 and 502/503/504 are retried with
+/// exponential backoff. A `Retry-After` header (delta-seconds) on 429/503
+/// replaces the computed backoff; if it asks for longer than
+/// `policy.max_backoff`, the error is returned immediately instead of
+/// stalling the caller. The error from the last attempt is returned
+/// unchanged, so callers see the same `Error` variants as with `send_raw`.
+///
+/// Requests whose body cannot be cloned (streams) are sent exactly once.
+pub(crate) async fn send_raw_with_retry(
+    builder: RequestBuilder,
+    policy: &RetryPolicy,
+) -> Result<Response, Error> {
+    let mut attempt = 1;
+    loop {
+        let Some(retry_builder) = builder
+            .try_clone()
+            .filter(|_| attempt < policy.max_attempts)
+        else {
+            return send_raw(builder).await;
+        };
+        let delay = match send_traced(retry_builder).await {
+            Ok(resp) if resp.status().is_success() => return Ok(resp),
+            Ok(resp) if is_retryable_status(resp.status()) => {
+                let status = resp.status();
+                let retry_after = honors_retry_after(status)
+                    .then(|| resp.headers().get(RETRY_AFTER))
+                    .flatten();
+                match retry_delay(policy, attempt, retry_after) {
+                    Some(delay) => {
+                        warn!("HTTP {status} on attempt {attempt}, retrying in {delay:?}");
+                        delay
+                    }
+                    None => return Err(error_from_response(status, resp).await),
+                }
+            }
+            Ok(resp) => return Err(error_from_response(resp.status(), resp).await),
+            Err(e) if e.is_connect() || e.is_timeout() => {
+                let delay = policy.backoff(attempt);
+                warn!("Request failed on attempt {attempt} ({e}), retrying in {delay:?}");
+                delay
+            }
+            Err(e) => return Err(Error::NetworkRequest(e.to_string())),
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// How idempotent requests are retried on transient failures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the second attempt; doubled for each attempt after that.
+    pub initial_backoff: Duration,
+    /// Upper bound for both the computed backoff and an honored `Retry-After`.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Send every request exactly once.
+    pub const NEVER: Self = Self {
+        max_attempts: 1,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    /// Backoff before attempt `attempt + 1`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Statuses whose `Retry-After` is honored. 502 and 504 always use the
+/// computed backoff.
+fn honors_retry_after(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    )
+}
+
+/// Delay before retrying after a retryable status, or `None` if the server
+/// asked us to wait longer than the policy allows.
+///
+/// Only the delta-seconds form of `Retry-After` is understood; an HTTP-date
+/// (or any other unparsable value) falls back to the computed backoff.
+fn retry_delay(
+    policy: &RetryPolicy,
+    attempt: u32,
+    retry_after: Option<&reqwest::header::HeaderValue>,
+) -> Option<Duration> {
+    let requested = retry_after
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    match requested {
+        Some(delay) if delay > policy.max_backoff => None,
+        Some(delay) => Some(delay),
+        None => Some(policy.backoff(attempt)),
+    }
+}
+
 /// Drain the failed response body and convert it to a typed `Error`.
 async fn error_from_response(status: StatusCode, resp: Response) -> Error {
     let body = resp.text().await.ok();
@@ -111,7 +658,8 @@ pub(crate) fn map_status_to_error(status: StatusCode, body: Option<&str>) -> Err
         });
     let message = cap_error_message(raw);
     match status.as_u16() {
-        401 | 403 => Error::Unauthorized(message),
+        401 => Error::Unauthorized(message),
+        403 => Error::Forbidden(message),
         404 => Error::NotFound(message),
         409 | 423 => Error::Conflict(message),
         422 => Error::Unprocessable(message),
@@ -147,6 +695,8 @@ fn extract_message(body: Option<&str>) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{get_api_client, test_support::MockSpeleoDb};
+    use common::api_types::ProjectType;
 
     #[test]
     fn map_unauthorized_status_with_json_body() {
@@ -155,9 +705,52 @@ mod tests {
     }
 
     #[test]
-    fn map_forbidden_collapses_to_unauthorized() {
+    fn map_forbidden_status() {
         let err = map_status_to_error(StatusCode::FORBIDDEN, Some(r#"{"error": "nope"}"#));
-        assert!(matches!(err, Error::Unauthorized(ref m) if m == "nope"));
+        assert!(matches!(err, Error::Forbidden(ref m) if m == "nope"));
+    }
+
+    #[test]
+    fn request_record_leaves_out_token_and_query() {
+        let request = get_api_client()
+            .get("https://www.speleodb.org/api/v2/projects/?search=secret")
+            .header(
+                AUTHORIZATION,
+                "Token 0123456789abcdef0123456789abcdef01234567",
+            )
+            .build()
+            .unwrap();
+        let request_id = Uuid::new_v4();
+        let record =
+            RequestRecord::new(request_id, &request).finish(None, Duration::from_millis(42));
+        let logged = record.to_string();
+        assert_eq!(
+            logged,
+            format!("GET /api/v2/projects/ -> no response in 42 ms [request {request_id}]")
+        );
+        assert!(!logged.contains("0123456789abcdef") && !logged.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn every_attempt_carries_its_own_request_id() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(503, "busy");
+        let builder = authenticated(
+            get_api_client().get(v2_url(&mock.instance(), "projects/")),
+            &mock.api_info(),
+        )
+        .unwrap();
+        send_raw_with_retry(builder, &RetryPolicy::default())
+            .await
+            .expect("second attempt succeeds");
+
+        let ids = mock.request_ids();
+        assert_eq!(ids.len(), 2);
+        let ids: Vec<Uuid> = ids
+            .into_iter()
+            .map(|id| id.expect("header sent").parse().expect("a UUID"))
+            .collect();
+        assert_ne!(ids[0], ids[1]);
     }
 
     #[test]
@@ -305,4 +898,533 @@ mod tests {
             "https://intranet.example.com/speleodb/api/v2/projects/abc-123/acquire/"
         );
     }
+
+    // ─── send_json / send_raw against the mock server ──────────────────────
+
+    fn mock_projects_request(mock: &MockSpeleoDb) -> RequestBuilder {
+        let url = v2_url(&mock.instance(), "projects/");
+        authenticated(get_api_client().get(url), &mock.api_info()).unwrap()
+    }
+
+    /// Every status the mapping distinguishes, as produced by a real HTTP
+    /// round-trip rather than a hand-built `StatusCode`.
+    fn status_cases() -> Vec<(u16, &'static str, Error)> {
+        vec![
+            (
+                401,
+                r#"{"error": "bad token"}"#,
+                Error::Unauthorized("bad token".to_string()),
+            ),
+            (
+                403,
+                r#"{"error": "nope"}"#,
+                Error::Forbidden("nope".to_string()),
+            ),
+            (
+                404,
+                r#"{"error": "missing"}"#,
+                Error::NotFound("missing".to_string()),
+            ),
+            (
+                409,
+                r#"{"error": "taken"}"#,
+                Error::Conflict("taken".to_string()),
+            ),
+            (
+                423,
+                r#"{"error": "locked"}"#,
+                Error::Conflict("locked".to_string()),
+            ),
+            (
+                422,
+                r#"{"error": "bad"}"#,
+                Error::Unprocessable("bad".to_string()),
+            ),
+            (
+                429,
+                "slow down",
+                Error::Api {
+                    status: 429,
+                    message: "slow down".to_string(),
+                },
+            ),
+            (
+                500,
+                r#"{"error": "boom"}"#,
+                Error::Api {
+                    status: 500,
+                    message: "boom".to_string(),
+                },
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn send_json_maps_every_error_status_from_the_wire() {
+        let mock = MockSpeleoDb::start().await;
+        for (status, body, expected) in status_cases() {
+            mock.fail_next(status, body);
+            let err = send_json::<serde_json::Value>(mock_projects_request(&mock))
+                .await
+                .expect_err("injected failure must surface as an error");
+            assert_eq!(err, expected, "HTTP {status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn send_raw_maps_every_error_status_from_the_wire() {
+        let mock = MockSpeleoDb::start().await;
+        for (status, body, expected) in status_cases() {
+            mock.fail_next(status, body);
+            let err = send_raw(mock_projects_request(&mock))
+                .await
+                .expect_err("injected failure must surface as an error");
+            assert_eq!(err, expected, "HTTP {status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn send_json_caps_oversized_html_body_from_the_wire() {
+        let mock = MockSpeleoDb::start().await;
+        let html = "<!DOCTYPE html><html><body>".to_string() + &"x".repeat(8192) + "</body></html>";
+        mock.fail_next(502, &html);
+        let err = send_json::<serde_json::Value>(mock_projects_request(&mock))
+            .await
+            .expect_err("502 must fail");
+        let Error::Api { status, message } = err else {
+            panic!("expected Api variant for 502, got: {err:?}");
+        };
+        assert_eq!(status, 502);
+        assert!(message.ends_with("…[truncated]"));
+    }
+
+    #[tokio::test]
+    async fn send_json_success_deserializes_body() {
+        let mock = MockSpeleoDb::start().await;
+        let projects: Vec<serde_json::Value> = send_json(mock_projects_request(&mock))
+            .await
+            .expect("empty project list must deserialize");
+        assert!(projects.is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_json_malformed_success_body_returns_deserialization() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(200, "this is not json");
+        let err = send_json::<serde_json::Value>(mock_projects_request(&mock))
+            .await
+            .expect_err("malformed body must fail");
+        assert!(
+            matches!(err, Error::Deserialization(_)),
+            "expected Deserialization, got: {err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_raw_success_returns_response() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(200, "raw payload");
+        let resp = send_raw(mock_projects_request(&mock))
+            .await
+            .expect("2xx must pass through");
+        assert_eq!(resp.bytes().await.unwrap().as_ref(), b"raw payload");
+    }
+
+    #[tokio::test]
+    async fn send_json_unreachable_host_returns_network_request() {
+        // Bind then drop a listener so the port is known to be closed.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let instance = Url::parse(&format!("http://{addr}")).unwrap();
+        let req = get_api_client().get(v2_url(&instance, "projects/"));
+        let err = send_json::<serde_json::Value>(req)
+            .await
+            .expect_err("closed port must fail");
+        assert!(
+            matches!(err, Error::NetworkRequest(_)),
+            "expected NetworkRequest, got: {err:?}"
+        );
+    }
+
+    // ─── retry ─────────────────────────────────────────────────────────────
+
+    fn fast_retry_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(20),
+        }
+    }
+
+    #[test]
+    fn retry_backoff_doubles_and_is_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn retry_delay_prefers_retry_after_seconds() {
+        let policy = RetryPolicy::default();
+        let header = reqwest::header::HeaderValue::from_static("2");
+        assert_eq!(
+            retry_delay(&policy, 1, Some(&header)),
+            Some(Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn retry_delay_gives_up_when_retry_after_exceeds_max_backoff() {
+        let policy = RetryPolicy::default();
+        let header = reqwest::header::HeaderValue::from_static("3600");
+        assert_eq!(retry_delay(&policy, 1, Some(&header)), None);
+    }
+
+    #[test]
+    fn retry_delay_falls_back_to_backoff_for_http_date() {
+        let policy = RetryPolicy::default();
+        let header = reqwest::header::HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT");
+        assert_eq!(
+            retry_delay(&policy, 2, Some(&header)),
+            Some(policy.backoff(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn send_json_with_retry_recovers_from_transient_failures() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(503, r#"{"error": "maintenance"}"#);
+        mock.fail_next(429, r#"{"error": "slow down"}"#);
+
+        let projects: Vec<serde_json::Value> =
+            send_json_with_retry(mock_projects_request(&mock), &fast_retry_policy())
+                .await
+                .expect("third attempt must succeed");
+
+        assert!(projects.is_empty());
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 3);
+    }
+
+    #[tokio::test]
+    async fn send_json_with_retry_returns_last_error_after_final_attempt() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(502, "first");
+        mock.fail_next(504, "second");
+        mock.fail_next(503, r#"{"error": "still down"}"#);
+
+        let err = send_json_with_retry::<serde_json::Value>(
+            mock_projects_request(&mock),
+            &fast_retry_policy(),
+        )
+        .await
+        .expect_err("all attempts fail");
+
+        assert_eq!(
+            err,
+            Error::Api {
+                status: 503,
+                message: "still down".to_string()
+            }
+        );
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 3);
+    }
+
+    #[tokio::test]
+    async fn send_raw_with_retry_does_not_retry_client_errors() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(404, r#"{"error": "missing"}"#);
+
+        let err = send_raw_with_retry(mock_projects_request(&mock), &fast_retry_policy())
+            .await
+            .expect_err("404 must fail");
+
+        assert_eq!(err, Error::NotFound("missing".to_string()));
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 1);
+    }
+
+    #[tokio::test]
+    async fn send_raw_with_retry_honors_retry_after() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next_with_headers(429, "", &[("Retry-After", "0")]);
+        // A backoff this long would time the test out if Retry-After were
+        // ignored.
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(60),
+        };
+
+        let resp = tokio::time::timeout(
+            Duration::from_secs(5),
+            send_raw_with_retry(mock_projects_request(&mock), &policy),
+        )
+        .await
+        .expect("Retry-After: 0 must override the backoff")
+        .expect("second attempt must succeed");
+
+        assert!(resp.status().is_success());
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 2);
+    }
+
+    #[tokio::test]
+    async fn send_raw_with_retry_gives_up_on_long_retry_after() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next_with_headers(503, "down", &[("Retry-After", "3600")]);
+
+        let err = send_raw_with_retry(mock_projects_request(&mock), &fast_retry_policy())
+            .await
+            .expect_err("long Retry-After must not be waited out");
+
+        assert_eq!(
+            err,
+            Error::Api {
+                status: 503,
+                message: "down".to_string()
+            }
+        );
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 1);
+    }
+
+    #[tokio::test]
+    async fn send_raw_with_retry_ignores_retry_after_on_bad_gateway() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next_with_headers(502, "", &[("Retry-After", "3600")]);
+
+        let resp = send_raw_with_retry(mock_projects_request(&mock), &fast_retry_policy())
+            .await
+            .expect("502 must be retried with the computed backoff");
+
+        assert!(resp.status().is_success());
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 2);
+    }
+
+    #[tokio::test]
+    async fn send_listing_if_modified_skips_unchanged_listings() {
+        let mock = MockSpeleoDb::start().await;
+        let policy = fast_retry_policy();
+
+        let first: Option<Vec<serde_json::Value>> =
+            send_listing_if_modified(mock_projects_request(&mock), &policy)
+                .await
+                .unwrap();
+        assert_eq!(first, Some(Vec::new()));
+
+        let unchanged: Option<Vec<serde_json::Value>> =
+            send_listing_if_modified(mock_projects_request(&mock), &policy)
+                .await
+                .unwrap();
+        assert_eq!(unchanged, None);
+
+        mock.add_project("Compass", ProjectType::Compass);
+        let changed: Option<Vec<serde_json::Value>> =
+            send_listing_if_modified(mock_projects_request(&mock), &policy)
+                .await
+                .unwrap();
+        assert_eq!(changed.map(|projects| projects.len()), Some(1));
+    }
+
+    #[tokio::test]
+    async fn send_listing_if_modified_detects_changes_on_later_pages() {
+        let mock = MockSpeleoDb::start().await;
+        let policy = fast_retry_policy();
+        mock.add_project("First", ProjectType::Compass);
+        let second = mock.add_project("Second", ProjectType::Compass);
+        mock.paginate_projects(1);
+
+        let first: Option<Vec<serde_json::Value>> =
+            send_listing_if_modified(mock_projects_request(&mock), &policy)
+                .await
+                .unwrap();
+        assert_eq!(first.map(|projects| projects.len()), Some(2));
+        let unchanged: Option<Vec<serde_json::Value>> =
+            send_listing_if_modified(mock_projects_request(&mock), &policy)
+                .await
+                .unwrap();
+        assert_eq!(unchanged, None);
+
+        // Only page 2 changes; page 1 still answers 304 and comes from the cache.
+        mock.lock_project_as(second, "other@example.com");
+        let changed: Vec<serde_json::Value> =
+            send_listing_if_modified(mock_projects_request(&mock), &policy)
+                .await
+                .unwrap()
+                .expect("a change on page 2 must return the whole listing");
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed[0]["name"], "First");
+        assert_eq!(changed[1]["active_mutex"]["user"], "other@example.com");
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 6);
+    }
+
+    #[tokio::test]
+    async fn send_listing_with_retry_follows_next_links() {
+        let mock = MockSpeleoDb::start().await;
+        for name in ["A", "B", "C", "D", "E"] {
+            mock.add_project(name, ProjectType::Compass);
+        }
+        mock.paginate_projects(2);
+
+        let projects: Vec<serde_json::Value> =
+            send_listing_with_retry(mock_projects_request(&mock), &fast_retry_policy())
+                .await
+                .unwrap();
+
+        let names: Vec<&str> = projects
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["A", "B", "C", "D", "E"]);
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 3);
+    }
+
+    #[tokio::test]
+    async fn send_listing_with_retry_accepts_bare_arrays() {
+        let mock = MockSpeleoDb::start().await;
+        mock.add_project("A", ProjectType::Compass);
+
+        let projects: Vec<serde_json::Value> =
+            send_listing_with_retry(mock_projects_request(&mock), &fast_retry_policy())
+                .await
+                .unwrap();
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 1);
+    }
+
+    #[test]
+    fn next_page_request_stays_on_host_and_never_loops() {
+        let current = get_api_client()
+            .get("https://speleodb.example.com/api/v2/projects/")
+            .header(AUTHORIZATION, "Token secret")
+            .build()
+            .unwrap();
+        let mut visited = HashSet::new();
+
+        let next = next_page_request(&current, "?page=2", &mut visited).unwrap();
+        assert_eq!(
+            next.url().as_str(),
+            "https://speleodb.example.com/api/v2/projects/?page=2"
+        );
+        assert_eq!(next.headers().get(AUTHORIZATION).unwrap(), "Token secret");
+
+        assert!(
+            next_page_request(&current, "https://evil.example.com/?page=2", &mut visited).is_err()
+        );
+        assert!(next_page_request(&next, "/api/v2/projects/", &mut visited).is_err());
+    }
+
+    #[test]
+    fn next_page_request_keeps_the_origin_of_links_from_behind_a_proxy() {
+        let current = get_api_client()
+            .get("https://speleodb.example.com/api/v2/projects/")
+            .build()
+            .unwrap();
+        let mut visited = HashSet::new();
+
+        let next = next_page_request(
+            &current,
+            "http://speleodb.example.com:8000/api/v2/projects/?page=2",
+            &mut visited,
+        )
+        .unwrap();
+
+        assert_eq!(
+            next.url().as_str(),
+            "https://speleodb.example.com/api/v2/projects/?page=2"
+        );
+    }
+
+    #[test]
+    fn expected_sha256_reads_repr_digest_then_digest() {
+        let digest: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let encoded = BASE64_STANDARD.encode(digest);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(expected_sha256(&headers), None);
+
+        headers.insert(
+            "digest",
+            HeaderValue::from_str(&format!("MD5=AAAA, SHA-256={encoded}")).unwrap(),
+        );
+        assert_eq!(expected_sha256(&headers), Some(digest));
+
+        headers.insert(
+            "repr-digest",
+            HeaderValue::from_str(&format!("sha-512=:AAAA:, sha-256=:{encoded}:")).unwrap(),
+        );
+        headers.insert("digest", HeaderValue::from_static("SHA-256=AAAA"));
+        assert_eq!(expected_sha256(&headers), Some(digest));
+
+        headers.insert("repr-digest", HeaderValue::from_static("sha-256=:AAAA:"));
+        assert_eq!(expected_sha256(&headers), None);
+    }
+
+    #[test]
+    fn cache_key_differs_per_user() {
+        let url = Url::parse("https://speleodb.example.com/api/v2/projects/").unwrap();
+        let request_as = |token: &str| {
+            get_api_client()
+                .get(url.clone())
+                .header(AUTHORIZATION, format!("Token {token}"))
+                .build()
+                .unwrap()
+        };
+        assert_eq!(
+            cache_key(&request_as("alice")),
+            cache_key(&request_as("alice"))
+        );
+        assert_ne!(
+            cache_key(&request_as("alice")),
+            cache_key(&request_as("bob"))
+        );
+        assert!(!cache_key(&request_as("alice")).contains("alice"));
+    }
+
+    #[test]
+    fn validators_prefer_etag_over_last_modified() {
+        let mut headers = HeaderMap::new();
+        Validators {
+            etag: Some(HeaderValue::from_static("\"abc\"")),
+            last_modified: Some(HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT")),
+        }
+        .apply(&mut headers);
+        assert_eq!(headers.get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+        assert!(headers.get(IF_MODIFIED_SINCE).is_none());
+
+        let mut headers = HeaderMap::new();
+        Validators {
+            etag: None,
+            last_modified: Some(HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT")),
+        }
+        .apply(&mut headers);
+        assert_eq!(
+            headers.get(IF_MODIFIED_SINCE).unwrap(),
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_json_with_retry_retries_connection_failures() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let instance = Url::parse(&format!("http://{addr}")).unwrap();
+        let req = get_api_client().get(v2_url(&instance, "projects/"));
+        let err = send_json_with_retry::<serde_json::Value>(req, &fast_retry_policy())
+            .await
+            .expect_err("closed port must fail on every attempt");
+        assert!(
+            matches!(err, Error::NetworkRequest(_)),
+            "expected NetworkRequest, got: {err:?}"
+        );
+    }
 }
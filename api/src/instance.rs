@@ -0,0 +1,168 @@
+//! Probing an instance URL before signing in to it: is it reachable, is its
+//! certificate trusted, does it serve the v2 API, and which version and
+//! optional features does it report.
+
+use common::{ServerCapabilities, api_types::InstanceProbe};
+use log::info;
+use reqwest::{Response, StatusCode};
+use url::Url;
+
+use crate::{get_api_client, http};
+
+/// Probe `instance` without credentials. Never retried: the auth screen
+/// shows the result as soon as the instance field is left.
+///
+/// `server/info/` reports the version and features. Servers that predate
+/// it answer 404 there, so the token endpoint is asked next: a v2 server
+/// rejects the anonymous request with 401 or 403, anything else is not
+/// SpeleoDB.
+pub async fn probe_instance(instance: Url) -> InstanceProbe {
+    info!("Probing instance: {instance}");
+    let probe = match get(&instance, "server/info/").await {
+        Ok(resp) if resp.status().is_success() => match resp.json::<ServerCapabilities>().await {
+            Ok(capabilities) => InstanceProbe::Available(capabilities),
+            Err(_) => InstanceProbe::NotSpeleoDb,
+        },
+        Ok(resp) if is_legacy_answer(resp.status()) => probe_legacy(&instance).await,
+        Ok(resp) => unexpected_status(resp.status()),
+        Err(probe) => probe,
+    };
+    info!("Instance {instance} probed: {probe:?}");
+    probe
+}
+
+/// Negotiate capabilities for a sign-in. Probe failures fall back to the
+/// defaults: the credentials were just accepted, so the instance is there.
+pub(crate) async fn negotiate_capabilities(instance: &Url) -> ServerCapabilities {
+    match probe_instance(instance.clone()).await {
+        InstanceProbe::Available(capabilities) => capabilities,
+        _ => ServerCapabilities::default(),
+    }
+}
+
+async fn probe_legacy(instance: &Url) -> InstanceProbe {
+    match get(instance, "user/auth-token/").await {
+        Ok(resp)
+            if matches!(
+                resp.status(),
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+            ) =>
+        {
+            InstanceProbe::Available(ServerCapabilities::default())
+        }
+        Ok(resp) => unexpected_status(resp.status()),
+        Err(probe) => probe,
+    }
+}
+
+/// Statuses of a server without the `server/info/` endpoint, or one that
+/// only shows it to signed-in users.
+fn is_legacy_answer(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::NOT_FOUND | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    )
+}
+
+fn unexpected_status(status: StatusCode) -> InstanceProbe {
+    if status.is_server_error() {
+        InstanceProbe::Unreachable(format!("The server answered with HTTP {status}"))
+    } else {
+        InstanceProbe::NotSpeleoDb
+    }
+}
+
+async fn get(instance: &Url, path: &str) -> Result<Response, InstanceProbe> {
+    let builder = get_api_client().get(http::v2_url(instance, path));
+    http::send_traced(builder)
+        .await
+        .map_err(|e| transport_failure(&e))
+}
+
+/// Tell a failed TLS handshake apart from a server that can't be reached.
+fn transport_failure(err: &reqwest::Error) -> InstanceProbe {
+    let mut cause: &dyn std::error::Error = err;
+    while let Some(source) = cause.source() {
+        cause = source;
+        if let Some(tls) = tls_failure(cause) {
+            return InstanceProbe::TlsError(tls.to_string());
+        }
+    }
+    InstanceProbe::Unreachable(cause.to_string())
+}
+
+/// The rustls error behind `cause`, if any. The connector wraps it in I/O
+/// errors, whose `source` skips the wrapped error, so those are unwrapped
+/// by hand.
+fn tls_failure<'a>(cause: &'a (dyn std::error::Error + 'static)) -> Option<&'a rustls::Error> {
+    match cause.downcast_ref::<std::io::Error>() {
+        Some(e) => e.get_ref().and_then(|inner| tls_failure(inner)),
+        None => cause.downcast_ref::<rustls::Error>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockSpeleoDb;
+
+    #[tokio::test]
+    async fn probe_reports_server_capabilities() {
+        let mock = MockSpeleoDb::start().await;
+        let InstanceProbe::Available(capabilities) = probe_instance(mock.instance()).await else {
+            panic!("the mock is a SpeleoDB instance");
+        };
+        assert!(capabilities.version.is_some());
+        assert_eq!(capabilities.features, ["project_history"]);
+        assert_eq!(mock.request_count("GET", "/api/v2/user/auth-token/"), 0);
+    }
+
+    #[tokio::test]
+    async fn probe_falls_back_to_token_endpoint_on_older_servers() {
+        let mock = MockSpeleoDb::start().await;
+        mock.fail_next(404, "Not found");
+        assert_eq!(
+            probe_instance(mock.instance()).await,
+            InstanceProbe::Available(ServerCapabilities::default())
+        );
+        assert_eq!(mock.request_count("GET", "/api/v2/user/auth-token/"), 1);
+    }
+
+    #[tokio::test]
+    async fn probe_rejects_servers_without_the_v2_api() {
+        let mock = MockSpeleoDb::start().await;
+        let wrong_prefix = mock.instance().join("not-speleodb/").unwrap();
+        assert_eq!(
+            probe_instance(wrong_prefix).await,
+            InstanceProbe::NotSpeleoDb
+        );
+
+        mock.fail_next(503, "down");
+        assert!(matches!(
+            probe_instance(mock.instance()).await,
+            InstanceProbe::Unreachable(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn probe_reports_transport_failures() {
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        let unreachable: Url = format!("http://{closed}/").parse().unwrap();
+        assert!(matches!(
+            probe_instance(unreachable).await,
+            InstanceProbe::Unreachable(_)
+        ));
+
+        // The mock speaks plain HTTP, so a TLS handshake with it fails.
+        let mock = MockSpeleoDb::start().await;
+        let mut https = mock.instance();
+        https.set_scheme("https").unwrap();
+        assert!(matches!(
+            probe_instance(https).await,
+            InstanceProbe::TlsError(_)
+        ));
+    }
+}
@@ -1,12 +1,19 @@
 pub mod auth;
+mod client;
 mod http;
+pub mod instance;
 pub mod project;
 
+pub use client::{ApiFuture, SpeleoDbApi, SpeleoDbClient, TransferProgress};
+
 #[cfg(test)]
 mod test_support;
 
-use reqwest::Client;
-use std::{sync::LazyLock, time::Duration};
+use common::{ClientSettings, Error};
+use http::RetryPolicy;
+use log::info;
+use reqwest::{Certificate, Client, Proxy};
+use std::sync::{LazyLock, RwLock};
 
 #[cfg(debug_assertions)]
 pub const API_BASE_URL: &str = "https://stage.speleodb.org";
@@ -17,21 +24,102 @@ const API_USER_AGENT: &str = concat!(
     env!("CARGO_PKG_VERSION")
 );
 
-static API_CLIENT: LazyLock<Client> = LazyLock::new(|| {
-    Client::builder()
-        .user_agent(API_USER_AGENT)
-        .timeout(Duration::from_secs(10))
-        .build()
-        .expect("Failed to build API client")
+/// The shared client together with the settings it was built from.
+static API_CLIENT: LazyLock<RwLock<(ClientSettings, Client)>> = LazyLock::new(|| {
+    let settings = ClientSettings::default();
+    let client = build_client(&settings).expect("Failed to build API client");
+    RwLock::new((settings, client))
 });
 
 fn get_api_client() -> Client {
-    API_CLIENT.clone()
+    API_CLIENT
+        .read()
+        .expect("API client lock poisoned")
+        .1
+        .clone()
+}
+
+/// Settings the shared client is currently built from.
+pub fn client_settings() -> ClientSettings {
+    API_CLIENT
+        .read()
+        .expect("API client lock poisoned")
+        .0
+        .clone()
+}
+
+/// Rebuild the shared client from `settings`. Unchanged settings are a no-op;
+/// invalid ones are rejected and the current client is kept. Requests already
+/// in flight finish on the client they started with.
+pub fn set_client_settings(settings: &ClientSettings) -> Result<(), Error> {
+    if client_settings() == *settings {
+        return Ok(());
+    }
+    let client = build_client(settings)?;
+    *API_CLIENT.write().expect("API client lock poisoned") = (settings.clone(), client);
+    info!(
+        "HTTP client rebuilt (proxy: {}, extra root certificates: {}, timeouts: {}s connect / {}s request, retries: {})",
+        settings
+            .proxy_url
+            .as_ref()
+            .map_or("system", |url| url.as_str()),
+        settings.root_certificates_pem.is_some(),
+        settings.connect_timeout_secs,
+        settings.request_timeout_secs,
+        settings.max_retries
+    );
+    Ok(())
+}
+
+fn build_client(settings: &ClientSettings) -> Result<Client, Error> {
+    if settings.connect_timeout_secs == 0 || settings.request_timeout_secs == 0 {
+        return Err(Error::InvalidClientSettings(
+            "timeouts must be at least one second".to_string(),
+        ));
+    }
+    let mut builder = Client::builder()
+        .user_agent(API_USER_AGENT)
+        .connect_timeout(settings.connect_timeout())
+        .timeout(settings.request_timeout());
+    if let Some(proxy_url) = &settings.proxy_url {
+        let proxy = Proxy::all(proxy_url.as_str())
+            .map_err(|e| Error::InvalidClientSettings(format!("proxy URL {proxy_url}: {e}")))?;
+        builder = builder.proxy(proxy);
+    }
+    if let Some(pem) = &settings.root_certificates_pem {
+        let certificates = Certificate::from_pem_bundle(pem.as_bytes())
+            .map_err(|e| Error::InvalidClientSettings(format!("root certificates: {e}")))?;
+        if certificates.is_empty() {
+            return Err(Error::InvalidClientSettings(
+                "no certificates found in the root certificate PEM".to_string(),
+            ));
+        }
+        builder = builder.tls_certs_merge(certificates);
+    }
+    builder
+        .build()
+        .map_err(|e| Error::InvalidClientSettings(e.to_string()))
+}
+
+/// Retry policy for idempotent requests (GETs and mutex acquire/release),
+/// from the current client settings. Uploads and project creation are never
+/// retried.
+fn retry_policy() -> RetryPolicy {
+    let max_retries = API_CLIENT
+        .read()
+        .expect("API client lock poisoned")
+        .0
+        .max_retries;
+    RetryPolicy {
+        max_attempts: max_retries.saturating_add(1),
+        ..RetryPolicy::default()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::API_USER_AGENT;
+    use super::*;
+    use crate::test_support::MockSpeleoDb;
 
     #[test]
     fn api_user_agent_has_expected_format() {
@@ -43,4 +131,58 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn build_client_rejects_invalid_settings() {
+        let zero_timeout = ClientSettings {
+            connect_timeout_secs: 0,
+            ..ClientSettings::default()
+        };
+        let not_pem = ClientSettings {
+            root_certificates_pem: Some("not a certificate".to_string()),
+            ..ClientSettings::default()
+        };
+        for settings in [zero_timeout, not_pem] {
+            assert!(
+                matches!(
+                    build_client(&settings),
+                    Err(Error::InvalidClientSettings(_))
+                ),
+                "{settings:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_settings_keep_the_current_client() {
+        let invalid = ClientSettings {
+            request_timeout_secs: 0,
+            ..ClientSettings::default()
+        };
+        assert!(set_client_settings(&invalid).is_err());
+        assert_eq!(client_settings(), ClientSettings::default());
+    }
+
+    #[tokio::test]
+    async fn configured_proxy_receives_requests() {
+        // The mock routes on the request path, so it also works as a plain
+        // HTTP proxy for requests aimed at a host that does not exist.
+        let mock = MockSpeleoDb::start().await;
+        let settings = ClientSettings {
+            proxy_url: Some(mock.api_info().instance().clone()),
+            ..ClientSettings::default()
+        };
+        let client = build_client(&settings).expect("proxy settings are valid");
+        let unreachable_instance: url::Url = "http://speleodb.invalid/".parse().unwrap();
+        let request = http::authenticated(
+            client.get(http::v2_url(&unreachable_instance, "projects/")),
+            &mock.api_info(),
+        )
+        .unwrap();
+
+        let projects: Vec<common::api_types::ProjectInfo> = http::send_json(request)
+            .await
+            .expect("request goes through the proxy");
+        assert!(projects.is_empty());
+    }
 }
@@ -5,15 +5,39 @@ use std::{
 
 use common::{
     ApiInfo, Error,
-    api_types::{ProjectInfo, ProjectSaveResult, ProjectType},
+    api_types::{
+        CommitInfo, Page, ProjectInfo, ProjectMetadata, ProjectPermissions, ProjectSaveResult,
+        ProjectType, TeamPermission, UserPermission,
+    },
 };
+use futures_util::StreamExt;
 use log::{error, info, warn};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
-use crate::{get_api_client, http};
+pub use tokio_util::sync::CancellationToken;
 
-const PROJECT_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
-const PROJECT_UPLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+use crate::{get_api_client, http, retry_policy};
+
+/// Commits requested per page of project history.
+pub const PROJECT_COMMITS_PAGE_SIZE: u32 = 25;
+/// Longest a download may go without receiving a byte before it is
+/// abandoned. The total duration is only bounded by
+/// `PROJECT_DOWNLOAD_MAX_TIMEOUT`, so large projects on slow links finish.
+const PROJECT_DOWNLOAD_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Hard ceiling on a download, headers and body included.
+const PROJECT_DOWNLOAD_MAX_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+/// Allowance for an upload before the payload size is taken into account.
+const PROJECT_UPLOAD_BASE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Slowest sustained uplink an upload should still complete on (satellite
+/// links from the field can be this slow).
+const PROJECT_UPLOAD_MIN_BYTES_PER_SEC: u64 = 16 * 1024;
+/// Hard ceiling so a stalled upload is eventually abandoned.
+const PROJECT_UPLOAD_MAX_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+/// Read size for the streamed upload body; also the progress granularity.
+const PROJECT_UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
 
 /// HTTP status used by the v2 upload endpoint when the working copy already
 /// matches the latest server commit. We surface this as `NoChanges` rather
@@ -58,7 +82,7 @@ pub async fn acquire_project_mutex(
         &format!("projects/{project_id}/acquire/"),
     );
     let req = http::authenticated(get_api_client().post(url), api_info)?;
-    match http::send_json(req).await {
+    match http::send_json_with_retry(req, &retry_policy()).await {
         Ok(info) => Ok(info),
         Err(Error::Conflict(_)) => {
             warn!("Mutex already locked by another user for project: {project_id}");
@@ -78,18 +102,49 @@ pub async fn release_project_mutex(
         &format!("projects/{project_id}/release/"),
     );
     let req = http::authenticated(get_api_client().post(url), api_info)?;
-    http::send_json(req).await
+    http::send_json_with_retry(req, &retry_policy()).await
 }
 
 pub async fn fetch_projects(api_info: &ApiInfo) -> Result<Vec<ProjectInfo>, Error> {
     let url = http::v2_url(api_info.instance(), "projects/");
     info!("Fetching projects from server: {url}");
     let req = http::authenticated(get_api_client().get(url), api_info)?;
-    let mut projects: Vec<ProjectInfo> = http::send_json(req).await?;
+    let mut projects: Vec<ProjectInfo> =
+        http::send_listing_with_retry(req, &retry_policy()).await?;
     retain_compass_projects(&mut projects);
     Ok(projects)
 }
 
+/// `fetch_projects` for polling: returns `Ok(None)` when the listing has not
+/// changed since the last call that returned `Some`, without downloading it
+/// again.
+pub async fn fetch_projects_if_modified(
+    api_info: &ApiInfo,
+) -> Result<Option<Vec<ProjectInfo>>, Error> {
+    let url = http::v2_url(api_info.instance(), "projects/");
+    info!("Polling projects from server: {url}");
+    let req = http::authenticated(get_api_client().get(url), api_info)?;
+    let projects: Option<Vec<ProjectInfo>> =
+        http::send_listing_if_modified(req, &retry_policy()).await?;
+    Ok(projects.map(|mut projects| {
+        retain_compass_projects(&mut projects);
+        projects
+    }))
+}
+
+/// Make the next `fetch_projects_if_modified` return the listing even if it
+/// is unchanged, for a caller that failed to apply the last one.
+pub fn forget_projects_listing(api_info: &ApiInfo) {
+    let url = http::v2_url(api_info.instance(), "projects/");
+    // Without a token there was no poll, so nothing is cached.
+    let Ok(req) = http::authenticated(get_api_client().get(url), api_info) else {
+        return;
+    };
+    if let Err(e) = http::forget_listing(req) {
+        warn!("Failed to forget the cached project listing: {e}");
+    }
+}
+
 fn retain_compass_projects(projects: &mut Vec<ProjectInfo>) {
     projects.retain(|p| p.project_type.is_compass());
 }
@@ -101,60 +156,289 @@ pub async fn fetch_project_info(
     let url = http::v2_url(api_info.instance(), &format!("projects/{project_id}/"));
     info!("Fetching project info from server: {url}");
     let req = http::authenticated(get_api_client().get(url), api_info)?;
-    http::send_json(req).await
+    http::send_json_with_retry(req, &retry_policy()).await
+}
+
+/// Replace the name, description, country and coordinates of a project.
+///
+/// Empty or missing coordinates are sent as `null`, clearing them on the
+/// server. The request sets absolute values, so it is safe to retry.
+pub async fn update_project(
+    api_info: &ApiInfo,
+    project_id: Uuid,
+    metadata: &ProjectMetadata,
+) -> Result<ProjectInfo, Error> {
+    info!("Updating metadata of project: {project_id}");
+    let url = http::v2_url(api_info.instance(), &format!("projects/{project_id}/"));
+    let coordinate = |value: &Option<String>| value.clone().filter(|s| !s.is_empty());
+    let body = serde_json::json!({
+        "name": metadata.name,
+        "description": metadata.description,
+        "country": metadata.country,
+        "latitude": coordinate(&metadata.latitude),
+        "longitude": coordinate(&metadata.longitude),
+    });
+    let req = http::authenticated(get_api_client().patch(url).json(&body), api_info)?;
+    http::send_json_with_retry(req, &retry_policy()).await
+}
+
+/// List who has access to a project: users granted a level directly, and
+/// teams whose members all get that level. Any user who can see the project
+/// may list its permissions.
+pub async fn fetch_project_permissions(
+    api_info: &ApiInfo,
+    project_id: Uuid,
+) -> Result<ProjectPermissions, Error> {
+    info!("Fetching permissions of project: {project_id}");
+    let users_url = http::v2_url(
+        api_info.instance(),
+        &format!("projects/{project_id}/permissions/user/"),
+    );
+    let teams_url = http::v2_url(
+        api_info.instance(),
+        &format!("projects/{project_id}/permissions/team/"),
+    );
+    let users_req = http::authenticated(get_api_client().get(users_url), api_info)?;
+    let teams_req = http::authenticated(get_api_client().get(teams_url), api_info)?;
+    let policy = retry_policy();
+    let (users, teams): (Vec<UserPermission>, Vec<TeamPermission>) = tokio::try_join!(
+        http::send_json_with_retry(users_req, &policy),
+        http::send_json_with_retry(teams_req, &policy),
+    )?;
+    Ok(ProjectPermissions { users, teams })
 }
 
+/// Fetch one page of a project's commit history, newest first.
+///
+/// `page` is 1-based and pages hold `PROJECT_COMMITS_PAGE_SIZE` commits;
+/// keep requesting the next page while `Page::has_next` is true.
+pub async fn fetch_project_commits(
+    api_info: &ApiInfo,
+    project_id: Uuid,
+    page: u32,
+) -> Result<Page<CommitInfo>, Error> {
+    let mut url = http::v2_url(
+        api_info.instance(),
+        &format!("projects/{project_id}/commits/"),
+    );
+    url.query_pairs_mut()
+        .append_pair("page", &page.to_string())
+        .append_pair("page_size", &PROJECT_COMMITS_PAGE_SIZE.to_string());
+    info!("Fetching project commits from server: {url}");
+    let req = http::authenticated(get_api_client().get(url), api_info)?;
+    http::send_json_with_retry(req, &retry_policy()).await
+}
+
+/// What a ZIP download wrote to its destination.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadedZip {
+    /// Number of bytes written.
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the bytes written.
+    pub sha256: String,
+}
+
+/// Stream the project's Compass ZIP into `dest`, chunk by chunk.
+///
+/// `on_progress` is called after every chunk with the chunk length and the
+/// total size from `Content-Length` (when the server sends one), the same
+/// shape the updater plugin uses for its download callback.
+///
+/// The archive is hashed as it streams. When the server sends a SHA-256
+/// digest (`Repr-Digest` or `Digest`) a mismatch fails with
+/// `Error::ChecksumMismatch`, and a body shorter than its `Content-Length`
+/// fails with `Error::NetworkRequest`, as does a body that stalls for
+/// `PROJECT_DOWNLOAD_IDLE_TIMEOUT`. On any error `dest` may hold a
+/// partial or corrupt archive that must not be unpacked; the caller owns
+/// cleanup.
 pub async fn download_project_zip(
     api_info: &ApiInfo,
     project_id: Uuid,
-) -> Result<bytes::Bytes, Error> {
+    dest: &mut (impl AsyncWrite + Unpin),
+    on_progress: impl FnMut(usize, Option<u64>),
+) -> Result<DownloadedZip, Error> {
     info!("Downloading project zip for project: {project_id}");
     let url = http::v2_url(
         api_info.instance(),
         &format!("projects/{project_id}/download/compass_zip/"),
     );
+    stream_project_zip(
+        api_info,
+        project_id,
+        url,
+        dest,
+        on_progress,
+        PROJECT_DOWNLOAD_IDLE_TIMEOUT,
+    )
+    .await
+}
+
+/// `download_project_zip` for the revision recorded by commit `commit_id`
+/// rather than the latest one. An unknown commit fails with
+/// `Error::NotFound`.
+pub async fn download_project_revision_zip(
+    api_info: &ApiInfo,
+    project_id: Uuid,
+    commit_id: &str,
+    dest: &mut (impl AsyncWrite + Unpin),
+    on_progress: impl FnMut(usize, Option<u64>),
+) -> Result<DownloadedZip, Error> {
+    info!("Downloading project zip for project: {project_id} at commit {commit_id}");
+    let url = http::v2_url(
+        api_info.instance(),
+        &format!("projects/{project_id}/download/compass_zip/{commit_id}/"),
+    );
+    stream_project_zip(
+        api_info,
+        project_id,
+        url,
+        dest,
+        on_progress,
+        PROJECT_DOWNLOAD_IDLE_TIMEOUT,
+    )
+    .await
+}
+
+async fn stream_project_zip(
+    api_info: &ApiInfo,
+    project_id: Uuid,
+    url: url::Url,
+    dest: &mut (impl AsyncWrite + Unpin),
+    mut on_progress: impl FnMut(usize, Option<u64>),
+    idle_timeout: Duration,
+) -> Result<DownloadedZip, Error> {
     let req = http::authenticated(
-        get_api_client().get(url).timeout(PROJECT_DOWNLOAD_TIMEOUT),
+        get_api_client()
+            .get(url)
+            .timeout(PROJECT_DOWNLOAD_MAX_TIMEOUT),
         api_info,
     )?;
 
     let started = Instant::now();
-    let result = http::send_raw(req).await;
-    info!(
-        "Download request completed for project {project_id} in {:?}",
-        started.elapsed()
-    );
-
-    match result {
-        Ok(resp) => resp
-            .bytes()
-            .await
-            .map_err(|e| Error::Deserialization(e.to_string())),
-        Err(Error::Unprocessable(_)) => Err(Error::NoProjectData(project_id)),
+    let mut resp = match http::send_raw_with_retry(req, &retry_policy()).await {
+        Ok(resp) => resp,
+        Err(Error::Unprocessable(_)) => return Err(Error::NoProjectData(project_id)),
         Err(e) => {
             error!("Download failed for project {project_id}: {e}");
-            Err(e)
+            return Err(e);
         }
+    };
+
+    let content_len = resp.content_length();
+    let expected_sha256 = http::expected_sha256(resp.headers());
+    let mut hasher = Sha256::new();
+    let mut written: u64 = 0;
+    loop {
+        let chunk = match tokio::time::timeout(idle_timeout, resp.chunk()).await {
+            Ok(Ok(Some(chunk))) => chunk,
+            Ok(Ok(None)) => break,
+            Ok(Err(e)) => {
+                error!("Download of project {project_id} interrupted: {e}");
+                return Err(Error::NetworkRequest(e.to_string()));
+            }
+            Err(_) => {
+                error!(
+                    "Download of project {project_id} stalled after {written} bytes, giving up after {idle_timeout:?}"
+                );
+                return Err(Error::NetworkRequest(format!(
+                    "Download stalled: nothing received for {} seconds",
+                    idle_timeout.as_secs()
+                )));
+            }
+        };
+        dest.write_all(&chunk)
+            .await
+            .map_err(|e| Error::FileWrite(e.to_string()))?;
+        hasher.update(&chunk);
+        written += chunk.len() as u64;
+        on_progress(chunk.len(), content_len);
+    }
+    dest.flush()
+        .await
+        .map_err(|e| Error::FileWrite(e.to_string()))?;
+    if let Some(expected) = content_len
+        && written != expected
+    {
+        error!("Download of project {project_id} truncated: {written} of {expected} bytes");
+        return Err(Error::NetworkRequest(format!(
+            "Download truncated: received {written} of {expected} bytes"
+        )));
     }
+    let sha256: [u8; 32] = hasher.finalize().into();
+    if let Some(expected) = expected_sha256
+        && expected != sha256
+    {
+        let (expected, actual) = (to_hex(&expected), to_hex(&sha256));
+        error!("Checksum mismatch for project {project_id}: expected {expected}, got {actual}");
+        return Err(Error::ChecksumMismatch { expected, actual });
+    }
+    info!(
+        "Downloaded {written} bytes for project {project_id} in {:?}",
+        started.elapsed()
+    );
+    Ok(DownloadedZip {
+        size: written,
+        sha256: to_hex(&sha256),
+    })
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
 }
 
+/// Upload a project ZIP as a new commit.
+///
+/// The file is streamed from disk rather than read into memory. As the body
+/// is consumed, `on_progress` is called with each chunk length and the total
+/// payload size. Triggering `cancel` aborts the request and returns
+/// `Error::Cancelled`. The server only creates a commit once the full body
+/// has arrived, so cancelling while the body is still being sent leaves the
+/// project unchanged; after the last chunk, cancelling only stops waiting for
+/// the answer and the commit may still be created. The request timeout grows
+/// with the payload size (see `upload_timeout`).
 pub async fn upload_project_zip(
     api_info: &ApiInfo,
     project_id: Uuid,
     commit_message: String,
     zip_path: &Path,
+    mut on_progress: impl FnMut(usize, Option<u64>) + Send + 'static,
+    cancel: &CancellationToken,
 ) -> Result<ProjectSaveResult, Error> {
     info!("Uploading project ZIP for project: {project_id}");
+    if cancel.is_cancelled() {
+        return Err(Error::Cancelled);
+    }
     let url = http::v2_url(
         api_info.instance(),
         &format!("projects/{project_id}/upload/compass_zip/"),
     );
 
-    let zip_bytes = std::fs::read(zip_path).map_err(|e| Error::FileRead(e.to_string()))?;
-    let part = reqwest::multipart::Part::bytes(zip_bytes)
-        .file_name("project.zip")
-        .mime_str("application/zip")
-        .expect("application/zip is a valid MIME type");
+    let zip_file = tokio::fs::File::open(zip_path)
+        .await
+        .map_err(|e| Error::FileRead(e.to_string()))?;
+    let payload_len = zip_file
+        .metadata()
+        .await
+        .map_err(|e| Error::FileRead(e.to_string()))?
+        .len();
+    let body_cancel = cancel.clone();
+    let body = ReaderStream::with_capacity(zip_file, PROJECT_UPLOAD_CHUNK_SIZE).map(move |chunk| {
+        if body_cancel.is_cancelled() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "upload cancelled",
+            ));
+        }
+        if let Ok(bytes) = &chunk {
+            on_progress(bytes.len(), Some(payload_len));
+        }
+        chunk
+    });
+    let part =
+        reqwest::multipart::Part::stream_with_length(reqwest::Body::wrap_stream(body), payload_len)
+            .file_name("project.zip")
+            .mime_str("application/zip")
+            .expect("application/zip is a valid MIME type");
     let form = reqwest::multipart::Form::new()
         .text("message", commit_message)
         .part("artifact", part);
@@ -162,15 +446,24 @@ pub async fn upload_project_zip(
     let req = http::authenticated(
         get_api_client()
             .put(url)
-            .timeout(PROJECT_UPLOAD_TIMEOUT)
+            .timeout(upload_timeout(payload_len))
             .multipart(form),
         api_info,
     )?;
 
+    // Never retried: a PUT that timed out client-side may still have
+    // created a commit server-side.
     let started = Instant::now();
-    let result = http::send_raw(req).await;
+    let result = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => {
+            warn!("Upload cancelled for project {project_id} after {:?}", started.elapsed());
+            return Err(Error::Cancelled);
+        }
+        result = http::send_raw(req) => result,
+    };
     info!(
-        "Upload request completed for project {project_id} in {:?}",
+        "Upload request completed for project {project_id} ({payload_len} bytes) in {:?}",
         started.elapsed()
     );
 
@@ -190,14 +483,28 @@ pub async fn upload_project_zip(
     }
 }
 
+/// Request timeout for an upload of `payload_len` bytes: the base allowance
+/// plus the time the payload takes at `PROJECT_UPLOAD_MIN_BYTES_PER_SEC`,
+/// capped at `PROJECT_UPLOAD_MAX_TIMEOUT`.
+fn upload_timeout(payload_len: u64) -> Duration {
+    let transfer = Duration::from_secs(payload_len.div_ceil(PROJECT_UPLOAD_MIN_BYTES_PER_SEC));
+    (PROJECT_UPLOAD_BASE_TIMEOUT + transfer).min(PROJECT_UPLOAD_MAX_TIMEOUT)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test_support::{
-        build_minimal_compass_zip, ensure_test_env_vars, fixture_project_id, test_api_info,
-        unauthorized_api_info, with_acquired_project_mutex,
+        MOCK_USER_EMAIL, MockSpeleoDb, build_minimal_compass_zip, ensure_test_env_vars,
+        fixture_project_id, test_api_info, unauthorized_api_info, with_acquired_project_mutex,
     };
+    use base64::{Engine, prelude::BASE64_STANDARD};
+    use common::api_types::{Permission, Visibility};
     use serial_test::serial;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
 
     /// A UUID that is virtually guaranteed not to exist server-side, used
     /// to exercise 404-returning endpoints without polluting the database.
@@ -211,16 +518,16 @@ mod tests {
             name: "Test Project".to_string(),
             description: "Test Description".to_string(),
             is_active: true,
-            permission: "ADMIN".to_string(),
+            permission: Permission::Admin,
             active_mutex: None,
             country: "US".to_string(),
             created_by: "tester@example.com".to_string(),
-            creation_date: "2026-01-01T00:00:00Z".to_string(),
-            modified_date: "2026-01-01T00:00:00Z".to_string(),
+            creation_date: chrono::DateTime::UNIX_EPOCH,
+            modified_date: chrono::DateTime::UNIX_EPOCH,
             latitude: None,
             longitude: None,
             fork_from: None,
-            visibility: "PRIVATE".to_string(),
+            visibility: Visibility::Private,
             exclude_geojson: false,
             latest_commit: None,
             project_type,
@@ -510,7 +817,7 @@ mod tests {
         .await
         .expect("fresh project creation must succeed");
 
-        let err = download_project_zip(&api_info, fresh.id)
+        let err = download_project_zip(&api_info, fresh.id, &mut Vec::new(), |_, _| {})
             .await
             .expect_err("empty project must fail");
         assert!(
@@ -526,9 +833,14 @@ mod tests {
         if !ensure_test_env_vars().await {
             return;
         }
-        let err = download_project_zip(&test_api_info(), unknown_project_id())
-            .await
-            .expect_err("unknown project must fail");
+        let err = download_project_zip(
+            &test_api_info(),
+            unknown_project_id(),
+            &mut Vec::new(),
+            |_, _| {},
+        )
+        .await
+        .expect_err("unknown project must fail");
         assert!(
             matches!(err, Error::NotFound(_)),
             "expected NotFound, got: {err:?}"
@@ -541,9 +853,14 @@ mod tests {
         if !ensure_test_env_vars().await {
             return;
         }
-        let err = download_project_zip(&unauthorized_api_info(), existing_project_id().await)
-            .await
-            .expect_err("bogus token must fail");
+        let err = download_project_zip(
+            &unauthorized_api_info(),
+            existing_project_id().await,
+            &mut Vec::new(),
+            |_, _| {},
+        )
+        .await
+        .expect_err("bogus token must fail");
         assert!(matches!(err, Error::Unauthorized(_)));
     }
 
@@ -578,9 +895,12 @@ mod tests {
                     fresh.id,
                     "Test commit from upload_then_download_project_zip_success".into(),
                     zip.path(),
+                    |_, _| {},
+                    &CancellationToken::new(),
                 )
                 .await?;
-                let bytes = download_project_zip(&api_info, fresh.id).await?;
+                let mut bytes = Vec::new();
+                download_project_zip(&api_info, fresh.id, &mut bytes, |_, _| {}).await?;
                 Ok::<_, Error>((save_result, bytes))
             }
         })
@@ -602,6 +922,8 @@ mod tests {
             unknown_project_id(),
             "Test commit".into(),
             zip.path(),
+            |_, _| {},
+            &CancellationToken::new(),
         )
         .await
         .expect_err("unknown project must fail");
@@ -623,6 +945,8 @@ mod tests {
             existing_project_id().await,
             "Test commit".into(),
             zip.path(),
+            |_, _| {},
+            &CancellationToken::new(),
         )
         .await
         .expect_err("bogus token must fail");
@@ -636,6 +960,8 @@ mod tests {
             Uuid::new_v4(),
             "x".into(),
             Path::new("/definitely/does/not/exist.zip"),
+            |_, _| {},
+            &CancellationToken::new(),
         )
         .await
         .expect_err("missing zip path must fail");
@@ -644,4 +970,690 @@ mod tests {
             "expected FileRead, got: {err:?}"
         );
     }
+
+    // ─── MockSpeleoDb: hermetic endpoint coverage ──────────────────────────
+    //
+    // The tests above need a live server; the ones below run against the
+    // in-process mock on every `cargo test` so each endpoint's success and
+    // error mapping is always exercised.
+
+    #[tokio::test]
+    async fn mock_fetch_projects_filters_to_compass() {
+        let mock = MockSpeleoDb::start().await;
+        let compass_id = mock.add_project("Compass", ProjectType::Compass);
+        mock.add_project("Other", ProjectType::Ignored);
+
+        let projects = fetch_projects(&mock.api_info()).await.unwrap();
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].id, compass_id);
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_projects_filters_every_page() {
+        let mock = MockSpeleoDb::start().await;
+        let first = mock.add_project("Compass 1", ProjectType::Compass);
+        mock.add_project("Other 1", ProjectType::Ignored);
+        mock.add_project("Other 2", ProjectType::Ignored);
+        let second = mock.add_project("Compass 2", ProjectType::Compass);
+        mock.paginate_projects(2);
+
+        let projects = fetch_projects(&mock.api_info()).await.unwrap();
+
+        let ids: Vec<Uuid> = projects.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![first, second]);
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 2);
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_projects_unauthorized() {
+        let mock = MockSpeleoDb::start().await;
+        let err = fetch_projects(&mock.unauthorized_api_info())
+            .await
+            .expect_err("bogus token must fail");
+        assert!(matches!(err, Error::Unauthorized(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_project_info_success_and_not_found() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+
+        let info = fetch_project_info(&mock.api_info(), id).await.unwrap();
+        assert_eq!(info.id, id);
+
+        let err = fetch_project_info(&mock.api_info(), unknown_project_id())
+            .await
+            .expect_err("unknown project must fail");
+        assert!(matches!(err, Error::NotFound(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_create_project_round_trips_fields() {
+        let mock = MockSpeleoDb::start().await;
+        let info = create_project(
+            &mock.api_info(),
+            "New Cave".into(),
+            "Description".into(),
+            "MX".into(),
+            Some("20.5".into()),
+            Some("-87.25".into()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(info.name, "New Cave");
+        assert_eq!(info.country, "MX");
+        assert_eq!(info.latitude, Some(20.5));
+        assert_eq!(info.longitude, Some(-87.25));
+        assert_eq!(info.project_type, ProjectType::Compass);
+        assert_eq!(mock.project(info.id), Some(info));
+    }
+
+    #[tokio::test]
+    async fn mock_create_project_empty_name_is_rejected() {
+        let mock = MockSpeleoDb::start().await;
+        let err = create_project(
+            &mock.api_info(),
+            String::new(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+        )
+        .await
+        .expect_err("empty name must be rejected");
+        assert!(
+            matches!(err, Error::Api { status: 400, .. }),
+            "got: {err:?}"
+        );
+    }
+
+    fn edited_metadata() -> ProjectMetadata {
+        ProjectMetadata {
+            name: "Renamed Cave".into(),
+            description: "Surveyed 2026".into(),
+            country: "MX".into(),
+            latitude: Some("20.5".into()),
+            longitude: Some("-87.25".into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_update_project_replaces_metadata() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+
+        let info = update_project(&mock.api_info(), id, &edited_metadata())
+            .await
+            .unwrap();
+        assert_eq!(info.metadata(), edited_metadata());
+        assert_eq!(mock.project(id), Some(info));
+
+        let cleared = ProjectMetadata {
+            latitude: Some(String::new()),
+            longitude: None,
+            ..edited_metadata()
+        };
+        let info = update_project(&mock.api_info(), id, &cleared)
+            .await
+            .unwrap();
+        assert_eq!(info.latitude, None);
+        assert_eq!(info.longitude, None);
+    }
+
+    #[tokio::test]
+    async fn mock_update_project_rejects_non_admins_and_unknown_projects() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        mock.set_permission(id, Permission::ReadAndWrite);
+
+        let err = update_project(&mock.api_info(), id, &edited_metadata())
+            .await
+            .expect_err("non-admins must not edit metadata");
+        assert!(matches!(err, Error::Forbidden(_)), "got: {err:?}");
+        assert_eq!(mock.project(id).unwrap().name, "Compass");
+
+        let err = update_project(&mock.api_info(), unknown_project_id(), &edited_metadata())
+            .await
+            .expect_err("unknown project must fail");
+        assert!(matches!(err, Error::NotFound(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_project_permissions_lists_users_and_teams() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        mock.add_collaborator(id, "surveyor@example.com", Permission::ReadAndWrite);
+        mock.add_collaborator(id, "visitor@example.com", Permission::WebViewer);
+        mock.add_team(id, "Dive Team", Permission::ReadOnly);
+
+        let permissions = fetch_project_permissions(&mock.api_info(), id)
+            .await
+            .unwrap();
+        let users: Vec<(&str, &str)> = permissions
+            .users
+            .iter()
+            .map(|p| (p.user.as_str(), p.level.as_str()))
+            .collect();
+        assert_eq!(
+            users,
+            vec![
+                (MOCK_USER_EMAIL, "ADMIN"),
+                ("surveyor@example.com", "READ_AND_WRITE"),
+                ("visitor@example.com", "WEB_VIEWER"),
+            ]
+        );
+        assert_eq!(
+            permissions.teams,
+            vec![TeamPermission {
+                team: "Dive Team".to_string(),
+                level: Permission::ReadOnly,
+            }]
+        );
+
+        let err = fetch_project_permissions(&mock.api_info(), unknown_project_id())
+            .await
+            .expect_err("unknown project must fail");
+        assert!(matches!(err, Error::NotFound(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_projects_if_modified_reports_unchanged_listing() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project("Compass", ProjectType::Compass);
+
+        let projects = fetch_projects_if_modified(&api_info).await.unwrap();
+        assert_eq!(projects.map(|p| p.len()), Some(1));
+        assert_eq!(fetch_projects_if_modified(&api_info).await.unwrap(), None);
+
+        mock.lock_project_as(id, "other@example.com");
+        let projects = fetch_projects_if_modified(&api_info)
+            .await
+            .unwrap()
+            .expect("a changed listing must be returned");
+        assert_eq!(
+            projects[0].active_mutex.as_ref().unwrap().user,
+            "other@example.com"
+        );
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 3);
+    }
+
+    #[tokio::test]
+    async fn mock_forgotten_listing_is_returned_again() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        mock.add_project("Compass", ProjectType::Compass);
+
+        assert!(
+            fetch_projects_if_modified(&api_info)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        forget_projects_listing(&api_info);
+
+        let projects = fetch_projects_if_modified(&api_info)
+            .await
+            .unwrap()
+            .expect("a forgotten listing must be returned even if unchanged");
+        assert_eq!(projects.len(), 1);
+        assert_eq!(fetch_projects_if_modified(&api_info).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn mock_acquire_and_release_project_mutex() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+
+        let info = acquire_project_mutex(&mock.api_info(), id).await.unwrap();
+        assert_eq!(info.active_mutex.unwrap().user, MOCK_USER_EMAIL);
+
+        let info = release_project_mutex(&mock.api_info(), id).await.unwrap();
+        assert!(info.active_mutex.is_none());
+    }
+
+    #[tokio::test]
+    async fn mock_acquire_project_mutex_held_by_other_user_returns_locked() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        mock.lock_project_as(id, "someone.else@example.com");
+
+        let err = acquire_project_mutex(&mock.api_info(), id)
+            .await
+            .expect_err("locked project must fail");
+        assert_eq!(err, Error::ProjectMutexLocked(id));
+    }
+
+    #[tokio::test]
+    async fn mock_acquire_project_mutex_not_found() {
+        let mock = MockSpeleoDb::start().await;
+        let err = acquire_project_mutex(&mock.api_info(), unknown_project_id())
+            .await
+            .expect_err("unknown project must fail");
+        assert!(matches!(err, Error::NotFound(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_release_project_mutex_held_by_other_user_is_forbidden() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        mock.lock_project_as(id, "someone.else@example.com");
+
+        let err = release_project_mutex(&mock.api_info(), id)
+            .await
+            .expect_err("foreign mutex must not be released");
+        assert!(matches!(err, Error::Forbidden(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_zip_without_data_returns_no_project_data() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Empty", ProjectType::Compass);
+
+        let err = download_project_zip(&mock.api_info(), id, &mut Vec::new(), |_, _| {})
+            .await
+            .expect_err("empty project must fail");
+        assert_eq!(err, Error::NoProjectData(id));
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_zip_streams_with_progress() {
+        let mock = MockSpeleoDb::start().await;
+        let zip: Vec<u8> = (0..512 * 1024).map(|i| (i % 251) as u8).collect();
+        let id = mock.add_project_with_zip("Big Cave", zip.clone());
+
+        let mut dest = Vec::new();
+        let mut reported = 0usize;
+        let mut totals = Vec::new();
+        let written = download_project_zip(&mock.api_info(), id, &mut dest, |chunk, total| {
+            reported += chunk;
+            totals.push(total);
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(written.size, zip.len() as u64);
+        assert_eq!(written.sha256, to_hex(&Sha256::digest(&zip)));
+        assert_eq!(reported, zip.len());
+        assert!(totals.iter().all(|t| *t == Some(zip.len() as u64)));
+        assert_eq!(dest, zip);
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_zip_gives_up_on_a_stalled_body() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project_with_zip("Compass", vec![7; 64 * 1024]);
+        mock.stall_downloads();
+        let url = http::v2_url(
+            &mock.instance(),
+            &format!("projects/{id}/download/compass_zip/"),
+        );
+
+        let mut dest = Vec::new();
+        let err = stream_project_zip(
+            &mock.api_info(),
+            id,
+            url,
+            &mut dest,
+            |_, _| {},
+            Duration::from_millis(200),
+        )
+        .await
+        .expect_err("a stalled download must fail");
+
+        assert!(
+            matches!(&err, Error::NetworkRequest(message) if message.contains("stalled")),
+            "got: {err:?}"
+        );
+        assert_eq!(dest.len(), 32 * 1024);
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_zip_checksum_mismatch_fails() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project_with_zip("Compass", b"zip".to_vec());
+        let wrong = BASE64_STANDARD.encode(Sha256::digest(b"not the zip"));
+        mock.fail_next_with_headers(
+            200,
+            "zip",
+            &[("Repr-Digest", &format!("sha-256=:{wrong}:"))],
+        );
+
+        let err = download_project_zip(&mock.api_info(), id, &mut Vec::new(), |_, _| {})
+            .await
+            .expect_err("corrupt archive must fail");
+        assert_eq!(
+            err,
+            Error::ChecksumMismatch {
+                expected: to_hex(&Sha256::digest(b"not the zip")),
+                actual: to_hex(&Sha256::digest(b"zip")),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_zip_without_digest_still_hashes() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project_with_zip("Compass", b"zip".to_vec());
+        mock.fail_next(200, "zip");
+
+        let download = download_project_zip(&mock.api_info(), id, &mut Vec::new(), |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(download.size, 3);
+        assert_eq!(download.sha256, to_hex(&Sha256::digest(b"zip")));
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_zip_server_error_passes_through() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project_with_zip("Compass", b"zip".to_vec());
+        mock.fail_next(500, r#"{"error": "boom"}"#);
+
+        let err = download_project_zip(&mock.api_info(), id, &mut Vec::new(), |_, _| {})
+            .await
+            .expect_err("500 must fail");
+        assert_eq!(
+            err,
+            Error::Api {
+                status: 500,
+                message: "boom".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_upload_then_download_then_no_changes() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        acquire_project_mutex(&api_info, id).await.unwrap();
+        let zip = build_minimal_compass_zip();
+
+        let first = upload_project_zip(
+            &api_info,
+            id,
+            "First".into(),
+            zip.path(),
+            |_, _| {},
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first, ProjectSaveResult::Saved);
+        assert_eq!(
+            mock.project(id).unwrap().latest_commit.unwrap().message,
+            "First"
+        );
+
+        let mut bytes = Vec::new();
+        download_project_zip(&api_info, id, &mut bytes, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(Some(bytes), mock.project_zip(id));
+
+        let second = upload_project_zip(
+            &api_info,
+            id,
+            "Second".into(),
+            zip.path(),
+            |_, _| {},
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(second, ProjectSaveResult::NoChanges);
+        assert_eq!(
+            mock.request_count("PUT", &format!("/api/v2/projects/{id}/upload/compass_zip/")),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_projects_retries_transient_failure() {
+        let mock = MockSpeleoDb::start().await;
+        mock.add_project("Compass", ProjectType::Compass);
+        mock.fail_next_with_headers(503, "", &[("Retry-After", "0")]);
+
+        let projects = fetch_projects(&mock.api_info()).await.unwrap();
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(mock.request_count("GET", "/api/v2/projects/"), 2);
+    }
+
+    #[tokio::test]
+    async fn mock_upload_project_zip_is_never_retried() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        acquire_project_mutex(&api_info, id).await.unwrap();
+        mock.fail_next_with_headers(503, r#"{"error": "down"}"#, &[("Retry-After", "0")]);
+        let zip = build_minimal_compass_zip();
+
+        let err = upload_project_zip(
+            &api_info,
+            id,
+            "msg".into(),
+            zip.path(),
+            |_, _| {},
+            &CancellationToken::new(),
+        )
+        .await
+        .expect_err("503 must fail");
+
+        assert!(
+            matches!(err, Error::Api { status: 503, .. }),
+            "got: {err:?}"
+        );
+        assert_eq!(
+            mock.request_count("PUT", &format!("/api/v2/projects/{id}/upload/compass_zip/")),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_upload_project_zip_without_mutex_returns_conflict() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        let zip = build_minimal_compass_zip();
+
+        let err = upload_project_zip(
+            &mock.api_info(),
+            id,
+            "msg".into(),
+            zip.path(),
+            |_, _| {},
+            &CancellationToken::new(),
+        )
+        .await
+        .expect_err("upload without mutex must fail");
+        assert!(matches!(err, Error::Conflict(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_upload_project_zip_unauthorized() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        let zip = build_minimal_compass_zip();
+
+        let err = upload_project_zip(
+            &mock.unauthorized_api_info(),
+            id,
+            "msg".into(),
+            zip.path(),
+            |_, _| {},
+            &CancellationToken::new(),
+        )
+        .await
+        .expect_err("bogus token must fail");
+        assert!(matches!(err, Error::Unauthorized(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_upload_project_zip_streams_with_progress() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        acquire_project_mutex(&api_info, id).await.unwrap();
+        let payload: Vec<u8> = (0..300 * 1024).map(|i| (i % 251) as u8).collect();
+        let zip = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(zip.path(), &payload).unwrap();
+
+        let reported = Arc::new(AtomicUsize::new(0));
+        let counter = reported.clone();
+        let result = upload_project_zip(
+            &api_info,
+            id,
+            "Streamed".into(),
+            zip.path(),
+            move |chunk, total| {
+                assert_eq!(total, Some(300 * 1024));
+                counter.fetch_add(chunk, Ordering::SeqCst);
+            },
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result, ProjectSaveResult::Saved);
+        assert_eq!(reported.load(Ordering::SeqCst), payload.len());
+        assert_eq!(mock.project_zip(id), Some(payload));
+    }
+
+    #[tokio::test]
+    async fn mock_upload_project_zip_cancelled_before_start_sends_nothing() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        acquire_project_mutex(&api_info, id).await.unwrap();
+        let zip = build_minimal_compass_zip();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let err = upload_project_zip(&api_info, id, "msg".into(), zip.path(), |_, _| {}, &cancel)
+            .await
+            .expect_err("cancelled upload must fail");
+
+        assert_eq!(err, Error::Cancelled);
+        assert_eq!(
+            mock.request_count("PUT", &format!("/api/v2/projects/{id}/upload/compass_zip/")),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_upload_project_zip_cancelled_mid_stream_creates_no_commit() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        acquire_project_mutex(&api_info, id).await.unwrap();
+        let zip = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(zip.path(), vec![7u8; 1024 * 1024]).unwrap();
+
+        let cancel = CancellationToken::new();
+        let cancel_after_first_chunk = cancel.clone();
+        let err = upload_project_zip(
+            &api_info,
+            id,
+            "msg".into(),
+            zip.path(),
+            move |_, _| cancel_after_first_chunk.cancel(),
+            &cancel,
+        )
+        .await
+        .expect_err("cancelled upload must fail");
+
+        assert_eq!(err, Error::Cancelled);
+        assert!(mock.project(id).unwrap().latest_commit.is_none());
+        assert_eq!(mock.project_zip(id), None);
+    }
+
+    #[test]
+    fn upload_timeout_scales_with_payload_and_is_capped() {
+        assert_eq!(upload_timeout(0), PROJECT_UPLOAD_BASE_TIMEOUT);
+        assert_eq!(
+            upload_timeout(PROJECT_UPLOAD_MIN_BYTES_PER_SEC * 100),
+            PROJECT_UPLOAD_BASE_TIMEOUT + Duration::from_secs(100)
+        );
+        assert!(upload_timeout(200 * 1024 * 1024) > upload_timeout(20 * 1024 * 1024));
+        assert_eq!(upload_timeout(u64::MAX), PROJECT_UPLOAD_MAX_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_project_commits_pages_newest_first() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project("Compass", ProjectType::Compass);
+        let commits: Vec<CommitInfo> = (0..PROJECT_COMMITS_PAGE_SIZE + 2)
+            .map(|i| mock.push_revision(id, &format!("Commit {i}"), vec![i as u8]))
+            .collect();
+
+        let first = fetch_project_commits(&api_info, id, 1).await.unwrap();
+        assert_eq!(first.count, commits.len() as u64);
+        assert_eq!(first.results.len(), PROJECT_COMMITS_PAGE_SIZE as usize);
+        assert_eq!(first.results[0], *commits.last().unwrap());
+        assert!(first.has_next());
+        assert!(first.previous.is_none());
+
+        let second = fetch_project_commits(&api_info, id, 2).await.unwrap();
+        assert_eq!(second.results, vec![commits[1].clone(), commits[0].clone()]);
+        assert!(!second.has_next());
+    }
+
+    #[tokio::test]
+    async fn mock_fetch_project_commits_empty_and_unknown_project() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project("Compass", ProjectType::Compass);
+
+        let page = fetch_project_commits(&mock.api_info(), id, 1)
+            .await
+            .unwrap();
+        assert_eq!(page.count, 0);
+        assert!(page.results.is_empty());
+
+        let err = fetch_project_commits(&mock.api_info(), Uuid::new_v4(), 1)
+            .await
+            .expect_err("unknown project must fail");
+        assert!(matches!(err, Error::NotFound(_)), "got: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_revision_zip_returns_that_revision() {
+        let mock = MockSpeleoDb::start().await;
+        let api_info = mock.api_info();
+        let id = mock.add_project_with_zip("Compass", b"first".to_vec());
+        let first = mock.project(id).unwrap().latest_commit.unwrap();
+        mock.push_revision(id, "Second", b"second".to_vec());
+
+        let mut old = Vec::new();
+        download_project_revision_zip(&api_info, id, &first.id, &mut old, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(old, b"first");
+
+        let mut latest = Vec::new();
+        download_project_zip(&api_info, id, &mut latest, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(latest, b"second");
+    }
+
+    #[tokio::test]
+    async fn mock_download_project_revision_zip_unknown_commit_is_not_found() {
+        let mock = MockSpeleoDb::start().await;
+        let id = mock.add_project_with_zip("Compass", b"zip".to_vec());
+
+        let err = download_project_revision_zip(
+            &mock.api_info(),
+            id,
+            "0000000000000000000000000000000000000000",
+            &mut Vec::new(),
+            |_, _| {},
+        )
+        .await
+        .expect_err("unknown commit must fail");
+        assert!(matches!(err, Error::NotFound(_)), "got: {err:?}");
+    }
 }
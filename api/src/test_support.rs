@@ -14,6 +14,9 @@
 //!   staging with auto-created projects).
 //! - A `build_minimal_compass_zip` helper that produces a tiny, valid
 //!   Compass project ZIP in a temp file for upload-path tests.
+//! - `MockSpeleoDb`, an in-process fake of the v2 endpoints so the status
+//!   mapping of every request path runs on every `cargo test`, with or
+//!   without a live server configured.
 
 use std::{
     future::Future,
@@ -23,7 +26,7 @@ use std::{
     time::Duration,
 };
 
-use common::{ApiInfo, api_types::ProjectInfo};
+use common::{ApiInfo, OauthToken, api_types::ProjectInfo};
 use tempfile::NamedTempFile;
 use tokio::sync::OnceCell;
 use url::Url;
@@ -31,6 +34,10 @@ use uuid::Uuid;
 
 use crate::{get_api_client, http, project};
 
+mod mock_server;
+
+pub(crate) use mock_server::{MOCK_OAUTH_TOKEN, MOCK_USER_EMAIL, MOCK_USER_PASSWORD, MockSpeleoDb};
+
 static FIXTURE_PROJECT: OnceCell<Uuid> = OnceCell::const_new();
 static TEST_ENV_PREFLIGHT: OnceCell<Result<(), String>> = OnceCell::const_new();
 static TEST_ENV_PREFLIGHT_FAILURE_REPORTED: AtomicBool = AtomicBool::new(false);
@@ -143,6 +150,7 @@ pub(crate) fn test_instance() -> Url {
 pub(crate) fn test_api_info() -> ApiInfo {
     let oauth = std::env::var("TEST_SPELEODB_OAUTH").expect("TEST_SPELEODB_OAUTH not set");
     let email = std::env::var("TEST_SPELEODB_EMAIL").ok();
+    let oauth = OauthToken::new(oauth).expect("TEST_SPELEODB_OAUTH must be a valid token");
     ApiInfo::new(test_instance(), email, Some(oauth))
 }
 
@@ -152,7 +160,7 @@ pub(crate) fn unauthorized_api_info() -> ApiInfo {
     ApiInfo::new(
         test_instance(),
         Some("nobody@example.invalid".to_string()),
-        Some("0".repeat(40)),
+        Some(OauthToken::new("0".repeat(40)).expect("valid token shape")),
     )
 }
 
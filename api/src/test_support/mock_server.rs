@@ -0,0 +1,899 @@
+//! Hermetic, in-process stand-in for the SpeleoDB v2 API.
+//!
+//! `MockSpeleoDb::start` binds an ephemeral localhost port and serves the
+//! subset of endpoints this crate talks to, backed by in-memory state:
+//!
+//! - `GET|POST|DELETE user/auth-token/`
+//! - `GET|POST projects/` (the listing honors `If-None-Match` and is
+//!   paginated after [`MockSpeleoDb::paginate_projects`])
+//! - `GET|PATCH projects/{id}/`
+//! - `POST projects/{id}/acquire/`, `POST projects/{id}/release/`
+//! - `GET projects/{id}/commits/` (paginated with `page` / `page_size`)
+//! - `GET projects/{id}/permissions/user/`, `GET projects/{id}/permissions/team/`
+//! - `GET projects/{id}/download/compass_zip/[{commit}/]` (with `Repr-Digest`)
+//! - `PUT projects/{id}/upload/compass_zip/`
+//!
+//! Responses follow the v2 contract (bare JSON bodies, `{"error": "..."}`
+//! on failure) closely enough to drive every branch of `send_json` /
+//! `send_raw`. Arbitrary status codes can be injected with
+//! [`MockSpeleoDb::fail_next`] to cover the paths the in-memory model never
+//! produces on its own (5xx, 429, oversized HTML bodies, ...).
+//!
+//! The server task is aborted when the `MockSpeleoDb` is dropped, so every
+//! test gets a fresh instance with no state leaking between tests.
+
+use std::{
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::{Multipart, Path, Query, Request, State},
+    http::{HeaderMap, HeaderName, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use chrono::{DateTime, Utc};
+use common::{
+    ApiInfo, OauthToken,
+    api_types::{
+        ActiveMutex, CommitInfo, Permission, ProjectInfo, ProjectType, TeamPermission,
+        UserPermission, Visibility,
+    },
+    server_time,
+};
+use futures_util::{StreamExt, stream};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tokio::{net::TcpListener, task::JoinHandle};
+use url::Url;
+use uuid::Uuid;
+
+/// Token accepted by the mock for the default user.
+pub(crate) const MOCK_OAUTH_TOKEN: &str = "0123456789abcdef0123456789abcdef01234567";
+/// Email of the default user, returned by `user/auth-token/`.
+pub(crate) const MOCK_USER_EMAIL: &str = "caver@example.com";
+/// Password accepted for `MOCK_USER_EMAIL` on the email/password flow.
+pub(crate) const MOCK_USER_PASSWORD: &str = "correct horse battery staple";
+
+const MOCK_TIMESTAMP: &str = "2026-01-01T00:00:00Z";
+/// Page size of `projects/{id}/commits/` when the client does not ask for one.
+const MOCK_DEFAULT_PAGE_SIZE: usize = 25;
+
+#[derive(Clone)]
+struct MockProject {
+    info: ProjectInfo,
+    /// Every accepted upload, oldest first. The last entry is what
+    /// `download/compass_zip/` serves and what `latest_commit` describes.
+    revisions: Vec<MockRevision>,
+    /// Users other than the default one with access to the project. The
+    /// default user's own level is `info.permission`.
+    collaborators: Vec<UserPermission>,
+    teams: Vec<TeamPermission>,
+}
+
+#[derive(Clone)]
+struct MockRevision {
+    commit: CommitInfo,
+    zip: Vec<u8>,
+}
+
+impl MockProject {
+    fn new(info: ProjectInfo) -> Self {
+        Self {
+            info,
+            revisions: Vec::new(),
+            collaborators: Vec::new(),
+            teams: Vec::new(),
+        }
+    }
+
+    fn latest_zip(&self) -> Option<&[u8]> {
+        self.revisions.last().map(|r| r.zip.as_slice())
+    }
+
+    fn push_revision(&mut self, message: &str, zip: Vec<u8>) -> CommitInfo {
+        let commit = new_commit(message);
+        self.info.latest_commit = Some(commit.clone());
+        self.revisions.push(MockRevision {
+            commit: commit.clone(),
+            zip,
+        });
+        commit
+    }
+}
+
+#[derive(Clone)]
+struct InjectedFailure {
+    status: StatusCode,
+    body: String,
+    headers: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct MockState {
+    projects: Vec<MockProject>,
+    /// Page size of `projects/`; a bare array when `None`.
+    projects_page_size: Option<usize>,
+    injected_failures: VecDeque<InjectedFailure>,
+    requests: Vec<(String, String)>,
+    /// `X-Request-ID` of every request, in arrival order.
+    request_ids: Vec<Option<String>>,
+    /// ZIP downloads send half their body, then nothing more.
+    stall_downloads: bool,
+}
+
+type SharedState = Arc<Mutex<MockState>>;
+
+/// Running mock server plus a handle on its in-memory state.
+pub(crate) struct MockSpeleoDb {
+    instance: Url,
+    state: SharedState,
+    server: JoinHandle<()>,
+}
+
+impl MockSpeleoDb {
+    /// Start a fresh mock on `127.0.0.1` with an OS-assigned port.
+    pub(crate) async fn start() -> Self {
+        let state = SharedState::default();
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("mock server must bind a local port");
+        let addr = listener
+            .local_addr()
+            .expect("bound listener has an address");
+        let app = router(state.clone());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app)
+                .await
+                .expect("mock server must keep serving");
+        });
+        Self {
+            instance: Url::parse(&format!("http://{addr}")).expect("loopback URL is valid"),
+            state,
+            server,
+        }
+    }
+
+    pub(crate) fn instance(&self) -> Url {
+        self.instance.clone()
+    }
+
+    /// `ApiInfo` for the default user, authenticated with `MOCK_OAUTH_TOKEN`.
+    pub(crate) fn api_info(&self) -> ApiInfo {
+        ApiInfo::new(
+            self.instance(),
+            Some(MOCK_USER_EMAIL.to_string()),
+            Some(OauthToken::new(MOCK_OAUTH_TOKEN).expect("mock token is valid")),
+        )
+    }
+
+    /// `ApiInfo` pointing at the mock with a token it always rejects.
+    pub(crate) fn unauthorized_api_info(&self) -> ApiInfo {
+        ApiInfo::new(
+            self.instance(),
+            Some("nobody@example.invalid".to_string()),
+            Some(OauthToken::new("f".repeat(40)).expect("valid token shape")),
+        )
+    }
+
+    /// Seed a project owned by the default user and return its id.
+    pub(crate) fn add_project(&self, name: &str, project_type: ProjectType) -> Uuid {
+        let info = new_project_info(
+            name.to_string(),
+            String::new(),
+            "US".to_string(),
+            project_type,
+        );
+        let id = info.id;
+        self.lock().projects.push(MockProject::new(info));
+        id
+    }
+
+    /// Seed a project and give it an initial ZIP and commit.
+    pub(crate) fn add_project_with_zip(&self, name: &str, zip: Vec<u8>) -> Uuid {
+        let id = self.add_project(name, ProjectType::Compass);
+        self.push_revision(id, "Initial commit", zip);
+        id
+    }
+
+    /// Append a commit to `project_id`, as if another client had uploaded
+    /// `zip`, and return it.
+    pub(crate) fn push_revision(
+        &self,
+        project_id: Uuid,
+        message: &str,
+        zip: Vec<u8>,
+    ) -> CommitInfo {
+        let mut state = self.lock();
+        let project = find_project(&mut state, project_id).expect("unknown mock project");
+        project.push_revision(message, zip)
+    }
+
+    /// Give the default user `permission` (e.g. `ReadOnly`) on `project_id`.
+    pub(crate) fn set_permission(&self, project_id: Uuid, permission: Permission) {
+        let mut state = self.lock();
+        let project = find_project(&mut state, project_id).expect("unknown mock project");
+        project.info.permission = permission;
+    }
+
+    /// Serve `projects/` as pages of `page_size` projects from now on.
+    pub(crate) fn paginate_projects(&self, page_size: usize) {
+        self.lock().projects_page_size = Some(page_size);
+    }
+
+    /// Give another `user` access to `project_id` at `level`.
+    pub(crate) fn add_collaborator(&self, project_id: Uuid, user: &str, level: Permission) {
+        let mut state = self.lock();
+        let project = find_project(&mut state, project_id).expect("unknown mock project");
+        project.collaborators.push(UserPermission {
+            user: user.to_string(),
+            level,
+        });
+    }
+
+    /// Give every member of `team` access to `project_id` at `level`.
+    pub(crate) fn add_team(&self, project_id: Uuid, team: &str, level: Permission) {
+        let mut state = self.lock();
+        let project = find_project(&mut state, project_id).expect("unknown mock project");
+        project.teams.push(TeamPermission {
+            team: team.to_string(),
+            level,
+        });
+    }
+
+    /// Mark `project_id` as locked by `user`, as if another client held it.
+    pub(crate) fn lock_project_as(&self, project_id: Uuid, user: &str) {
+        let mut state = self.lock();
+        let project = find_project(&mut state, project_id).expect("unknown mock project");
+        project.info.active_mutex = Some(new_mutex(user));
+    }
+
+    /// Current server-side view of a project, if it exists.
+    pub(crate) fn project(&self, project_id: Uuid) -> Option<ProjectInfo> {
+        let mut state = self.lock();
+        find_project(&mut state, project_id).map(|p| p.info.clone())
+    }
+
+    /// Bytes of the last ZIP accepted for `project_id`.
+    pub(crate) fn project_zip(&self, project_id: Uuid) -> Option<Vec<u8>> {
+        let mut state = self.lock();
+        find_project(&mut state, project_id).and_then(|p| p.latest_zip().map(<[u8]>::to_vec))
+    }
+
+    /// Make the next request (whatever its route) answer with `status` and
+    /// `body` instead of reaching the in-memory model. Calls queue up, so
+    /// several failures can be scripted in order.
+    pub(crate) fn fail_next(&self, status: u16, body: &str) {
+        self.fail_next_with_headers(status, body, &[]);
+    }
+
+    /// Like [`Self::fail_next`], but also sets the given response headers
+    /// (e.g. `Retry-After`).
+    pub(crate) fn fail_next_with_headers(&self, status: u16, body: &str, headers: &[(&str, &str)]) {
+        self.lock().injected_failures.push_back(InjectedFailure {
+            status: StatusCode::from_u16(status).expect("valid HTTP status"),
+            body: body.to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        });
+    }
+
+    /// Make every ZIP download send its headers and the first half of the
+    /// archive, then stall without closing the connection.
+    pub(crate) fn stall_downloads(&self) {
+        self.lock().stall_downloads = true;
+    }
+
+    /// Number of requests received for `method` + `path` (path relative to
+    /// the instance root, e.g. `/api/v2/projects/`).
+    pub(crate) fn request_count(&self, method: &str, path: &str) -> usize {
+        self.lock()
+            .requests
+            .iter()
+            .filter(|(m, p)| m == method && p == path)
+            .count()
+    }
+
+    /// `X-Request-ID` header of every request received so far.
+    pub(crate) fn request_ids(&self) -> Vec<Option<String>> {
+        self.lock().request_ids.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock state lock poisoned")
+    }
+}
+
+impl Drop for MockSpeleoDb {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn router(state: SharedState) -> Router {
+    Router::new()
+        .route(
+            "/api/v2/user/auth-token/",
+            get(get_auth_token)
+                .post(post_auth_token)
+                .delete(delete_auth_token),
+        )
+        .route("/api/v2/server/info/", get(server_info))
+        .route("/api/v2/projects/", get(list_projects).post(create_project))
+        .route(
+            "/api/v2/projects/{id}/",
+            get(get_project).patch(update_project),
+        )
+        .route("/api/v2/projects/{id}/acquire/", post(acquire_mutex))
+        .route("/api/v2/projects/{id}/release/", post(release_mutex))
+        .route("/api/v2/projects/{id}/commits/", get(list_commits))
+        .route(
+            "/api/v2/projects/{id}/permissions/user/",
+            get(list_user_permissions),
+        )
+        .route(
+            "/api/v2/projects/{id}/permissions/team/",
+            get(list_team_permissions),
+        )
+        .route(
+            "/api/v2/projects/{id}/download/compass_zip/",
+            get(download_zip),
+        )
+        .route(
+            "/api/v2/projects/{id}/download/compass_zip/{commit}/",
+            get(download_revision_zip),
+        )
+        .route("/api/v2/projects/{id}/upload/compass_zip/", put(upload_zip))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            record_and_inject_failures,
+        ))
+        .with_state(state)
+}
+
+/// Log every request, then short-circuit with a scripted failure if one is
+/// queued.
+async fn record_and_inject_failures(
+    State(state): State<SharedState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let failure = {
+        let mut state = state.lock().expect("mock state lock poisoned");
+        state.requests.push((
+            request.method().to_string(),
+            request.uri().path().to_string(),
+        ));
+        state.request_ids.push(
+            request
+                .headers()
+                .get("x-request-id")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        );
+        state.injected_failures.pop_front()
+    };
+    let Some(failure) = failure else {
+        return next.run(request).await;
+    };
+    let mut response = Response::builder().status(failure.status);
+    for (name, value) in &failure.headers {
+        response = response.header(name, value);
+    }
+    response
+        .body(Body::from(failure.body))
+        .expect("injected failure must build a valid response")
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+fn not_found() -> Response {
+    error(StatusCode::NOT_FOUND, "Project not found.")
+}
+
+fn unauthorized() -> Response {
+    error(
+        StatusCode::UNAUTHORIZED,
+        "Authentication credentials were not provided or are invalid.",
+    )
+}
+
+fn is_authorized(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        == Some(format!("Token {MOCK_OAUTH_TOKEN}").as_str())
+}
+
+/// Authority the client addressed, for absolute `next` / `previous` links.
+fn host(headers: &HeaderMap) -> &str {
+    headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("localhost")
+}
+
+fn find_project(state: &mut MockState, id: Uuid) -> Option<&mut MockProject> {
+    state.projects.iter_mut().find(|p| p.info.id == id)
+}
+
+fn new_project_info(
+    name: String,
+    description: String,
+    country: String,
+    project_type: ProjectType,
+) -> ProjectInfo {
+    ProjectInfo {
+        id: Uuid::new_v4(),
+        name,
+        description,
+        is_active: true,
+        permission: Permission::Admin,
+        active_mutex: None,
+        country,
+        created_by: MOCK_USER_EMAIL.to_string(),
+        creation_date: mock_timestamp(),
+        modified_date: mock_timestamp(),
+        latitude: None,
+        longitude: None,
+        fork_from: None,
+        visibility: Visibility::Private,
+        exclude_geojson: false,
+        latest_commit: None,
+        project_type,
+    }
+}
+
+fn mock_timestamp() -> DateTime<Utc> {
+    server_time::parse(MOCK_TIMESTAMP).expect("MOCK_TIMESTAMP is RFC 3339")
+}
+
+fn new_mutex(user: &str) -> ActiveMutex {
+    ActiveMutex {
+        user: user.to_string(),
+        creation_date: mock_timestamp(),
+        modified_date: mock_timestamp(),
+    }
+}
+
+fn new_commit(message: &str) -> CommitInfo {
+    CommitInfo {
+        id: format!(
+            "{}{}",
+            Uuid::new_v4().simple(),
+            &Uuid::new_v4().simple().to_string()[..8]
+        ),
+        message: message.to_string(),
+        author_name: MOCK_USER_EMAIL.to_string(),
+        commit_date: Some(mock_timestamp()),
+        dt_since: "just now".to_string(),
+        tree: vec![],
+    }
+}
+
+// ─── auth ──────────────────────────────────────────────────────────────────
+
+async fn server_info() -> Response {
+    Json(json!({ "version": "2.4.0", "features": ["project_history"] })).into_response()
+}
+
+fn token_response() -> Response {
+    Json(json!({ "token": MOCK_OAUTH_TOKEN, "user": MOCK_USER_EMAIL })).into_response()
+}
+
+async fn get_auth_token(headers: HeaderMap) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    token_response()
+}
+
+async fn delete_auth_token(headers: HeaderMap) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[derive(Deserialize)]
+struct Credentials {
+    email: String,
+    password: String,
+}
+
+async fn post_auth_token(Json(credentials): Json<Credentials>) -> Response {
+    if credentials.email != MOCK_USER_EMAIL || credentials.password != MOCK_USER_PASSWORD {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "Unable to log in with provided credentials.",
+        );
+    }
+    token_response()
+}
+
+// ─── projects ──────────────────────────────────────────────────────────────
+
+async fn list_projects(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(params): Query<PageParams>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let state = state.lock().expect("mock state lock poisoned");
+    let projects: Vec<&ProjectInfo> = state.projects.iter().map(|p| &p.info).collect();
+    let body = match state.projects_page_size {
+        None => serde_json::to_vec(&projects),
+        Some(default_page_size) => {
+            let page = params.page.unwrap_or(1);
+            let page_size = params.page_size.unwrap_or(default_page_size).max(1);
+            let start = match page.checked_sub(1).map(|p| p * page_size) {
+                Some(start) if start < projects.len() || page == 1 => start,
+                _ => return error(StatusCode::NOT_FOUND, "Invalid page."),
+            };
+            let results = &projects[start..(start + page_size).min(projects.len())];
+            let page_url = |page: usize| {
+                format!(
+                    "http://{}/api/v2/projects/?page={page}&page_size={page_size}",
+                    host(&headers)
+                )
+            };
+            serde_json::to_vec(&json!({
+                "count": projects.len(),
+                "next": (page * page_size < projects.len()).then(|| page_url(page + 1)),
+                "previous": (page > 1).then(|| page_url(page - 1)),
+                "results": results,
+            }))
+        }
+    }
+    .expect("project list serializes");
+    // Strong validator derived from the body, like Django's ConditionalGetMiddleware.
+    let etag = format!("\"{:016x}\"", {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        hasher.finish()
+    });
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| value.as_bytes() == etag.as_bytes());
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+    (
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::ETAG, etag),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+async fn create_project(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let field = |name: &str| body[name].as_str().unwrap_or_default().to_string();
+    let name = field("name");
+    if name.is_empty() {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "name: This field may not be blank.",
+        );
+    }
+    let project_type = serde_json::from_value(body["type"].clone()).unwrap_or(ProjectType::Ignored);
+    let mut info = new_project_info(name, field("description"), field("country"), project_type);
+    info.latitude = body["latitude"].as_str().and_then(|s| s.parse().ok());
+    info.longitude = body["longitude"].as_str().and_then(|s| s.parse().ok());
+
+    let mut state = state.lock().expect("mock state lock poisoned");
+    state.projects.push(MockProject::new(info.clone()));
+    (StatusCode::CREATED, Json(info)).into_response()
+}
+
+async fn get_project(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    match find_project(&mut state, id) {
+        Some(project) => Json(&project.info).into_response(),
+        None => not_found(),
+    }
+}
+
+async fn update_project(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Json(body): Json<Value>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    if !project.info.can_edit_metadata() {
+        return error(
+            StatusCode::FORBIDDEN,
+            "You do not have permission to perform this action.",
+        );
+    }
+    if body["name"].as_str().is_some_and(str::is_empty) {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "name: This field may not be blank.",
+        );
+    }
+    let info = &mut project.info;
+    if let Some(name) = body["name"].as_str() {
+        info.name = name.to_string();
+    }
+    if let Some(description) = body["description"].as_str() {
+        info.description = description.to_string();
+    }
+    if let Some(country) = body["country"].as_str() {
+        info.country = country.to_string();
+    }
+    // Present-but-null clears a coordinate; absent leaves it unchanged.
+    if let Some(latitude) = body.get("latitude") {
+        info.latitude = latitude.as_str().and_then(|s| s.parse().ok());
+    }
+    if let Some(longitude) = body.get("longitude") {
+        info.longitude = longitude.as_str().and_then(|s| s.parse().ok());
+    }
+    Json(&project.info).into_response()
+}
+
+async fn acquire_mutex(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    match &project.info.active_mutex {
+        Some(mutex) if mutex.user != MOCK_USER_EMAIL => error(
+            StatusCode::CONFLICT,
+            &format!("Project is locked by {}.", mutex.user),
+        ),
+        _ => {
+            project.info.active_mutex = Some(new_mutex(MOCK_USER_EMAIL));
+            Json(&project.info).into_response()
+        }
+    }
+}
+
+async fn release_mutex(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    match &project.info.active_mutex {
+        Some(mutex) if mutex.user != MOCK_USER_EMAIL => error(
+            StatusCode::FORBIDDEN,
+            "You do not own the mutex on this project.",
+        ),
+        _ => {
+            project.info.active_mutex = None;
+            Json(&project.info).into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PageParams {
+    page: Option<usize>,
+    page_size: Option<usize>,
+}
+
+async fn list_commits(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PageParams>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    let page = params.page.unwrap_or(1);
+    let page_size = params.page_size.unwrap_or(MOCK_DEFAULT_PAGE_SIZE).max(1);
+    let newest_first: Vec<&CommitInfo> =
+        project.revisions.iter().rev().map(|r| &r.commit).collect();
+    let start = match page.checked_sub(1).map(|p| p * page_size) {
+        // Like the real API, page 1 of an empty listing is valid.
+        Some(start) if start < newest_first.len() || page == 1 => start,
+        _ => return error(StatusCode::NOT_FOUND, "Invalid page."),
+    };
+    let results = &newest_first[start..(start + page_size).min(newest_first.len())];
+    let host = host(&headers);
+    let page_url = |page: usize| {
+        format!("http://{host}/api/v2/projects/{id}/commits/?page={page}&page_size={page_size}")
+    };
+    let next = (page * page_size < newest_first.len()).then(|| page_url(page + 1));
+    let previous = (page > 1).then(|| page_url(page - 1));
+    Json(json!({
+        "count": newest_first.len(),
+        "next": next,
+        "previous": previous,
+        "results": results,
+    }))
+    .into_response()
+}
+
+async fn list_user_permissions(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    let own = UserPermission {
+        user: MOCK_USER_EMAIL.to_string(),
+        level: project.info.permission.clone(),
+    };
+    let users: Vec<&UserPermission> = std::iter::once(&own)
+        .chain(&project.collaborators)
+        .collect();
+    Json(users).into_response()
+}
+
+async fn list_team_permissions(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    match find_project(&mut state, id) {
+        Some(project) => Json(&project.teams).into_response(),
+        None => not_found(),
+    }
+}
+
+/// ZIP body with the `Repr-Digest` a checksum-aware server sends.
+fn zip_response(zip: Vec<u8>, stall: bool) -> Response {
+    let digest = format!("sha-256=:{}:", BASE64_STANDARD.encode(Sha256::digest(&zip)));
+    let headers = [
+        (header::CONTENT_TYPE, "application/zip".to_string()),
+        (header::CONTENT_LENGTH, zip.len().to_string()),
+        (HeaderName::from_static("repr-digest"), digest),
+    ];
+    if !stall {
+        return (headers, zip).into_response();
+    }
+    let first_half = zip[..zip.len() / 2].to_vec();
+    let body = stream::once(async { Ok::<_, std::io::Error>(first_half) }).chain(stream::pending());
+    (headers, Body::from_stream(body)).into_response()
+}
+
+async fn download_zip(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    match project.latest_zip() {
+        Some(zip) => zip_response(zip.to_vec(), state.stall_downloads),
+        None => error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "This project does not have any data yet.",
+        ),
+    }
+}
+
+async fn download_revision_zip(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path((id, commit_id)): Path<(Uuid, String)>,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    match project.revisions.iter().find(|r| r.commit.id == commit_id) {
+        Some(revision) => zip_response(revision.zip.clone(), state.stall_downloads),
+        None => error(StatusCode::NOT_FOUND, "Commit not found."),
+    }
+}
+
+async fn upload_zip(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Response {
+    if !is_authorized(&headers) {
+        return unauthorized();
+    }
+    let mut message = None;
+    let mut artifact = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name() {
+            Some("message") => message = field.text().await.ok(),
+            Some("artifact") => artifact = field.bytes().await.ok(),
+            _ => {}
+        }
+    }
+
+    let mut state = state.lock().expect("mock state lock poisoned");
+    let Some(project) = find_project(&mut state, id) else {
+        return not_found();
+    };
+    if project
+        .info
+        .active_mutex
+        .as_ref()
+        .is_none_or(|mutex| mutex.user != MOCK_USER_EMAIL)
+    {
+        return error(
+            StatusCode::CONFLICT,
+            "You must acquire the project mutex before uploading.",
+        );
+    }
+    let (Some(message), Some(artifact)) = (message, artifact) else {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "Both `message` and `artifact` are required.",
+        );
+    };
+    if project.latest_zip() == Some(&artifact[..]) {
+        return StatusCode::NOT_MODIFIED.into_response();
+    }
+    project.push_revision(&message, artifact.to_vec());
+    Json(&project.info).into_response()
+}
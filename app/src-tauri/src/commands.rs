@@ -3,12 +3,21 @@ use crate::{
     project_management::LocalProject,
     self_update::{REPO_URL, open_latest_release_url},
     state::AppState,
-    user_prefs::UserPrefs,
 };
-use common::{Error, api_types::ProjectSaveResult};
+use common::{
+    ClientSettings, Error, OauthToken,
+    api_types::{
+        CommitInfo, InstanceProbe, Page, ProjectMetadata, ProjectPermissions, ProjectSaveResult,
+    },
+};
 use log::info;
 use serde::Serialize;
-use std::{path::PathBuf, process::Command, sync::mpsc, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc,
+    time::Duration,
+};
 use tauri::{AppHandle, Manager, State, Url};
 use tauri_plugin_dialog::{DialogExt, FilePath};
 use uuid::Uuid;
@@ -87,31 +96,65 @@ pub fn open_latest_release() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn sign_out(app_handle: AppHandle) -> Result<(), String> {
+pub async fn sign_out(app_handle: AppHandle) -> Result<(), String> {
     let app_state = app_handle.state::<AppState>();
-    app_state.sign_out(&app_handle).map_err(|e| e.to_string())?;
+    app_state
+        .sign_out(&app_handle)
+        .await
+        .map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Switch to the saved profile `profile_id`, or with `None` go to the
+/// sign-in screen to add another account. Refused while the open project
+/// is transferring or has unsaved changes.
+#[tauri::command]
+pub async fn switch_profile(app_handle: AppHandle, profile_id: Option<Uuid>) -> Result<(), String> {
+    let app_state = app_handle.state::<AppState>();
+    app_state
+        .switch_profile(&app_handle, profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Check `instance` before signing in: reachability, certificate, v2 API
+/// and the version and features the server reports.
+#[tauri::command]
+pub async fn probe_instance(
+    app_state: State<'_, AppState>,
+    instance: Url,
+) -> Result<InstanceProbe, Error> {
+    Ok(app_state.api().probe_instance(instance).await)
+}
+
 #[tauri::command]
 pub async fn auth_request(
     app_handle: AppHandle,
     email: Option<String>,
     password: Option<String>,
-    oauth: Option<String>,
+    oauth: Option<OauthToken>,
     instance: Url,
 ) -> Result<(), String> {
     info!("Starting auth request");
+    let app_state = app_handle.state::<AppState>();
     let api_info = if let Some(oauth_token) = oauth {
-        api::auth::authorize_with_token(instance, &oauth_token).await?
+        app_state
+            .api()
+            .authorize_with_token(instance, &oauth_token)
+            .await?
     } else {
         let email = email.ok_or("Email is required for email/password authentication")?;
         let password = password.ok_or("Password is required for email/password authentication")?;
-        api::auth::authorize_with_email(instance, &email, &password).await?
+        app_state
+            .api()
+            .authorize_with_email(instance, &email, &password)
+            .await?
     };
     info!("Auth request successful, updating user preferences");
-    let prefs = UserPrefs::new(api_info);
-    let app_state = app_handle.state::<AppState>();
+    let prefs = app_state
+        .user_prefs()
+        .with_signed_in(api_info)
+        .with_client_settings(api::client_settings());
     app_state
         .update_user_prefs(prefs)
         .map_err(|e| e.to_string())?;
@@ -119,29 +162,73 @@ pub async fn auth_request(
     Ok(())
 }
 
+#[tauri::command]
+pub fn client_settings() -> ClientSettings {
+    api::client_settings()
+}
+
+/// Rebuild the HTTP client from `client_settings` and persist them. Invalid
+/// settings are rejected before anything is saved.
+#[tauri::command]
+pub fn update_client_settings(
+    app_handle: AppHandle,
+    client_settings: ClientSettings,
+) -> Result<(), Error> {
+    api::set_client_settings(&client_settings)?;
+    let app_state = app_handle.state::<AppState>();
+    let prefs = app_state.user_prefs().with_client_settings(client_settings);
+    app_state.update_user_prefs(prefs)
+}
+
 #[tauri::command]
 pub fn open_project(_app_state: State<'_, AppState>, project_id: Uuid) -> Result<(), Error> {
-    let project_dir = compass_project_working_path(project_id);
+    let _compass_pid = open_project_dir(project_id, &compass_project_working_path(project_id))?;
+    #[cfg(target_os = "windows")]
+    _app_state.set_compass_pid(_compass_pid);
+    Ok(())
+}
+
+/// Check out a historical revision read-only and open it like `open_project`.
+/// Compass is not tracked for a revision checkout: it cannot hold locks on
+/// the working copy.
+#[tauri::command]
+pub async fn open_project_revision(
+    app_handle: AppHandle,
+    project_id: Uuid,
+    commit_id: String,
+) -> Result<(), Error> {
+    let app_state = app_handle.state::<AppState>();
+    let checkout_path = app_state
+        .checkout_project_revision(project_id, &commit_id)
+        .await?;
+    open_project_dir(project_id, &checkout_path)?;
+    Ok(())
+}
+
+/// Open an unpacked project folder: with Compass on Windows, in the system
+/// file browser elsewhere. Returns the Compass process id when it was
+/// launched.
+fn open_project_dir(_project_id: Uuid, project_dir: &Path) -> Result<Option<u32>, Error> {
     if !project_dir.exists() {
-        return Err(Error::ProjectNotFound(project_dir));
+        return Err(Error::ProjectNotFound(project_dir.to_path_buf()));
     }
 
     // Just open the folder in system file explorer
     #[cfg(target_os = "macos")]
     {
         Command::new("open")
-            .arg(&project_dir)
+            .arg(project_dir)
             .spawn()
             .map_err(|e| Error::OsCommand(e.to_string()))?;
-        Ok(())
+        Ok(None)
     }
     #[cfg(target_os = "linux")]
     {
         Command::new("xdg-open")
-            .arg(&project_dir)
+            .arg(project_dir)
             .spawn()
             .map_err(|e| Error::OsCommand(e.to_string()))?;
-        Ok(())
+        Ok(None)
     }
 
     // On Windows, actually try to open the project with Compass if possible
@@ -155,7 +242,7 @@ pub fn open_project(_app_state: State<'_, AppState>, project_id: Uuid) -> Result
             // If compass isn't found, open the folder in explorer, but return an error so the UI can notify the user
             return Err(Error::CompassNotFound);
         }
-        let project_path = LocalProject::mak_file_path(project_id)?;
+        let project_path = LocalProject::mak_file_path_in(_project_id, project_dir)?;
 
         log::info!(
             "Opening {} with Compass: {}",
@@ -174,9 +261,7 @@ pub fn open_project(_app_state: State<'_, AppState>, project_id: Uuid) -> Result
                 Err(Error::CompassExecutable(e.to_string()))
             }
         }?;
-        let pid = child_process.id();
-        _app_state.set_compass_pid(Some(pid));
-        Ok(())
+        Ok(Some(child_process.id()))
     }
 }
 
@@ -190,6 +275,22 @@ pub async fn save_project(
     app_state.save_active_project(commit_message).await
 }
 
+#[tauri::command]
+pub async fn revert_project(
+    app_handle: AppHandle,
+    commit_id: String,
+) -> Result<ProjectSaveResult, Error> {
+    info!("Reverting active project to revision {commit_id}");
+    let app_state = app_handle.state::<AppState>();
+    app_state.revert_active_project(commit_id).await
+}
+
+#[tauri::command]
+pub fn cancel_save_project(app_handle: AppHandle) -> bool {
+    let app_state = app_handle.state::<AppState>();
+    app_state.cancel_active_project_save()
+}
+
 async fn pick_compass_project_file_path(app_handle: &AppHandle) -> Result<PathBuf, Error> {
     let (tx, rx) = mpsc::channel::<Option<FilePath>>();
     app_handle
@@ -228,6 +329,9 @@ async fn import_project_from_path(
 ) -> Result<(), Error> {
     info!("Selected MAK file: {}", mak_path.display());
     info!("Importing into Compass project: {:?}", project_id);
+    app_handle
+        .state::<AppState>()
+        .require_edit_access(project_id)?;
 
     if clear_working_copy {
         LocalProject::clear_working_copy_compass_artifacts(project_id)?;
@@ -312,13 +416,43 @@ pub async fn release_project_mutex(
     app_state: State<'_, AppState>,
     project_id: Uuid,
 ) -> Result<(), String> {
-    api::project::release_project_mutex(&app_state.api_info(), project_id)
+    let released = app_state
+        .api()
+        .release_project_mutex(&app_state.api_info(), project_id)
+        .await;
+    app_state
+        .check_session(released)
         .await
         .map_err(|e| e.to_string())?;
     // Always return success (fire and forget)
     Ok(())
 }
 
+#[tauri::command]
+pub async fn fetch_project_commits(
+    app_state: State<'_, AppState>,
+    project_id: Uuid,
+    page: u32,
+) -> Result<Page<CommitInfo>, Error> {
+    let commits = app_state
+        .api()
+        .fetch_project_commits(&app_state.api_info(), project_id, page)
+        .await;
+    app_state.check_session(commits).await
+}
+
+#[tauri::command]
+pub async fn fetch_project_permissions(
+    app_state: State<'_, AppState>,
+    project_id: Uuid,
+) -> Result<ProjectPermissions, Error> {
+    let permissions = app_state
+        .api()
+        .fetch_project_permissions(&app_state.api_info(), project_id)
+        .await;
+    app_state.check_session(permissions).await
+}
+
 #[tauri::command]
 pub async fn create_project(
     app_handle: AppHandle,
@@ -329,21 +463,37 @@ pub async fn create_project(
     longitude: Option<String>,
 ) -> Result<(), Error> {
     let app_state = app_handle.state::<AppState>();
-    let project_info = api::project::create_project(
-        &app_state.api_info(),
-        name,
-        description,
-        country,
-        latitude,
-        longitude,
-    )
-    .await?;
+    let created = app_state
+        .api()
+        .create_project(
+            &app_state.api_info(),
+            name,
+            description,
+            country,
+            latitude,
+            longitude,
+        )
+        .await;
+    let project_info = app_state.check_session(created).await?;
     let id = project_info.id;
     app_state.update_local_project(project_info).await?;
     app_state.set_active_project(Some(id)).await?;
     Ok(())
 }
 
+#[tauri::command]
+pub async fn update_project(
+    app_handle: AppHandle,
+    project_id: Uuid,
+    metadata: ProjectMetadata,
+) -> Result<(), Error> {
+    info!("Updating metadata of project {project_id}");
+    let app_state = app_handle.state::<AppState>();
+    app_state
+        .update_project_metadata(project_id, metadata)
+        .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::about_info_from_package_info;
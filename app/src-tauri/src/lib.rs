@@ -3,16 +3,22 @@ mod commands;
 mod macos_menu;
 mod paths;
 mod project_management;
+mod secret_store;
 mod self_update;
 mod state;
+#[cfg(test)]
+mod test_support;
 mod user_prefs;
 
 use crate::{
     commands::{
-        about_info, auth_request, check_for_updates_now, clear_active_project, create_project,
-        discard_changes, dismiss_update_notification, ensure_initialized, import_compass_project,
-        open_latest_release, open_project, pick_compass_project_file, reimport_compass_project,
-        release_project_mutex, report_frontend_error, save_project, set_active_project, sign_out,
+        about_info, auth_request, cancel_save_project, check_for_updates_now, clear_active_project,
+        client_settings, create_project, discard_changes, dismiss_update_notification,
+        ensure_initialized, fetch_project_commits, fetch_project_permissions,
+        import_compass_project, open_latest_release, open_project, open_project_revision,
+        pick_compass_project_file, probe_instance, reimport_compass_project, release_project_mutex,
+        report_frontend_error, revert_project, save_project, set_active_project, sign_out,
+        switch_profile, update_client_settings, update_project,
     },
     paths::{compass_home, ensure_app_dir_exists, init_file_logger},
     state::{ABOUT_MENU_ID, AppState, CHECK_FOR_UPDATES_MENU_ID, SIGN_OUT_MENU_ID},
@@ -81,30 +87,50 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             about_info,
             auth_request,
+            cancel_save_project,
             clear_active_project,
+            client_settings,
             check_for_updates_now,
             create_project,
             discard_changes,
             dismiss_update_notification,
             ensure_initialized,
+            fetch_project_commits,
+            fetch_project_permissions,
             sign_out,
+            switch_profile,
             import_compass_project,
             open_latest_release,
             pick_compass_project_file,
+            probe_instance,
             reimport_compass_project,
             report_frontend_error,
+            revert_project,
             open_project,
+            open_project_revision,
             release_project_mutex,
             set_active_project,
             save_project,
+            update_client_settings,
+            update_project,
         ])
         .manage(AppState::new())
         .setup(|app| {
             app.on_menu_event(move |app_handle, event| match event.id().0.as_str() {
                 SIGN_OUT_MENU_ID => {
                     log::info!("Sign out menu item clicked");
-                    let app_state = app_handle.state::<AppState>();
-                    app_state.sign_out(app_handle).ok();
+                    let app_handle = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let app_state = app_handle.state::<AppState>();
+                        if let Err(e) = app_state.sign_out(&app_handle).await {
+                            app_handle
+                                .dialog()
+                                .message(e.to_string())
+                                .title("Can't Sign Out")
+                                .kind(MessageDialogKind::Warning)
+                                .show(|_| {});
+                        }
+                    });
                 }
                 ABOUT_MENU_ID => {
                     log::info!("About menu item clicked");
@@ -167,7 +193,9 @@ pub fn run() {
                     );
                     tauri::async_runtime::block_on(async {
                         let app_state = app_handle.state::<AppState>();
-                        api::project::release_project_mutex(&app_state.api_info(), project_id)
+                        app_state
+                            .api()
+                            .release_project_mutex(&app_state.api_info(), project_id)
                             .await
                             .ok();
                     });
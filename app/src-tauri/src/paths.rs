@@ -1,7 +1,8 @@
 use common::Error;
+use log::{info, warn};
 use std::{
     path::{Path, PathBuf},
-    sync::{LazyLock, OnceLock},
+    sync::{LazyLock, OnceLock, RwLock},
 };
 use uuid::Uuid;
 
@@ -53,18 +54,69 @@ pub fn compass_home() -> &'static Path {
     &COMPASS_HOME_DIR
 }
 
+/// Profile whose projects `compass_project_path` resolves to; `None` while
+/// signed out. `AppState` only changes it with no project open, so no
+/// transfer or working copy is caught between two folders.
+static ACTIVE_PROFILE: RwLock<Option<Uuid>> = RwLock::new(None);
+
 /// Return a clone of the compass projects folder path.
 pub fn compass_dir_path() -> &'static Path {
     &COMPASS_PROJECT_DIR
 }
 
-/// Get the path for a specific project in the compass folder.
-pub fn compass_project_path(project_id: Uuid) -> PathBuf {
+/// Switch the project folders to those of `profile_id`. The first profile
+/// activated adopts the projects downloaded before profiles existed.
+pub fn set_active_profile(profile_id: Option<Uuid>) {
+    *ACTIVE_PROFILE.write().unwrap() = profile_id;
+    if let Some(profile_id) = profile_id {
+        adopt_legacy_projects(compass_dir_path(), &compass_profile_path(profile_id));
+    }
+}
+
+/// Get the folder holding the projects of one profile.
+pub fn compass_profile_path(profile_id: Uuid) -> PathBuf {
     let mut path = compass_dir_path().to_path_buf();
+    path.push(profile_id.to_string());
+    path
+}
+
+/// Get the folder holding the active profile's projects.
+fn active_profile_path() -> PathBuf {
+    match *ACTIVE_PROFILE.read().unwrap() {
+        Some(profile_id) => compass_profile_path(profile_id),
+        None => compass_dir_path().to_path_buf(),
+    }
+}
+
+/// Get the path for a specific project in the active profile's folder.
+pub fn compass_project_path(project_id: Uuid) -> PathBuf {
+    let mut path = active_profile_path();
     path.push(project_id.to_string());
     path
 }
 
+/// Move project folders sitting directly in `projects_dir` (recognizable by
+/// their `index` folder) into `profile_dir`. A folder that can't be moved,
+/// e.g. because Compass holds one of its files open, is left where it was.
+fn adopt_legacy_projects(projects_dir: &Path, profile_dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(projects_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let legacy = entry.path();
+        if !legacy.join("index").is_dir() {
+            continue;
+        }
+        let adopted = profile_dir.join(entry.file_name());
+        let moved =
+            std::fs::create_dir_all(profile_dir).and_then(|()| std::fs::rename(&legacy, &adopted));
+        match moved {
+            Ok(()) => info!("Moved {} to {}", legacy.display(), adopted.display()),
+            Err(e) => warn!("Couldn't move {} into its profile: {e}", legacy.display()),
+        }
+    }
+}
+
 /// Get the path for a specific project in the compass folder.
 pub fn compass_project_index_path(project_id: Uuid) -> PathBuf {
     let mut path = compass_project_path(project_id);
@@ -79,6 +131,24 @@ pub fn compass_project_working_path(project_id: Uuid) -> PathBuf {
     path
 }
 
+/// Get the folder holding read-only checkouts of a project's historical
+/// revisions. It sits outside the project folder, so a checkout never makes
+/// a remote-only project look downloaded and discarding the working copy
+/// leaves it alone.
+pub fn compass_project_revisions_path(project_id: Uuid) -> PathBuf {
+    let mut path = active_profile_path();
+    path.push("revisions");
+    path.push(project_id.to_string());
+    path
+}
+
+/// Get the read-only checkout folder of the revision recorded by `commit_id`.
+pub fn compass_project_revision_path(project_id: Uuid, commit_id: &str) -> PathBuf {
+    let mut path = compass_project_revisions_path(project_id);
+    path.push(commit_id);
+    path
+}
+
 /// Ensure the application directory exists, creating it if necessary.
 pub fn ensure_app_dir_exists() -> std::io::Result<()> {
     std::fs::create_dir_all(compass_home())?;
@@ -190,6 +260,28 @@ mod tests {
         assert!(path.is_dir(), "Path should be a directory");
     }
 
+    #[test]
+    fn adopt_legacy_projects_moves_only_project_folders() {
+        let projects_dir = compass_home().join("adopt_legacy_projects_test");
+        let project = projects_dir.join(Uuid::new_v4().to_string());
+        let other_profile = projects_dir.join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(project.join("index")).unwrap();
+        std::fs::create_dir_all(other_profile.join(Uuid::new_v4().to_string())).unwrap();
+        let profile_dir = projects_dir.join(Uuid::new_v4().to_string());
+
+        adopt_legacy_projects(&projects_dir, &profile_dir);
+
+        assert!(!project.exists());
+        assert!(
+            profile_dir
+                .join(project.file_name().unwrap())
+                .join("index")
+                .is_dir()
+        );
+        assert!(other_profile.is_dir(), "profile folders must stay put");
+        std::fs::remove_dir_all(&projects_dir).unwrap();
+    }
+
     #[test]
     fn ensure_app_dir_is_idempotent() {
         // Calling ensure_app_dir_exists multiple times should work
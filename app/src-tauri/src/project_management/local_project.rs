@@ -277,21 +277,24 @@ impl LocalProject {
         }
         Ok(())
     }
+    /// Path of the `.mak` file of the project unpacked in `project_dir` (the
+    /// working copy or a revision checkout).
     #[cfg(target_os = "windows")]
-    pub fn mak_file_path(id: Uuid) -> Result<PathBuf, Error> {
-        let local_project = LocalProject::load_working_project(id)?;
+    pub fn mak_file_path_in(id: Uuid, project_dir: &Path) -> Result<PathBuf, Error> {
+        let local_project = LocalProject::load_project_in(project_dir)?;
         let mak_file_name = local_project
             .project_map
             .mak_file
             .ok_or(Error::NoProjectData(id))?;
-        let mut mak_path = compass_project_working_path(id);
-        mak_path.push(&mak_file_name);
-        Ok(mak_path)
+        Ok(project_dir.join(mak_file_name))
     }
 
     fn load_working_project(id: Uuid) -> Result<Self, Error> {
-        let mut project_path = compass_project_working_path(id);
-        project_path.push(SPELEODB_COMPASS_PROJECT_FILE);
+        LocalProject::load_project_in(&compass_project_working_path(id))
+    }
+
+    fn load_project_in(project_dir: &Path) -> Result<Self, Error> {
+        let project_path = project_dir.join(SPELEODB_COMPASS_PROJECT_FILE);
         let project_data = std::fs::read_to_string(&project_path)
             .map_err(|_| Error::ProjectNotFound(project_path.clone()))?;
         let project: LocalProject =
@@ -300,13 +303,7 @@ impl LocalProject {
     }
 
     fn load_index_project(id: Uuid) -> Result<Self, Error> {
-        let mut project_path = compass_project_index_path(id);
-        project_path.push(SPELEODB_COMPASS_PROJECT_FILE);
-        let project_data = std::fs::read_to_string(&project_path)
-            .map_err(|_| Error::ProjectNotFound(project_path.clone()))?;
-        let project: LocalProject =
-            toml::from_str(&project_data).map_err(|e| Error::Deserialization(e.to_string()))?;
-        Ok(project)
+        LocalProject::load_project_in(&compass_project_index_path(id))
     }
 
     pub fn working_copy_exists(id: Uuid) -> bool {
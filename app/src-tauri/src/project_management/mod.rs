@@ -3,11 +3,14 @@ mod revision;
 
 pub use {local_project::LocalProject, revision::SpeleoDbProjectRevision};
 
-use crate::paths::{
-    compass_project_index_path, compass_project_path, compass_project_working_path,
-    ensure_compass_project_dirs_exist,
+use crate::{
+    paths::{
+        compass_project_index_path, compass_project_path, compass_project_revision_path,
+        compass_project_working_path, ensure_compass_project_dirs_exist,
+    },
+    self_update::DownloadProgress,
 };
-use bytes::Bytes;
+use api::{SpeleoDbApi, project::CancellationToken};
 use common::{
     ApiInfo, Error,
     api_types::{CommitInfo, ProjectInfo},
@@ -16,13 +19,14 @@ use common::{
 use log::{debug, error, info, warn};
 use std::{
     fs::{File, copy, create_dir_all, read_dir},
-    path::Path,
+    path::{Path, PathBuf},
 };
 use uuid::Uuid;
 use zip::ZipArchive;
 
 pub const SPELEODB_COMPASS_PROJECT_FILE: &str = "compass.toml";
 const SPELEODB_PROJECT_REVISION_FILE: &str = ".revision.txt";
+const SPELEODB_PROJECT_CHECKSUM_FILE: &str = ".revision.sha256";
 const AUTOMATED_PROJECT_CREATION_COMMIT_MESSAGE: &str = "[Automated] Project Creation";
 
 // Information about the status of a Compass project.
@@ -88,7 +92,11 @@ impl ProjectManager {
         ProjectStatus::new(local_status, self.project_info.clone())
     }
 
-    pub async fn update_project(&mut self, api_info: &ApiInfo) -> Result<ProjectStatus, Error> {
+    pub async fn update_project(
+        &mut self,
+        api: &dyn SpeleoDbApi,
+        api_info: &ApiInfo,
+    ) -> Result<ProjectStatus, Error> {
         // Check local project status, and update if clean and out of date
         let project_status = self.local_project_status();
         match project_status {
@@ -107,7 +115,7 @@ impl ProjectManager {
                     "Local working copy for project {} is out of date, updating local copy",
                     self.project_info.name
                 );
-                self.update_local_copies(api_info).await?;
+                self.update_local_copies(api, api_info, |_| {}).await?;
             }
             _ => {}
         }
@@ -121,14 +129,19 @@ impl ProjectManager {
         Ok(self.project_status())
     }
 
-    pub async fn make_local(&self, api_info: &ApiInfo) -> Result<(), Error> {
+    pub async fn make_local(
+        &self,
+        api: &dyn SpeleoDbApi,
+        api_info: &ApiInfo,
+        on_progress: impl FnMut(u8) + Send,
+    ) -> Result<(), Error> {
         let project_status = self.local_project_status();
         if let LocalProjectStatus::RemoteOnly = project_status {
             info!(
                 "Making local copy of remote project: {}",
                 self.project_info.name
             );
-            self.update_local_copies(api_info).await?;
+            self.update_local_copies(api, api_info, on_progress).await?;
             Ok(())
         } else {
             // Project is already local, nothing to do
@@ -138,19 +151,34 @@ impl ProjectManager {
 
     pub async fn save_local_changes(
         &mut self,
+        api: &dyn SpeleoDbApi,
         api_info: &ApiInfo,
         commit_message: String,
+        mut on_progress: impl FnMut(u8) + Send + 'static,
+        cancel: &CancellationToken,
     ) -> Result<ProjectSaveResult, Error> {
         log::info!(
             "Zipping project folder for project: {}",
             self.project_info.name
         );
         let zip_file = LocalProject::pack_zip(self.id())?;
-        let save_result =
-            api::project::upload_project_zip(api_info, self.id(), commit_message, &zip_file)
-                .await?;
+        let mut progress = DownloadProgress::default();
+        let save_result = api
+            .upload_project_zip(
+                api_info,
+                self.id(),
+                commit_message,
+                &zip_file,
+                Box::new(move |chunk_len, content_len| {
+                    if let Some(percent) = progress.record_chunk(chunk_len, content_len) {
+                        on_progress(percent);
+                    }
+                }),
+                cancel,
+            )
+            .await;
         std::fs::remove_file(&zip_file).ok();
-        Ok(save_result)
+        save_result
     }
 
     /// After a successful upload, sync local state without re-downloading.
@@ -170,6 +198,7 @@ impl ProjectManager {
             );
             Error::FileWrite(e.to_string())
         })?;
+        SpeleoDbProjectRevision::save_checksum_for_project(self.id(), None)?;
         if let Some(latest_commit) = self.latest_remote_commit() {
             SpeleoDbProjectRevision::from(latest_commit).save_revision_for_project(self.id())?;
         } else {
@@ -247,40 +276,45 @@ impl ProjectManager {
 
     /// Update the local copy of a Compass project by downloading the latest ZIP from SpeleoDB
     /// and unpacking it into both the index directory and working copy.
+    /// The ZIP is streamed to a temp file; `on_progress` receives the download percentage
+    /// each time it changes (only when the server reports a content length).
     /// Returns the updated local project status Ok(LocalProjectStatus::UpToDate) if successful.
     /// Returns Ok(LocalProjectStatus::EmptyLocal) if there is no project data on the server.
+    /// A download that fails its checksum is discarded without touching the index.
     pub async fn update_local_copies(
         &self,
+        api: &dyn SpeleoDbApi,
         api_info: &ApiInfo,
+        mut on_progress: impl FnMut(u8) + Send,
     ) -> Result<LocalProjectStatus, Error> {
         ensure_compass_project_dirs_exist(self.id())?;
-        log::info!("Downloading project ZIP from");
-        match api::project::download_project_zip(api_info, self.id()).await {
-            Ok(bytes) => {
-                log::info!("Downloaded ZIP ({} bytes)", bytes.len());
-                unpack_project_zip(self.id(), bytes)?;
-                // Copy index to working copy
-                let src = compass_project_index_path(self.id());
-                let dst = compass_project_working_path(self.id());
-                sync_dir_all(&src, &dst).map_err(|e| {
-                    error!(
-                        "Failed to copy index to working copy ({} -> {}): {}",
-                        src.display(),
-                        dst.display(),
-                        e
-                    );
-                    Error::FileWrite(e.to_string())
-                })?;
-                if let Some(latest_commit) = self.latest_remote_commit() {
-                    SpeleoDbProjectRevision::from(latest_commit)
-                        .save_revision_for_project(self.id())?;
-                } else {
-                    warn!(
-                        "Downloaded project ZIP for project {} but latest commit metadata is missing; skipping local revision update",
-                        self.id()
-                    );
-                }
-                Ok(LocalProjectStatus::UpToDate)
+        let zip_path = std::env::temp_dir().join(format!("project_{}_download.zip", self.id()));
+        info!("Downloading project ZIP to: {}", zip_path.display());
+        let mut zip_file = tokio::fs::File::create(&zip_path)
+            .await
+            .map_err(|e| Error::FileWrite(e.to_string()))?;
+        let mut progress = DownloadProgress::default();
+        let download = api
+            .download_project_zip(
+                api_info,
+                self.id(),
+                &mut zip_file,
+                &mut |chunk_len, content_len| {
+                    if let Some(percent) = progress.record_chunk(chunk_len, content_len) {
+                        on_progress(percent);
+                    }
+                },
+            )
+            .await;
+        drop(zip_file);
+        let result = match download {
+            Ok(download) => {
+                log::info!(
+                    "Downloaded ZIP ({} bytes, sha256 {})",
+                    download.size,
+                    download.sha256
+                );
+                self.install_downloaded_zip(&zip_path, &download.sha256)
             }
             Err(Error::NoProjectData(_)) => {
                 log::error!(
@@ -294,24 +328,266 @@ impl ProjectManager {
                 log::error!("Failed to download project ZIP: {}", e);
                 Err(e)
             }
+        };
+        cleanup_temp_zip(&zip_path);
+        result
+    }
+
+    /// Download the revision recorded by `commit_id` and unpack it into its own
+    /// read-only folder (see `compass_project_revision_path`), leaving the
+    /// index and working copy untouched. Revisions never change, so an
+    /// existing checkout is reused without downloading again. Returns the
+    /// checkout folder.
+    pub async fn checkout_revision(
+        &self,
+        api: &dyn SpeleoDbApi,
+        api_info: &ApiInfo,
+        commit_id: &str,
+        on_progress: impl FnMut(u8) + Send,
+    ) -> Result<PathBuf, Error> {
+        if !is_valid_commit_id(commit_id) {
+            return Err(Error::NotFound(format!("Invalid commit id: {commit_id}")));
+        }
+        let checkout_path = compass_project_revision_path(self.id(), commit_id);
+        if checkout_path.exists() {
+            info!(
+                "Reusing checkout of revision {commit_id} for project {}",
+                self.id()
+            );
+            return Ok(checkout_path);
+        }
+
+        let zip_path = self
+            .download_revision_zip(api, api_info, commit_id, on_progress)
+            .await?;
+        // Unpack next to the final folder and rename it into place, so an
+        // interrupted checkout is never mistaken for a complete one.
+        let staging_path = checkout_path.with_extension("partial");
+        let result = unpack_zip_into(&zip_path, &staging_path).and_then(|_| {
+            std::fs::rename(&staging_path, &checkout_path)
+                .and_then(|_| set_tree_read_only(&checkout_path))
+                .map_err(|e| {
+                    error!(
+                        "Failed to finalize revision checkout {}: {}",
+                        checkout_path.display(),
+                        e
+                    );
+                    Error::FileWrite(e.to_string())
+                })
+        });
+        cleanup_temp_zip(&zip_path);
+        result?;
+        info!(
+            "Checked out revision {commit_id} to: {}",
+            checkout_path.display()
+        );
+        Ok(checkout_path)
+    }
+
+    /// Make the revision recorded by `commit_id` the latest one again by
+    /// uploading it as a new commit. The revision is unpacked next to the
+    /// working copy and swapped in only once complete; the previous working
+    /// copy is kept aside and swapped back if the upload fails or is
+    /// cancelled.
+    ///
+    /// Only allowed while the current user holds the project mutex and the
+    /// working copy has no unsaved changes, since those would be overwritten.
+    pub async fn revert_to_revision(
+        &mut self,
+        api: &dyn SpeleoDbApi,
+        api_info: &ApiInfo,
+        commit_id: &str,
+        on_progress: impl FnMut(u8) + Send + 'static,
+        cancel: &CancellationToken,
+    ) -> Result<ProjectSaveResult, Error> {
+        let holds_mutex = self
+            .project_info
+            .active_mutex
+            .as_ref()
+            .is_some_and(|mutex| Some(mutex.user.as_str()) == api_info.email());
+        if !holds_mutex {
+            warn!(
+                "Refusing to revert project {} without holding its mutex",
+                self.id()
+            );
+            return Err(Error::ProjectMutexLocked(self.id()));
+        }
+        match self.local_project_status() {
+            LocalProjectStatus::UpToDate | LocalProjectStatus::OutOfDate => {}
+            LocalProjectStatus::Dirty | LocalProjectStatus::DirtyAndOutOfDate => {
+                warn!(
+                    "Refusing to revert project {}: working copy has unsaved changes",
+                    self.id()
+                );
+                return Err(Error::UnsavedChanges(self.id()));
+            }
+            _ => {
+                return Err(Error::ProjectNotFound(compass_project_working_path(
+                    self.id(),
+                )));
+            }
+        }
+        if !is_valid_commit_id(commit_id) {
+            return Err(Error::NotFound(format!("Invalid commit id: {commit_id}")));
+        }
+
+        info!("Reverting project {} to revision {commit_id}", self.id());
+        let zip_path = self
+            .download_revision_zip(api, api_info, commit_id, |_| {})
+            .await?;
+        let working_path = compass_project_working_path(self.id());
+        let staging_path = working_path.with_extension("partial");
+        let previous_path = working_path.with_extension("previous");
+        let unpacked = unpack_zip_into(&zip_path, &staging_path).and_then(|_| {
+            // Fails without touching the working copy if Compass holds one
+            // of its files open.
+            swap_dir_into_place(&working_path, &staging_path, &previous_path).map_err(|e| {
+                error!(
+                    "Failed to replace working copy of project {}: {}",
+                    self.id(),
+                    e
+                );
+                Error::FileWrite(e.to_string())
+            })
+        });
+        cleanup_temp_zip(&zip_path);
+        let result = match unpacked {
+            Ok(()) => {
+                let result = self
+                    .save_local_changes(
+                        api,
+                        api_info,
+                        revert_commit_message(commit_id),
+                        on_progress,
+                        cancel,
+                    )
+                    .await;
+                if let Err(e) = &result {
+                    error!(
+                        "Revert of project {} to {commit_id} failed ({e}), restoring working copy",
+                        self.id()
+                    );
+                    let index_path = compass_project_index_path(self.id());
+                    let restored =
+                        swap_dir_into_place(&working_path, &previous_path, &staging_path)
+                            .or_else(|_| sync_dir_all(&index_path, &working_path));
+                    if let Err(restore_err) = restored {
+                        error!(
+                            "Failed to restore working copy of project {}: {}",
+                            self.id(),
+                            restore_err
+                        );
+                    }
+                }
+                result
+            }
+            Err(e) => Err(e),
+        };
+        for leftover in [&staging_path, &previous_path] {
+            if leftover.exists()
+                && let Err(e) = std::fs::remove_dir_all(leftover)
+            {
+                warn!("Failed to remove {}: {}", leftover.display(), e);
+            }
+        }
+        result
+    }
+
+    /// Download the ZIP of revision `commit_id` to a temp file and return its
+    /// path. The caller owns the file and must `cleanup_temp_zip` it.
+    async fn download_revision_zip(
+        &self,
+        api: &dyn SpeleoDbApi,
+        api_info: &ApiInfo,
+        commit_id: &str,
+        mut on_progress: impl FnMut(u8) + Send,
+    ) -> Result<PathBuf, Error> {
+        let zip_path = std::env::temp_dir().join(format!(
+            "project_{}_revision_{commit_id}_download.zip",
+            self.id()
+        ));
+        info!(
+            "Downloading revision {commit_id} ZIP to: {}",
+            zip_path.display()
+        );
+        let mut zip_file = tokio::fs::File::create(&zip_path)
+            .await
+            .map_err(|e| Error::FileWrite(e.to_string()))?;
+        let mut progress = DownloadProgress::default();
+        let download = api
+            .download_project_revision_zip(
+                api_info,
+                self.id(),
+                commit_id,
+                &mut zip_file,
+                &mut |chunk_len, content_len| {
+                    if let Some(percent) = progress.record_chunk(chunk_len, content_len) {
+                        on_progress(percent);
+                    }
+                },
+            )
+            .await;
+        drop(zip_file);
+        match download {
+            Ok(download) => {
+                info!(
+                    "Downloaded revision {commit_id} ZIP ({} bytes)",
+                    download.size
+                );
+                Ok(zip_path)
+            }
+            Err(e) => {
+                error!("Failed to download revision {commit_id} ZIP: {e}");
+                cleanup_temp_zip(&zip_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Unpack a downloaded ZIP into the index, mirror it into the working copy
+    /// and record the revision and archive checksum it corresponds to.
+    fn install_downloaded_zip(
+        &self,
+        zip_path: &Path,
+        sha256: &str,
+    ) -> Result<LocalProjectStatus, Error> {
+        unpack_project_zip(self.id(), zip_path)?;
+        // Copy index to working copy
+        let src = compass_project_index_path(self.id());
+        let dst = compass_project_working_path(self.id());
+        sync_dir_all(&src, &dst).map_err(|e| {
+            error!(
+                "Failed to copy index to working copy ({} -> {}): {}",
+                src.display(),
+                dst.display(),
+                e
+            );
+            Error::FileWrite(e.to_string())
+        })?;
+        SpeleoDbProjectRevision::save_checksum_for_project(self.id(), Some(sha256))?;
+        if let Some(latest_commit) = self.latest_remote_commit() {
+            SpeleoDbProjectRevision::from(latest_commit).save_revision_for_project(self.id())?;
+        } else {
+            warn!(
+                "Downloaded project ZIP for project {} but latest commit metadata is missing; skipping local revision update",
+                self.id()
+            );
         }
+        Ok(LocalProjectStatus::UpToDate)
     }
 }
 
-// Unpack a project zip directly into the index and return the resulting `CompassProject`.
-fn unpack_project_zip(project_id: Uuid, zip_bytes: Bytes) -> Result<(), Error> {
-    // Create temp zip file
-    let temp_dir = std::env::temp_dir();
-    let zip_filename = format!("project_{}.zip", project_id);
-    let zip_path = temp_dir.join(&zip_filename);
-    info!("Creating zip file in temp folder: {zip_path:?}");
-    std::fs::write(&zip_path, &zip_bytes).map_err(|e| Error::FileWrite(e.to_string()))?;
-    // Unzip the project
-    let file = std::fs::File::open(&zip_path).map_err(|e| Error::FileRead(e.to_string()))?;
+// Unpack a project zip file directly into the index.
+fn unpack_project_zip(project_id: Uuid, zip_path: &Path) -> Result<(), Error> {
+    unpack_zip_into(zip_path, &compass_project_index_path(project_id))
+}
+
+// Replace the contents of `dest_path` with the files of a project zip.
+fn unpack_zip_into(zip_path: &Path, dest_path: &Path) -> Result<(), Error> {
+    let file = std::fs::File::open(zip_path).map_err(|e| Error::FileRead(e.to_string()))?;
     let mut archive = ZipArchive::new(file).map_err(|e| Error::ZipFile(e.to_string()))?;
 
-    let index_path = compass_project_index_path(project_id);
-    reset_dir(&index_path).map_err(|e| Error::FileWrite(e.to_string()))?;
+    reset_dir(dest_path).map_err(|e| Error::FileWrite(e.to_string()))?;
 
     // Extract all files
     for i in 0..archive.len() {
@@ -320,7 +596,7 @@ fn unpack_project_zip(project_id: Uuid, zip_bytes: Bytes) -> Result<(), Error> {
             .map_err(|e| Error::ZipFile(e.to_string()))?;
 
         let file_path = match file.enclosed_name() {
-            Some(path) => index_path.join(path),
+            Some(path) => dest_path.join(path),
             None => continue,
         };
         if file.is_dir() {
@@ -336,10 +612,33 @@ fn unpack_project_zip(project_id: Uuid, zip_bytes: Bytes) -> Result<(), Error> {
             std::io::copy(&mut file, &mut out_file).map_err(|e| Error::FileWrite(e.to_string()))?;
         }
     }
-    // Clean up temp ZIP file
-    cleanup_temp_zip(&zip_path);
 
-    log::info!("Successfully unzipped project to: {}", index_path.display());
+    log::info!("Successfully unzipped project to: {}", dest_path.display());
+    Ok(())
+}
+
+fn revert_commit_message(commit_id: &str) -> String {
+    format!("[Automated] Revert to revision {commit_id}")
+}
+
+/// Commit ids are hex digests; anything else must not reach a file path.
+fn is_valid_commit_id(commit_id: &str) -> bool {
+    !commit_id.is_empty() && commit_id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Mark every file below `path` read-only, so a revision checkout opened in
+/// Compass cannot be edited by accident.
+fn set_tree_read_only(path: &Path) -> std::io::Result<()> {
+    for entry in read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            set_tree_read_only(&entry.path())?;
+        } else {
+            let mut permissions = entry.metadata()?.permissions();
+            permissions.set_readonly(true);
+            std::fs::set_permissions(entry.path(), permissions)?;
+        }
+    }
     Ok(())
 }
 
@@ -357,6 +656,26 @@ fn cleanup_temp_zip(zip_path: &Path) {
     }
 }
 
+/// Move `current` aside to `aside` and `replacement` into its place. If the
+/// second move fails, `current` is moved back, so it is never left missing.
+fn swap_dir_into_place(current: &Path, replacement: &Path, aside: &Path) -> std::io::Result<()> {
+    if aside.exists() {
+        std::fs::remove_dir_all(aside)?;
+    }
+    std::fs::rename(current, aside)?;
+    if let Err(e) = std::fs::rename(replacement, current) {
+        if let Err(restore_err) = std::fs::rename(aside, current) {
+            error!(
+                "Failed to move {} back into place: {}",
+                aside.display(),
+                restore_err
+            );
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
 fn reset_dir(path: &Path) -> std::io::Result<()> {
     if path.exists() {
         std::fs::remove_dir_all(path)?;
@@ -386,47 +705,15 @@ fn copy_dir_all<A: AsRef<Path>>(src: impl AsRef<Path>, dst: A) -> std::io::Resul
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::paths::{compass_project_index_path, compass_project_path};
-    use common::api_types::{CommitInfo, CommitTreeEntry, ProjectInfo, ProjectType};
+    use crate::{
+        paths::{compass_project_index_path, compass_project_path, compass_project_revision_path},
+        test_support::{
+            FakeSpeleoDb, TEST_ZIP_SHA256, cleanup_project_dir, install_up_to_date_copy,
+            locked_by_test_user, test_api_info, test_commit, test_project_info, test_project_zip,
+        },
+    };
     use std::io::Write;
 
-    fn cleanup_project_dir(id: Uuid) {
-        let _ = std::fs::remove_dir_all(compass_project_path(id));
-    }
-
-    fn test_project_info(id: Uuid, latest_commit: Option<CommitInfo>) -> ProjectInfo {
-        ProjectInfo {
-            id,
-            name: "Test Project".to_string(),
-            description: "Test Description".to_string(),
-            is_active: true,
-            permission: "ADMIN".to_string(),
-            active_mutex: None,
-            country: "US".to_string(),
-            created_by: "tester@example.com".to_string(),
-            creation_date: "2026-01-01T00:00:00Z".to_string(),
-            modified_date: "2026-01-01T00:00:00Z".to_string(),
-            latitude: None,
-            longitude: None,
-            fork_from: None,
-            visibility: "PRIVATE".to_string(),
-            exclude_geojson: false,
-            latest_commit,
-            project_type: ProjectType::Compass,
-        }
-    }
-
-    fn test_commit(message: &str, tree_entries: usize) -> CommitInfo {
-        CommitInfo {
-            id: "abc123".to_string(),
-            message: message.to_string(),
-            author_name: "SpeleoDB".to_string(),
-            commit_date: None,
-            dt_since: "now".to_string(),
-            tree: vec![CommitTreeEntry {}; tree_entries],
-        }
-    }
-
     #[test]
     fn test_local_project_status_empty_for_automated_project_creation_with_empty_tree() {
         let project_id = Uuid::new_v4();
@@ -609,9 +896,11 @@ mod tests {
                 .expect("zip write should succeed");
             zip_writer.finish().expect("zip finish should succeed");
         }
-        let zip_bytes = bytes::Bytes::from(cursor.into_inner());
+        let zip_path = std::env::temp_dir().join(format!("project_{project_id}_unpack_test.zip"));
+        std::fs::write(&zip_path, cursor.into_inner()).expect("zip should be written");
 
-        unpack_project_zip(project_id, zip_bytes).expect("unpack should succeed");
+        unpack_project_zip(project_id, &zip_path).expect("unpack should succeed");
+        cleanup_temp_zip(&zip_path);
 
         assert!(
             !index_path.join("stale.dat").exists(),
@@ -625,6 +914,300 @@ mod tests {
         cleanup_project_dir(project_id);
     }
 
+    #[test]
+    fn test_commit_id_validation_rejects_path_components() {
+        assert!(is_valid_commit_id("0123456789abcdefABCDEF"));
+        assert!(!is_valid_commit_id(""));
+        assert!(!is_valid_commit_id("../index"));
+        assert!(!is_valid_commit_id("abc/def"));
+    }
+
+    #[test]
+    fn test_revert_commit_message_references_full_commit_id() {
+        let commit_id = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            revert_commit_message(commit_id),
+            format!("[Automated] Revert to revision {commit_id}")
+        );
+    }
+
+    #[test]
+    fn test_set_tree_read_only_marks_nested_files() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        let checkout_path = compass_project_revision_path(project_id, "abc123");
+        std::fs::create_dir_all(checkout_path.join("nested")).expect("checkout dirs");
+        std::fs::write(checkout_path.join("cave.mak"), "mak").expect("mak");
+        std::fs::write(checkout_path.join("nested").join("SURVEY.DAT"), "dat").expect("dat");
+
+        set_tree_read_only(&checkout_path).expect("read-only marking should succeed");
+
+        for file in [
+            checkout_path.join("cave.mak"),
+            checkout_path.join("nested").join("SURVEY.DAT"),
+        ] {
+            let permissions = std::fs::metadata(&file).expect("metadata").permissions();
+            assert!(
+                permissions.readonly(),
+                "{} should be read-only",
+                file.display()
+            );
+        }
+        assert!(
+            !compass_project_index_path(project_id).exists(),
+            "a revision checkout must not create the index"
+        );
+
+        cleanup_project_dir(project_id);
+    }
+
+    #[tokio::test]
+    async fn test_checkout_revision_leaves_remote_only_project_remote_only() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        let api = FakeSpeleoDb {
+            revision_zip: test_project_zip(project_id, "historic"),
+            ..Default::default()
+        };
+        let manager = ProjectManager::initialize_from_info(test_project_info(
+            project_id,
+            Some(test_commit("Latest", 1)),
+        ));
+
+        let checkout_path = manager
+            .checkout_revision(&api, &test_api_info(), "def456", |_| {})
+            .await
+            .expect("checkout should succeed");
+
+        assert_eq!(
+            std::fs::read_to_string(checkout_path.join("cave.mak")).unwrap(),
+            "historic"
+        );
+        assert!(!compass_project_path(project_id).exists());
+        assert_eq!(
+            manager.local_project_status(),
+            LocalProjectStatus::RemoteOnly
+        );
+        cleanup_project_dir(project_id);
+    }
+
+    #[tokio::test]
+    async fn test_update_project_downloads_out_of_date_project() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        std::fs::create_dir_all(compass_project_path(project_id)).expect("project dir");
+        let api = FakeSpeleoDb {
+            project_zip: test_project_zip(project_id, "latest"),
+            ..Default::default()
+        };
+        let mut manager = ProjectManager::initialize_from_info(test_project_info(
+            project_id,
+            Some(test_commit("Latest", 1)),
+        ));
+        assert_eq!(
+            manager.local_project_status(),
+            LocalProjectStatus::OutOfDate
+        );
+
+        let status = manager
+            .update_project(&api, &test_api_info())
+            .await
+            .expect("update should succeed");
+
+        assert_eq!(status.local_status(), LocalProjectStatus::UpToDate);
+        assert_eq!(
+            std::fs::read_to_string(compass_project_working_path(project_id).join("cave.mak"))
+                .unwrap(),
+            "latest"
+        );
+        assert_eq!(
+            std::fs::read_to_string(
+                compass_project_path(project_id).join(SPELEODB_PROJECT_CHECKSUM_FILE)
+            )
+            .unwrap(),
+            TEST_ZIP_SHA256
+        );
+        cleanup_project_dir(project_id);
+    }
+
+    #[tokio::test]
+    async fn test_update_local_copies_keeps_index_on_checksum_mismatch() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        let api = FakeSpeleoDb {
+            project_zip: test_project_zip(project_id, "latest"),
+            corrupt_downloads: true,
+            ..Default::default()
+        };
+        let manager = ProjectManager::initialize_from_info(test_project_info(
+            project_id,
+            Some(test_commit("Latest", 1)),
+        ));
+        install_up_to_date_copy(&manager, "original");
+
+        let result = manager
+            .update_local_copies(&api, &test_api_info(), |_| {})
+            .await;
+
+        assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+        assert_eq!(
+            std::fs::read_to_string(compass_project_index_path(project_id).join("cave.mak"))
+                .unwrap(),
+            "original"
+        );
+        assert!(
+            !std::env::temp_dir()
+                .join(format!("project_{project_id}_download.zip"))
+                .exists(),
+            "the corrupt download should be removed"
+        );
+        cleanup_project_dir(project_id);
+    }
+
+    #[tokio::test]
+    async fn test_update_project_leaves_dirty_working_copy_alone() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        let api = FakeSpeleoDb {
+            project_zip: test_project_zip(project_id, "latest"),
+            ..Default::default()
+        };
+        let mut manager = ProjectManager::initialize_from_info(test_project_info(
+            project_id,
+            Some(test_commit("Latest", 1)),
+        ));
+        install_up_to_date_copy(&manager, "original");
+        let working_mak = compass_project_working_path(project_id).join("cave.mak");
+        std::fs::write(&working_mak, "edited").expect("edit mak");
+
+        let status = manager
+            .update_project(&api, &test_api_info())
+            .await
+            .expect("update should succeed");
+
+        assert_eq!(status.local_status(), LocalProjectStatus::Dirty);
+        assert_eq!(std::fs::read_to_string(&working_mak).unwrap(), "edited");
+        cleanup_project_dir(project_id);
+    }
+
+    #[tokio::test]
+    async fn test_revert_requires_mutex_and_clean_working_copy() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        let api = FakeSpeleoDb {
+            revision_zip: test_project_zip(project_id, "reverted"),
+            ..Default::default()
+        };
+        let project_info = test_project_info(project_id, Some(test_commit("Latest", 1)));
+
+        let mut unlocked = ProjectManager::initialize_from_info(project_info.clone());
+        install_up_to_date_copy(&unlocked, "original");
+        let result = unlocked
+            .revert_to_revision(
+                &api,
+                &test_api_info(),
+                "def456",
+                |_| {},
+                &CancellationToken::new(),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::ProjectMutexLocked(id)) if id == project_id));
+
+        let mut locked = ProjectManager::initialize_from_info(locked_by_test_user(project_info));
+        let working_mak = compass_project_working_path(project_id).join("cave.mak");
+        std::fs::write(&working_mak, "edited").expect("edit mak");
+        let result = locked
+            .revert_to_revision(
+                &api,
+                &test_api_info(),
+                "def456",
+                |_| {},
+                &CancellationToken::new(),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::UnsavedChanges(id)) if id == project_id));
+
+        assert!(api.uploads.lock().unwrap().is_empty());
+        assert_eq!(std::fs::read_to_string(&working_mak).unwrap(), "edited");
+        cleanup_project_dir(project_id);
+    }
+
+    #[tokio::test]
+    async fn test_revert_restores_working_copy_when_upload_fails() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        let api = FakeSpeleoDb {
+            revision_zip: test_project_zip(project_id, "reverted"),
+            fail_uploads: true,
+            ..Default::default()
+        };
+        let mut manager = ProjectManager::initialize_from_info(locked_by_test_user(
+            test_project_info(project_id, Some(test_commit("Latest", 1))),
+        ));
+        install_up_to_date_copy(&manager, "original");
+
+        let result = manager
+            .revert_to_revision(
+                &api,
+                &test_api_info(),
+                "def456",
+                |_| {},
+                &CancellationToken::new(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(Error::NetworkRequest(_))));
+        assert_eq!(
+            *api.uploads.lock().unwrap(),
+            vec![revert_commit_message("def456")]
+        );
+        assert_eq!(
+            std::fs::read_to_string(compass_project_working_path(project_id).join("cave.mak"))
+                .unwrap(),
+            "original",
+            "working copy should be restored"
+        );
+        assert_eq!(manager.local_project_status(), LocalProjectStatus::UpToDate);
+        let working_path = compass_project_working_path(project_id);
+        assert!(!working_path.with_extension("partial").exists());
+        assert!(!working_path.with_extension("previous").exists());
+        cleanup_project_dir(project_id);
+    }
+
+    #[tokio::test]
+    async fn test_revert_leaves_working_copy_untouched_when_unpacking_fails() {
+        let project_id = Uuid::new_v4();
+        cleanup_project_dir(project_id);
+        let api = FakeSpeleoDb {
+            revision_zip: b"not a zip".to_vec(),
+            ..Default::default()
+        };
+        let mut manager = ProjectManager::initialize_from_info(locked_by_test_user(
+            test_project_info(project_id, Some(test_commit("Latest", 1))),
+        ));
+        install_up_to_date_copy(&manager, "original");
+
+        let result = manager
+            .revert_to_revision(
+                &api,
+                &test_api_info(),
+                "def456",
+                |_| {},
+                &CancellationToken::new(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(Error::ZipFile(_))));
+        assert!(api.uploads.lock().unwrap().is_empty());
+        let working_path = compass_project_working_path(project_id);
+        assert_eq!(
+            std::fs::read_to_string(working_path.join("cave.mak")).unwrap(),
+            "original"
+        );
+        assert!(!working_path.with_extension("partial").exists());
+        cleanup_project_dir(project_id);
+    }
+
     #[test]
     fn test_sync_after_save_copies_working_to_index_and_updates_revision() {
         let project_id = Uuid::new_v4();
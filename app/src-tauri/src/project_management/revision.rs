@@ -5,7 +5,10 @@ use uuid::Uuid;
 
 use common::{Error, api_types::CommitInfo};
 
-use crate::{paths::compass_project_path, project_management::SPELEODB_PROJECT_REVISION_FILE};
+use crate::{
+    paths::compass_project_path,
+    project_management::{SPELEODB_PROJECT_CHECKSUM_FILE, SPELEODB_PROJECT_REVISION_FILE},
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SpeleoDbProjectRevision {
@@ -24,6 +27,25 @@ impl SpeleoDbProjectRevision {
         std::fs::write(&path, &self.revision).map_err(|_| Error::ProjectWrite(path.clone()))
     }
 
+    /// Record the SHA-256 of the archive the index was unpacked from, next
+    /// to the revision. `None` removes a stale checksum when the index was
+    /// rebuilt from the working copy instead of a download.
+    pub fn save_checksum_for_project(id: Uuid, sha256: Option<&str>) -> Result<(), Error> {
+        let mut path = compass_project_path(id);
+        path.push(SPELEODB_PROJECT_CHECKSUM_FILE);
+        match sha256 {
+            Some(sha256) => {
+                std::fs::write(&path, sha256).map_err(|_| Error::ProjectWrite(path.clone()))
+            }
+            None => match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(Error::ProjectWrite(path.clone()))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
     fn path_for_project(id: Uuid) -> PathBuf {
         let mut revision_path = compass_project_path(id);
         revision_path.push(SPELEODB_PROJECT_REVISION_FILE);
@@ -0,0 +1,318 @@
+//! Storage for the OAuth token, kept out of the plaintext user preferences.
+//!
+//! Each profile's token is stored under its own account name. By default it
+//! is sealed with AES-256-GCM into a file next to the preferences. The key
+//! is derived from the machine id and the user's account, so a copy of
+//! `~/.compass` (a backup, a synced folder) is useless on another machine or
+//! account. It is not a defense against code running
+//! as the same user; builds with the `keychain` feature hand the token to the
+//! OS keychain instead.
+
+use common::{Error, OauthToken};
+use log::{info, warn};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    hkdf::{HKDF_SHA256, Salt},
+    rand::{SecureRandom, SystemRandom},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use crate::paths::COMPASS_HOME_DIR;
+
+#[cfg(test)]
+const CREDENTIALS_DIR_NAME: &str = "credentials_test";
+#[cfg(not(test))]
+const CREDENTIALS_DIR_NAME: &str = "credentials";
+
+const SALT_LEN: usize = 32;
+const KEY_INFO: &[u8] = b"speleodb-compass-sidecar credentials v1";
+
+/// Where OAuth tokens are persisted between runs, one per `account`.
+pub trait SecretStore: Send + Sync {
+    /// The stored token, or `None` if nothing has been stored yet.
+    fn load(&self, account: &str) -> Result<Option<OauthToken>, Error>;
+    fn save(&self, account: &str, token: &OauthToken) -> Result<(), Error>;
+    /// Remove the stored token. Succeeds when there is nothing to remove.
+    fn forget(&self, account: &str) -> Result<(), Error>;
+}
+
+/// The store used by `UserPrefs`: the OS keychain in builds with the
+/// `keychain` feature when one is reachable, the encrypted file otherwise.
+pub fn secret_store() -> &'static dyn SecretStore {
+    static STORE: LazyLock<Box<dyn SecretStore>> = LazyLock::new(|| {
+        #[cfg(feature = "keychain")]
+        match KeychainStore.load(KeychainStore::PROBE_ACCOUNT) {
+            Ok(_) => return Box::new(KeychainStore),
+            Err(e) => warn!("{e}; falling back to the encrypted credentials file"),
+        }
+        let mut dir = COMPASS_HOME_DIR.clone();
+        dir.push(CREDENTIALS_DIR_NAME);
+        Box::new(EncryptedFileStore::new(dir, machine_identity()))
+    });
+    STORE.as_ref()
+}
+
+/// Tokens sealed with a key derived from `identity`, one file per account
+/// in `dir`. A file holds a random salt, the nonce and the ciphertext; a
+/// fresh salt and nonce are drawn on every save, and the account name is
+/// authenticated so files can't be swapped between accounts.
+pub struct EncryptedFileStore {
+    dir: PathBuf,
+    identity: Vec<u8>,
+}
+
+impl EncryptedFileStore {
+    pub fn new(dir: PathBuf, identity: Vec<u8>) -> Self {
+        Self { dir, identity }
+    }
+
+    fn path(&self, account: &str) -> PathBuf {
+        self.dir.join(format!("{account}.bin"))
+    }
+
+    fn key(&self, salt: &[u8]) -> LessSafeKey {
+        let prk = Salt::new(HKDF_SHA256, salt).extract(&self.identity);
+        let info = [KEY_INFO];
+        let okm = prk
+            .expand(&info, &AES_256_GCM)
+            .expect("AES-256 key length is a valid HKDF output length");
+        LessSafeKey::new(UnboundKey::from(okm))
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn load(&self, account: &str) -> Result<Option<OauthToken>, Error> {
+        let path = self.path(account);
+        let mut sealed = match std::fs::read(&path) {
+            Ok(sealed) => sealed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(_) => return Err(Error::ApiInfoRead(path)),
+        };
+        if sealed.len() < SALT_LEN + NONCE_LEN {
+            return Err(Error::Deserialization(
+                "Stored credentials are truncated".to_string(),
+            ));
+        }
+        let mut ciphertext = sealed.split_off(SALT_LEN + NONCE_LEN);
+        let (salt, nonce) = sealed.split_at(SALT_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).expect("nonce has NONCE_LEN bytes");
+        let token = self
+            .key(salt)
+            .open_in_place(nonce, Aad::from(account.as_bytes()), &mut ciphertext)
+            .map_err(|_| {
+                Error::Deserialization(
+                    "Stored credentials can't be decrypted on this machine or account".to_string(),
+                )
+            })?;
+        let token =
+            std::str::from_utf8(token).map_err(|e| Error::Deserialization(e.to_string()))?;
+        OauthToken::new(token).map(Some)
+    }
+
+    fn save(&self, account: &str, token: &OauthToken) -> Result<(), Error> {
+        let path = self.path(account);
+        std::fs::create_dir_all(&self.dir).map_err(|_| Error::CreateDirectory(self.dir.clone()))?;
+        let rng = SystemRandom::new();
+        let mut header = [0u8; SALT_LEN + NONCE_LEN];
+        rng.fill(&mut header)
+            .map_err(|_| Error::ApiInfoWrite(path.clone()))?;
+        let (salt, nonce) = header.split_at(SALT_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).expect("nonce has NONCE_LEN bytes");
+        let mut ciphertext = token.as_ref().as_bytes().to_vec();
+        self.key(salt)
+            .seal_in_place_append_tag(nonce, Aad::from(account.as_bytes()), &mut ciphertext)
+            .map_err(|_| Error::ApiInfoWrite(path.clone()))?;
+
+        let mut sealed = header.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        std::fs::write(&path, sealed).map_err(|_| Error::ApiInfoWrite(path.clone()))?;
+        restrict_to_owner(&path)?;
+        info!("Credentials saved in {}", path.display());
+        Ok(())
+    }
+
+    fn forget(&self, account: &str) -> Result<(), Error> {
+        let path = self.path(account);
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::ApiInfoWrite(path)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Token kept in the OS keychain (Keychain on macOS, Credential Manager on
+/// Windows, the Secret Service on Linux).
+#[cfg(feature = "keychain")]
+pub struct KeychainStore;
+
+#[cfg(feature = "keychain")]
+impl KeychainStore {
+    const SERVICE: &str = "org.speleodb.compass-sidecar";
+    /// Looked up once to find out whether the keychain answers at all;
+    /// nothing is ever stored under it, so `NoEntry` means reachable.
+    const PROBE_ACCOUNT: &str = "probe";
+
+    fn entry(account: &str) -> Result<keyring::Entry, Error> {
+        keyring::Entry::new(Self::SERVICE, account)
+            .map_err(|e| Error::OsCommand(format!("Keychain unavailable: {e}")))
+    }
+}
+
+#[cfg(feature = "keychain")]
+impl SecretStore for KeychainStore {
+    fn load(&self, account: &str) -> Result<Option<OauthToken>, Error> {
+        match Self::entry(account)?.get_password() {
+            Ok(token) => OauthToken::new(token).map(Some),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(Error::OsCommand(format!("Keychain read failed: {e}"))),
+        }
+    }
+
+    fn save(&self, account: &str, token: &OauthToken) -> Result<(), Error> {
+        Self::entry(account)?
+            .set_password(token.as_ref())
+            .map_err(|e| Error::OsCommand(format!("Keychain write failed: {e}")))
+    }
+
+    fn forget(&self, account: &str) -> Result<(), Error> {
+        match Self::entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(Error::OsCommand(format!("Keychain delete failed: {e}"))),
+        }
+    }
+}
+
+/// Limit a file holding credentials to its owner (rw-------). Windows
+/// profiles are already private to the user.
+pub fn restrict_to_owner(path: &Path) -> Result<(), Error> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|_| Error::FilePermissionSet)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Key material tying the encrypted store to this machine and account: the
+/// OS machine id when one can be read, the user name and the home folder.
+fn machine_identity() -> Vec<u8> {
+    let mut identity = match machine_id() {
+        Some(id) => id.into_bytes(),
+        None => {
+            warn!("No machine id available; credentials are keyed to the user account only");
+            Vec::new()
+        }
+    };
+    for part in [
+        std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok(),
+        dirs::home_dir().map(|home| home.display().to_string()),
+    ] {
+        identity.push(0);
+        identity.extend(part.unwrap_or_default().into_bytes());
+    }
+    identity
+}
+
+#[cfg(target_os = "linux")]
+fn machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(target_os = "macos")]
+fn machine_id() -> Option<String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))
+        .and_then(|line| line.rsplit('"').nth(1))
+        .map(str::to_string)
+}
+
+#[cfg(target_os = "windows")]
+fn machine_id() -> Option<String> {
+    let output = std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("MachineGuid"))
+        .and_then(|line| line.split_whitespace().last())
+        .map(str::to_string)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn machine_id() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::compass_home;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    fn store_in(name: &str, identity: &[u8]) -> EncryptedFileStore {
+        EncryptedFileStore::new(compass_home().join(name), identity.to_vec())
+    }
+
+    #[test]
+    fn test_encrypted_store_round_trips_without_plaintext() {
+        let store = store_in("credentials_round_trip", b"machine\0user");
+        assert_eq!(store.load("work").unwrap(), None);
+
+        let token = OauthToken::new(TOKEN).unwrap();
+        store.save("work", &token).expect("save should succeed");
+        let sealed = std::fs::read(store.path("work")).unwrap();
+        assert!(
+            !sealed.windows(TOKEN.len()).any(|w| w == TOKEN.as_bytes()),
+            "the token must not be stored in plaintext"
+        );
+        assert_eq!(store.load("work").unwrap(), Some(token));
+        assert_eq!(store.load("home").unwrap(), None);
+
+        store.forget("work").expect("forget should succeed");
+        assert_eq!(store.load("work").unwrap(), None);
+        store.forget("work").expect("forgetting twice is fine");
+    }
+
+    #[test]
+    fn test_encrypted_store_rejects_other_identity_or_account() {
+        let store = store_in("credentials_other_identity", b"machine\0user");
+        store
+            .save("work", &OauthToken::new(TOKEN).unwrap())
+            .unwrap();
+
+        let elsewhere = EncryptedFileStore::new(store.dir.clone(), b"other\0user".to_vec());
+        assert!(matches!(
+            elsewhere.load("work"),
+            Err(Error::Deserialization(_))
+        ));
+
+        std::fs::copy(store.path("work"), store.path("home")).unwrap();
+        assert!(matches!(store.load("home"), Err(Error::Deserialization(_))));
+        store.forget("work").unwrap();
+        store.forget("home").unwrap();
+    }
+}
@@ -367,7 +367,9 @@ impl AppState {
                     );
                     tauri::async_runtime::block_on(async {
                         let app_state = app_handle_for_hook.state::<AppState>();
-                        api::project::release_project_mutex(&app_state.api_info(), project_id)
+                        app_state
+                            .api()
+                            .release_project_mutex(&app_state.api_info(), project_id)
                             .await
                             .ok();
                     });
@@ -1,10 +1,16 @@
-use crate::{paths::compass_dir_path, project_management::ProjectManager, user_prefs::UserPrefs};
+use crate::{
+    paths::{self, compass_dir_path},
+    project_management::ProjectManager,
+    self_update::should_publish_progress,
+    user_prefs::{Profile, UserPrefs},
+};
+use api::{SpeleoDbApi, SpeleoDbClient, project::CancellationToken};
 use chrono::{DateTime, Utc};
 use common::{
     ApiInfo, Error,
-    api_types::ProjectInfo,
+    api_types::{ProjectInfo, ProjectMetadata},
     ui_state::{
-        LoadingState, LocalProjectStatus, ProjectSaveResult, ProjectStatus, UiState,
+        LoadingState, LocalProjectStatus, MutexWarning, ProjectSaveResult, ProjectStatus, UiState,
         UpdateNotification,
     },
 };
@@ -13,7 +19,7 @@ use notify::{RecursiveMode, Watcher};
 use std::{
     collections::{HashMap, HashSet},
     sync::{
-        Mutex,
+        Arc, Mutex,
         atomic::{AtomicBool, AtomicU64, Ordering},
     },
     time::Duration,
@@ -26,6 +32,16 @@ use tauri::{
 use uuid::Uuid;
 
 const PROJECT_INFO_UPDATE_INTERVAL: Duration = Duration::from_secs(120); // update the list of projects status every 2 minutes
+/// Shortest lifetime of an unrenewed project mutex the sidecar plans for.
+/// SpeleoDB doesn't report it (`ActiveMutex` only carries the creation and
+/// last modification dates), so this is an assumption, not the server's
+/// setting; lower it if a deployment expires mutexes sooner.
+const PROJECT_MUTEX_EXPIRY: Duration = Duration::from_secs(15 * 60);
+/// How often the mutex on the active project is checked and renewed: three
+/// renewals per `PROJECT_MUTEX_EXPIRY`, so one failed attempt never lets a
+/// working session lapse.
+const PROJECT_MUTEX_RENEWAL_INTERVAL: Duration =
+    Duration::from_secs(PROJECT_MUTEX_EXPIRY.as_secs() / 3);
 
 /// Event key for UI state notifications
 pub const UI_STATE_EVENT: &str = "ui-state-update";
@@ -111,17 +127,60 @@ where
     MenuBuilder::new(manager).item(&submenu).build()
 }
 
+/// What `AppState::upload_active_project` sends as the new commit.
+#[derive(Clone)]
+enum ProjectUpload {
+    /// The working copy as it is.
+    LocalChanges { commit_message: String },
+    /// A previous revision, fetched from the server.
+    Revert { commit_id: String },
+}
+
+/// Kept while SpeleoDB rejects the token, so that signing in again picks
+/// up where the user was.
+struct ExpiredSession {
+    /// Profile whose token stopped working.
+    profile_id: Option<Uuid>,
+    /// The upload refused with the token, retried after signing in.
+    pending_upload: Option<ProjectUpload>,
+}
+
+/// A project ZIP transfer whose progress is shown in the UI.
+#[derive(Clone, Copy, Debug)]
+enum ProjectTransfer {
+    /// Opening or updating the active project.
+    Download,
+    /// Saving or reverting the active project.
+    Upload,
+}
+
 pub struct AppState {
+    /// The SpeleoDB every network operation goes through.
+    api: Arc<dyn SpeleoDbApi>,
     app_handle: Mutex<Option<AppHandle>>,
     initializing: Mutex<bool>,
     loading_state: Mutex<LoadingState>,
-    api_info: Mutex<ApiInfo>,
+    user_prefs: Mutex<UserPrefs>,
     project_info: Mutex<HashMap<uuid::Uuid, ProjectInfo>>,
     active_project: Mutex<Option<uuid::Uuid>>,
     project_downloading: Mutex<bool>,
+    /// Percentage of the active project's ZIP download, when the server
+    /// reports a content length.
+    project_download_progress: Mutex<Option<u8>>,
+    /// Percentage of the active project's ZIP upload while saving.
+    project_upload_progress: Mutex<Option<u8>>,
+    /// Cancels the in-flight `save_active_project` upload, if any.
+    project_save_cancel: Mutex<Option<CancellationToken>>,
     compass_pid: Mutex<Option<u32>>,
     background_task_handle: Mutex<Option<JoinHandle<()>>>,
     last_project_update: Mutex<DateTime<Utc>>,
+    /// Project whose mutex we acquired when it was opened and keep renewing.
+    held_project_mutex: Mutex<Option<Uuid>>,
+    last_mutex_renewal: Mutex<DateTime<Utc>>,
+    /// Set when the renewal finds the held mutex gone; blocks saving.
+    mutex_warning: Mutex<Option<MutexWarning>>,
+    /// Set when a request was rejected as unauthorized, until signed in again.
+    expired_session: Mutex<Option<ExpiredSession>>,
     last_emitted_ui_state: Mutex<UiState>,
     emit_mutex: tokio::sync::Mutex<()>,
     pub(crate) update_notification: Mutex<Option<UpdateNotification>>,
@@ -142,17 +201,31 @@ pub struct AppState {
 
 impl AppState {
     pub fn new() -> Self {
+        Self::with_api(Arc::new(SpeleoDbClient))
+    }
+
+    /// Build an `AppState` that talks to SpeleoDB through `api`, e.g. a fake
+    /// in tests.
+    pub fn with_api(api: Arc<dyn SpeleoDbApi>) -> Self {
         Self {
+            api,
             app_handle: Mutex::new(None),
             initializing: Mutex::new(false),
             loading_state: Mutex::new(LoadingState::NotStarted),
-            api_info: Mutex::new(ApiInfo::default()),
+            user_prefs: Mutex::new(UserPrefs::default()),
             project_info: Mutex::new(HashMap::new()),
             active_project: Mutex::new(None),
             project_downloading: Mutex::new(false),
+            project_download_progress: Mutex::new(None),
+            project_upload_progress: Mutex::new(None),
+            project_save_cancel: Mutex::new(None),
             compass_pid: Mutex::new(None),
             background_task_handle: Mutex::new(None),
             last_project_update: Mutex::new(chrono::Utc::now()),
+            held_project_mutex: Mutex::new(None),
+            last_mutex_renewal: Mutex::new(chrono::Utc::now()),
+            mutex_warning: Mutex::new(None),
+            expired_session: Mutex::new(None),
             last_emitted_ui_state: Mutex::new(UiState::default()),
             emit_mutex: tokio::sync::Mutex::new(()),
             update_notification: Mutex::new(None),
@@ -165,6 +238,10 @@ impl AppState {
         }
     }
 
+    pub fn api(&self) -> &dyn SpeleoDbApi {
+        self.api.as_ref()
+    }
+
     pub fn reset_ui_state(&self) {
         *self.last_emitted_ui_state.lock().unwrap() = UiState::default();
     }
@@ -231,18 +308,24 @@ impl AppState {
             .ok_or(Error::NoAppHandle)
     }
 
+    /// The signed-in account of the active profile.
     pub fn api_info(&self) -> ApiInfo {
-        self.api_info.lock().unwrap().clone()
+        self.user_prefs.lock().unwrap().api_info().clone()
     }
 
-    fn set_api_info(&self, api_info: ApiInfo) {
-        *self.api_info.lock().unwrap() = api_info;
+    pub fn user_prefs(&self) -> UserPrefs {
+        self.user_prefs.lock().unwrap().clone()
     }
 
     pub fn update_user_prefs(&self, prefs: UserPrefs) -> Result<(), Error> {
         let _ = self.app_handle()?;
+        // A bad setting must not lock the user out; keep the previous client.
+        if let Err(e) = api::set_client_settings(prefs.client_settings()) {
+            error!("Ignoring invalid HTTP client settings: {}", e);
+        }
         prefs.save()?;
-        self.set_api_info(prefs.api_info().clone());
+        paths::set_active_profile(prefs.active_profile().map(Profile::id));
+        *self.user_prefs.lock().unwrap() = prefs;
 
         // Menu update is deferred to `apply_menu_for_auth_state()`.
         // Do NOT spawn set_menu or emit_app_state_change here.
@@ -281,19 +364,187 @@ impl AppState {
         if let Ok(app_handle) = self.app_handle() {
             self.set_loading_state(LoadingState::LoadingProjects).await;
             self.init_app_state(&app_handle).await;
+            self.resume_expired_session().await;
+        }
+    }
+
+    /// Pass a SpeleoDB result through, noticing an `Unauthorized` error: the
+    /// token was revoked or expired. The UI moves to the sign-in screen but
+    /// the active project, its working copy and its mutex are kept.
+    pub async fn check_session<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(Error::Unauthorized(_)) = &result {
+            self.expire_session(None).await;
+        }
+        result
+    }
+
+    async fn expire_session(&self, pending_upload: Option<ProjectUpload>) {
+        let profile_id = self.user_prefs().active_profile().map(Profile::id);
+        {
+            let mut expired = self.expired_session.lock().unwrap();
+            match expired.as_mut() {
+                Some(session) => {
+                    if pending_upload.is_some() {
+                        session.pending_upload = pending_upload;
+                    }
+                }
+                None => {
+                    warn!("SpeleoDB rejected the OAuth token, asking to sign in again");
+                    *expired = Some(ExpiredSession {
+                        profile_id,
+                        pending_upload,
+                    });
+                }
+            }
+        }
+        self.set_loading_state(LoadingState::Unauthenticated).await;
+    }
+
+    /// After signing in again, retry the upload the expired token refused.
+    /// When another profile was signed in instead, close the old project.
+    /// Until signing in has reached `Ready`, the session is kept for later.
+    async fn resume_expired_session(&self) {
+        if self.loading_state() != LoadingState::Ready {
+            return;
+        }
+        let Some(session) = self.expired_session.lock().unwrap().take() else {
+            return;
+        };
+        let profile_id = self.user_prefs().active_profile().map(Profile::id);
+        if profile_id != session.profile_id {
+            info!("Signed in to another profile, closing the project of the expired one");
+            *self.active_project.lock().unwrap() = None;
+            self.stop_mutex_renewal();
+            self.emit_app_state_change().await;
+            return;
+        }
+        if let Some(upload) = session.pending_upload {
+            info!("Resuming the upload interrupted by the expired token");
+            if let Err(e) = self.upload_active_project(upload).await {
+                error!("Resumed upload failed: {}", e);
+            }
         }
     }
 
-    pub fn sign_out(&self, app_handle: &AppHandle) -> Result<(), Error> {
-        UserPrefs::forget()?;
+    /// Sign out of the active profile only; the other profiles stay signed
+    /// in and can still be switched to. Its token is revoked on the server
+    /// in the background.
+    pub async fn sign_out(&self, app_handle: &AppHandle) -> Result<(), Error> {
+        let prefs = self.user_prefs().without_active_profile();
+        self.close_project_for_profile_change().await?;
+        self.restart_with_prefs(app_handle, prefs)?;
+        tauri::async_runtime::spawn({
+            let app_handle = app_handle.clone();
+            async move {
+                let app_state = app_handle.state::<AppState>();
+                app_state.revoke_signed_out_tokens().await;
+            }
+        });
+        Ok(())
+    }
+
+    /// Revoke the tokens of signed-out profiles on their server. A token
+    /// that can't be revoked now, e.g. while offline or while the server
+    /// fails, stays queued in the preferences and is retried at the next
+    /// launch. One the server refuses to revoke (a 4xx answer) is dropped,
+    /// since asking again can't help.
+    async fn revoke_signed_out_tokens(&self) {
+        for profile in self.user_prefs().pending_revocations().to_vec() {
+            let api_info = profile.api_info();
+            let revoked = match api_info.oauth_token() {
+                Some(token) => {
+                    self.api()
+                        .revoke_token(api_info.instance().clone(), token)
+                        .await
+                }
+                None => Ok(()),
+            };
+            match revoked {
+                Ok(()) => info!("Revoked the token of signed-out profile {}", profile.id()),
+                // E.g. the token was already rejected, or the server can't
+                // revoke tokens.
+                Err(e) if e.is_client_error() => warn!(
+                    "SpeleoDB refused to revoke the token of signed-out profile {}, dropping it: {}",
+                    profile.id(),
+                    e
+                ),
+                Err(e) => {
+                    warn!(
+                        "Could not revoke the token of signed-out profile {}, retrying at next launch: {}",
+                        profile.id(),
+                        e
+                    );
+                    continue;
+                }
+            }
+            let prefs = self.user_prefs().without_pending_revocation(profile.id());
+            if let Err(e) = self.update_user_prefs(prefs) {
+                warn!("Failed to save preferences after revoking a token: {}", e);
+            }
+        }
+    }
+
+    /// Make `profile_id` the active profile, or with `None` show the sign-in
+    /// screen to add another account. The open project, if any, is closed
+    /// and the project list reloaded for the new profile.
+    pub async fn switch_profile(
+        &self,
+        app_handle: &AppHandle,
+        profile_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        let prefs = self.user_prefs().with_active_profile(profile_id)?;
+        self.close_project_for_profile_change().await?;
+        self.restart_with_prefs(app_handle, prefs)
+    }
+
+    /// Close the active project before leaving its profile, whose folder
+    /// the project paths stop pointing to. Refused while the project is
+    /// downloading or saving, or while its working copy has unsaved changes.
+    /// Its mutex is released, so collaborators needn't wait for it to
+    /// expire.
+    async fn close_project_for_profile_change(&self) -> Result<(), Error> {
+        let Some(project_id) = self.get_active_project_id() else {
+            return Ok(());
+        };
+        if *self.project_downloading.lock().unwrap()
+            || self.project_save_cancel.lock().unwrap().is_some()
         {
-            let mut project_lock = self.project_info.lock().unwrap();
-            project_lock.clear();
+            warn!("Refusing to leave the profile while project {project_id} is transferring");
+            return Err(Error::ProjectTransferInProgress(project_id));
         }
+        if let Some(status) = self.get_active_project_status()
+            && matches!(
+                status.local_status(),
+                LocalProjectStatus::Dirty | LocalProjectStatus::DirtyAndOutOfDate
+            )
         {
-            let user_prefs = ApiInfo::default();
-            *self.api_info.lock().unwrap() = user_prefs;
+            warn!("Refusing to leave the profile with unsaved changes in project {project_id}");
+            return Err(Error::UnsavedChanges(project_id));
         }
+        let held = *self.held_project_mutex.lock().unwrap() == Some(project_id);
+        self.stop_mutex_renewal();
+        *self.active_project.lock().unwrap() = None;
+        if held {
+            let released = self
+                .api()
+                .release_project_mutex(&self.api_info(), project_id)
+                .await;
+            match released {
+                Ok(_) => info!("Released mutex for project {project_id}"),
+                Err(e) => warn!("Failed to release mutex for project {project_id}: {e}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Save `prefs` and run initialization again from the start, as after a
+    /// fresh launch.
+    fn restart_with_prefs(&self, app_handle: &AppHandle, prefs: UserPrefs) -> Result<(), Error> {
+        self.update_user_prefs(prefs)?;
+        *self.expired_session.lock().unwrap() = None;
+        self.clear_local_projects();
+        self.stop_mutex_renewal();
+        *self.active_project.lock().unwrap() = None;
         self.set_loading_state_sync(LoadingState::NotStarted);
         tauri::async_runtime::spawn({
             let app_handle = app_handle.clone();
@@ -312,10 +563,64 @@ impl AppState {
     ) -> Result<ProjectStatus, Error> {
         self.set_project_info(project_info.clone());
         let mut project = ProjectManager::initialize_from_info(project_info);
-        let project_status = project.update_project(&self.api_info()).await?;
+        let project_status = project.update_project(self.api(), &self.api_info()).await?;
         Ok(project_status)
     }
 
+    fn start_mutex_renewal(&self, project_id: Uuid) {
+        *self.held_project_mutex.lock().unwrap() = Some(project_id);
+        *self.last_mutex_renewal.lock().unwrap() = chrono::Utc::now();
+        *self.mutex_warning.lock().unwrap() = None;
+    }
+
+    fn stop_mutex_renewal(&self) {
+        *self.held_project_mutex.lock().unwrap() = None;
+        *self.mutex_warning.lock().unwrap() = None;
+    }
+
+    /// Check that the mutex taken in `set_active_project` is still ours and,
+    /// if so, re-acquire it to push its expiry back. When it is gone, stop
+    /// renewing and raise a `MutexWarning` instead of silently taking it
+    /// again: someone may have uploaded in the meantime. Returns whether the
+    /// UI has something new to show, i.e. whether a warning was raised.
+    async fn renew_project_mutex(&self) -> Result<bool, Error> {
+        let Some(project_id) = *self.held_project_mutex.lock().unwrap() else {
+            return Ok(false);
+        };
+        let api_info = self.api_info();
+        let info = self.api().fetch_project_info(&api_info, project_id).await?;
+        let warning = match lost_mutex_warning(&info, api_info.email()) {
+            None => match self
+                .api()
+                .acquire_project_mutex(&api_info, project_id)
+                .await
+            {
+                Ok(info) => {
+                    debug!("Renewed mutex for project: {project_id}");
+                    // Only the mutex's dates moved; they go out with the
+                    // next change.
+                    self.set_project_info(info);
+                    return Ok(false);
+                }
+                // Taken between the two requests.
+                Err(Error::ProjectMutexLocked(_)) => MutexWarning {
+                    project_id,
+                    taken_by: None,
+                },
+                Err(e) => return Err(e),
+            },
+            Some(warning) => warning,
+        };
+        warn!(
+            "Mutex on project {project_id} is no longer held (now held by: {:?})",
+            warning.taken_by
+        );
+        self.set_project_info(info);
+        *self.held_project_mutex.lock().unwrap() = None;
+        *self.mutex_warning.lock().unwrap() = Some(warning);
+        Ok(true)
+    }
+
     pub async fn set_active_project(&self, project_id: Option<Uuid>) -> Result<(), Error> {
         if let Some(project_id) = project_id {
             info!("Selecting: {project_id} as active project");
@@ -323,21 +628,41 @@ impl AppState {
             // Immediately switch to the project detail view and show downloading spinner
             *self.active_project.lock().unwrap() = Some(project_id);
             *self.project_downloading.lock().unwrap() = true;
+            *self.project_download_progress.lock().unwrap() = None;
             self.emit_app_state_change().await;
 
-            // Now do the heavy work (mutex acquisition + download)
+            // Now do the heavy work (mutex acquisition + download). Users who
+            // can't edit the project open it read-only without asking for
+            // the mutex the server would refuse them.
             let result = async {
-                match api::project::acquire_project_mutex(&self.api_info(), project_id).await {
+                let read_only_info = self
+                    .get_project_info(project_id)
+                    .filter(|info| !info.permission.allows_editing());
+                if let Some(info) = read_only_info {
+                    info!(
+                        "Opening project {project_id} read-only ({})",
+                        info.permission
+                    );
+                    self.stop_mutex_renewal();
+                    self.make_local_and_update(info).await?;
+                    return Ok(());
+                }
+                match self
+                    .api()
+                    .acquire_project_mutex(&self.api_info(), project_id)
+                    .await
+                {
                     Ok(info) => {
                         info!("Project lock grabbed successfully");
-                        let project = ProjectManager::initialize_from_info(info.clone());
-                        project.make_local(&self.api_info()).await?;
-                        self.update_local_project(info).await?;
+                        self.start_mutex_renewal(project_id);
+                        self.make_local_and_update(info).await?;
                     }
+                    Err(e @ Error::Unauthorized(_)) => return Err(e),
                     Err(_e) => {
                         warn!(
                             "Failed to grab lock for project: {project_id}, opening as read-only"
                         );
+                        self.stop_mutex_renewal();
                     }
                 };
                 Ok::<(), Error>(())
@@ -346,12 +671,14 @@ impl AppState {
 
             // Clear downloading state regardless of success/failure
             *self.project_downloading.lock().unwrap() = false;
+            *self.project_download_progress.lock().unwrap() = None;
             self.emit_app_state_change().await;
 
             // Propagate any error from the download
-            result?;
+            self.check_session(result).await?;
         } else if let Some(active_project) = self.get_active_project_status() {
             *self.active_project.lock().unwrap() = None;
+            self.stop_mutex_renewal();
             self.set_loading_state_sync(LoadingState::LoadingProjects);
             self.emit_app_state_change().await;
             if let LocalProjectStatus::Dirty = active_project.local_status() {
@@ -363,9 +690,11 @@ impl AppState {
                     && active_mutex.user == email
                 {
                     info!("Active mutex owned by current user, releasing");
-                    let project_info =
-                        api::project::release_project_mutex(&self.api_info(), active_project.id())
-                            .await?;
+                    let released = self
+                        .api()
+                        .release_project_mutex(&self.api_info(), active_project.id())
+                        .await;
+                    let project_info = self.check_session(released).await?;
                     self.update_local_project(project_info).await?;
                 } else {
                     warn!("Active mutex not owned by current user, skipping release");
@@ -376,6 +705,37 @@ impl AppState {
         Ok(())
     }
 
+    /// Download `info`'s project if it only exists remotely, then refresh
+    /// its local status.
+    async fn make_local_and_update(&self, info: ProjectInfo) -> Result<(), Error> {
+        let project = ProjectManager::initialize_from_info(info.clone());
+        project
+            .make_local(
+                self.api(),
+                &self.api_info(),
+                self.project_transfer_progress_reporter(ProjectTransfer::Download),
+            )
+            .await?;
+        self.update_local_project(info).await?;
+        Ok(())
+    }
+
+    /// Refuse changes to `project_id` that the user's permission level
+    /// doesn't allow, rather than letting the server answer 403.
+    pub fn require_edit_access(&self, project_id: Uuid) -> Result<(), Error> {
+        let project_info = self
+            .get_project_info(project_id)
+            .ok_or(Error::NoProjectSelected)?;
+        if !project_info.permission.allows_editing() {
+            warn!(
+                "Refusing to change project {project_id} with permission {}",
+                project_info.permission
+            );
+            return Err(Error::InsufficientPermission(project_id));
+        }
+        Ok(())
+    }
+
     pub fn get_active_project_id(&self) -> Option<uuid::Uuid> {
         *self.active_project.lock().unwrap()
     }
@@ -391,28 +751,86 @@ impl AppState {
     pub async fn save_active_project(
         &self,
         commit_message: String,
+    ) -> Result<ProjectSaveResult, Error> {
+        self.upload_active_project(ProjectUpload::LocalChanges { commit_message })
+            .await
+    }
+
+    /// Upload the revision recorded by `commit_id` as a new commit of the
+    /// active project. See `ProjectManager::revert_to_revision`.
+    pub async fn revert_active_project(
+        &self,
+        commit_id: String,
+    ) -> Result<ProjectSaveResult, Error> {
+        self.upload_active_project(ProjectUpload::Revert { commit_id })
+            .await
+    }
+
+    async fn upload_active_project(
+        &self,
+        upload: ProjectUpload,
     ) -> Result<ProjectSaveResult, Error> {
         let Some(project_id) = self.get_active_project_id() else {
             error!("No active project to save");
             return Err(Error::NoProjectSelected);
         };
+        self.require_edit_access(project_id)?;
+        if let Some(warning) = self.mutex_warning.lock().unwrap().clone()
+            && warning.project_id == project_id
+        {
+            warn!("Refusing to upload project {project_id} without its mutex");
+            return Err(match warning.taken_by {
+                Some(_) => Error::ProjectMutexLocked(project_id),
+                None => Error::ProjectMutexLost(project_id),
+            });
+        }
+        // A revert replaces the working copy, which Compass may hold locked.
+        if matches!(upload, ProjectUpload::Revert { .. }) && self.compass_is_open() {
+            warn!("Refusing to revert project {project_id} while Compass is open");
+            return Err(Error::CompassIsOpen(project_id));
+        }
         let project_info = self
             .get_project_info(project_id)
             .ok_or(Error::NoProjectSelected)?;
         let mut project_manager = ProjectManager::initialize_from_info(project_info);
         let api_info = self.api_info();
-        let result = project_manager
-            .save_local_changes(&api_info, commit_message)
-            .await?;
+        let cancel = CancellationToken::new();
+        *self.project_save_cancel.lock().unwrap() = Some(cancel.clone());
+        *self.project_upload_progress.lock().unwrap() = None;
+        let on_progress = self.project_transfer_progress_reporter(ProjectTransfer::Upload);
+        let result = match upload.clone() {
+            ProjectUpload::LocalChanges { commit_message } => {
+                project_manager
+                    .save_local_changes(self.api(), &api_info, commit_message, on_progress, &cancel)
+                    .await
+            }
+            ProjectUpload::Revert { commit_id } => {
+                project_manager
+                    .revert_to_revision(self.api(), &api_info, &commit_id, on_progress, &cancel)
+                    .await
+            }
+        };
+        *self.project_save_cancel.lock().unwrap() = None;
+        *self.project_upload_progress.lock().unwrap() = None;
+        self.emit_app_state_change().await;
+        if let Err(Error::Unauthorized(_)) = &result {
+            self.expire_session(Some(upload)).await;
+        }
+        let result = result?;
 
         // After a successful upload, sync local state: copy working_copy -> index
         // and update .revision.txt. We must NOT call update_local_copies here because
         // it overwrites the working copy, which fails on Windows when Compass holds
         // file locks on the project files.
         let old_commit_id = project_manager.latest_remote_commit().map(|c| c.id.clone());
-        let updated_project_info =
-            Self::fetch_project_info_after_save(&api_info, project_id, old_commit_id.as_deref())
-                .await?;
+        let updated_project_info = Self::fetch_project_info_after_save(
+            self.api(),
+            &api_info,
+            project_id,
+            old_commit_id.as_deref(),
+        )
+        .await;
+        let updated_project_info = self.check_session(updated_project_info).await?;
         let project_manager = ProjectManager::initialize_from_info(updated_project_info.clone());
         project_manager.sync_after_save()?;
         self.set_project_info(updated_project_info);
@@ -423,6 +841,7 @@ impl AppState {
     /// Fetch project info after a save, retrying briefly if the server
     /// hasn't yet reflected the new commit (eventual consistency).
     async fn fetch_project_info_after_save(
+        api: &dyn SpeleoDbApi,
         api_info: &ApiInfo,
         project_id: uuid::Uuid,
         old_commit_id: Option<&str>,
@@ -431,7 +850,7 @@ impl AppState {
         const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
 
         for attempt in 0..=MAX_RETRIES {
-            let info = api::project::fetch_project_info(api_info, project_id).await?;
+            let info = api.fetch_project_info(api_info, project_id).await?;
             let new_commit_id = info.latest_commit.as_ref().map(|c| c.id.as_str());
             if new_commit_id != old_commit_id {
                 return Ok(info);
@@ -451,7 +870,66 @@ impl AppState {
              proceeding with available data",
             project_id, MAX_RETRIES
         );
-        api::project::fetch_project_info(api_info, project_id).await
+        api.fetch_project_info(api_info, project_id).await
+    }
+
+    /// Change the name, description and location of a project. Refused
+    /// locally for users the server would reject anyway.
+    pub async fn update_project_metadata(
+        &self,
+        project_id: Uuid,
+        metadata: ProjectMetadata,
+    ) -> Result<(), Error> {
+        let project_info = self
+            .get_project_info(project_id)
+            .ok_or(Error::NoProjectSelected)?;
+        if !project_info.can_edit_metadata() {
+            warn!(
+                "Refusing to edit project {project_id} with permission {}",
+                project_info.permission
+            );
+            return Err(Error::InsufficientPermission(project_id));
+        }
+        let updated = self
+            .api()
+            .update_project(&self.api_info(), project_id, &metadata)
+            .await;
+        let updated = self.check_session(updated).await?;
+        self.set_project_info(updated);
+        self.emit_app_state_change().await;
+        Ok(())
+    }
+
+    /// Unpack the revision recorded by `commit_id` into its read-only
+    /// checkout folder, downloading it first if needed, and return the folder.
+    pub async fn checkout_project_revision(
+        &self,
+        project_id: Uuid,
+        commit_id: &str,
+    ) -> Result<std::path::PathBuf, Error> {
+        let project_info = self
+            .get_project_info(project_id)
+            .ok_or(Error::NoProjectSelected)?;
+        let checkout = ProjectManager::initialize_from_info(project_info)
+            .checkout_revision(self.api(), &self.api_info(), commit_id, |_| {})
+            .await;
+        self.check_session(checkout).await
+    }
+
+    /// Abort the upload started by `save_active_project`. The pending save
+    /// then fails with `Error::Cancelled`. While the ZIP is still being sent
+    /// the project is left unchanged on the server; once it has been sent in
+    /// full, the save only stops waiting for the server, which may still
+    /// create the commit. Returns `false` when no save is in progress.
+    pub fn cancel_active_project_save(&self) -> bool {
+        match self.project_save_cancel.lock().unwrap().as_ref() {
+            Some(cancel) => {
+                info!("Cancelling project upload");
+                cancel.cancel();
+                true
+            }
+            None => false,
+        }
     }
 
     pub async fn discard_active_project_changes(&self) -> Result<(), Error> {
@@ -464,11 +942,62 @@ impl AppState {
             .ok_or(Error::NoProjectSelected)?;
         let project_manager = ProjectManager::initialize_from_info(project_info);
         let api_info = self.api_info();
-        project_manager.update_local_copies(&api_info).await?;
+        let updated = project_manager
+            .update_local_copies(self.api(), &api_info, |_| {})
+            .await;
+        self.check_session(updated).await?;
         self.emit_app_state_change().await;
         Ok(())
     }
 
+    /// Build a callback that publishes the progress of a project ZIP
+    /// `transfer` to the UI. Each update is emitted from its own task, so the
+    /// transfer loop never waits on the WebView; `should_publish_progress`
+    /// drops updates that arrive out of order.
+    fn project_transfer_progress_reporter(
+        &self,
+        transfer: ProjectTransfer,
+    ) -> impl FnMut(u8) + Send + 'static {
+        let app_handle = self.app_handle().ok();
+        move |percent| {
+            let Some(app_handle) = app_handle.clone() else {
+                return;
+            };
+            tauri::async_runtime::spawn(async move {
+                let app_state = app_handle.state::<AppState>();
+                app_state
+                    .publish_project_transfer_progress(transfer, percent)
+                    .await;
+            });
+        }
+    }
+
+    /// Record `percent` for `transfer` and emit it, unless the transfer has
+    /// already finished or the update is stale.
+    async fn publish_project_transfer_progress(&self, transfer: ProjectTransfer, percent: u8) {
+        let (in_progress, slot) = match transfer {
+            ProjectTransfer::Download => (
+                *self.project_downloading.lock().unwrap(),
+                &self.project_download_progress,
+            ),
+            ProjectTransfer::Upload => (
+                self.project_save_cancel.lock().unwrap().is_some(),
+                &self.project_upload_progress,
+            ),
+        };
+        let did_publish = in_progress && {
+            let mut progress = slot.lock().unwrap();
+            let should_publish = should_publish_progress(*progress, percent);
+            if should_publish {
+                *progress = Some(percent);
+            }
+            should_publish
+        };
+        if did_publish {
+            self.emit_app_state_change().await;
+        }
+    }
+
     pub fn compass_is_open(&self) -> bool {
         self.compass_pid.lock().unwrap().is_some()
     }
@@ -527,16 +1056,28 @@ impl AppState {
         let active_project_id = self.get_active_project_id();
         let compass_is_open = self.compass_is_open();
         let project_downloading = *self.project_downloading.lock().unwrap();
+        let project_download_progress = *self.project_download_progress.lock().unwrap();
+        let project_upload_progress = *self.project_upload_progress.lock().unwrap();
         let update_notification = self.update_notification.lock().unwrap().clone();
-        let ui_state = UiState::new(
-            loading_state.clone(),
-            user_email,
-            project_statuses,
-            active_project_id,
-            compass_is_open,
-            project_downloading,
-            update_notification,
-        );
+        let mutex_warning = self.mutex_warning.lock().unwrap().clone();
+        let user_prefs = self.user_prefs();
+        let ui_state = UiState {
+            project_download_progress,
+            project_upload_progress,
+            mutex_warning,
+            profiles: user_prefs.profiles().iter().map(Profile::summary).collect(),
+            active_profile: user_prefs.active_profile().map(Profile::id),
+            session_expired: self.expired_session.lock().unwrap().is_some(),
+            ..UiState::new(
+                loading_state.clone(),
+                user_email,
+                project_statuses,
+                active_project_id,
+                compass_is_open,
+                project_downloading,
+                update_notification,
+            )
+        };
         // Only send if the state has actually changed
         {
             let mut last_state = match self.last_emitted_ui_state.lock() {
@@ -626,10 +1167,17 @@ impl AppState {
             log::warn!("No OAuth token found in user preferences");
             return Err("No OAuth token found".to_string());
         };
-        match api::auth::authorize_with_token(api_info.instance().clone(), token).await {
+        match self
+            .api()
+            .authorize_with_token(api_info.instance().clone(), token)
+            .await
+        {
             Ok(api_info) => {
                 log::info!("User authenticated successfully");
-                let prefs = UserPrefs::new(api_info);
+                let prefs = self
+                    .user_prefs()
+                    .with_signed_in(api_info)
+                    .with_client_settings(api::client_settings());
                 if self.update_user_prefs(prefs).is_err() {
                     log::warn!("Failed to save user preferences after authentication");
                 }
@@ -648,13 +1196,41 @@ impl AppState {
         // Errors are propagated; both callers (init and the background task)
         // log the failure themselves, so we don't log it again here to avoid
         // duplicate Sentry breadcrumbs/events for a single failure.
-        let projects = api::project::fetch_projects(&api_info).await?;
+        let projects = self.api().fetch_projects(&api_info).await?;
+        self.replace_user_projects(projects.clone()).await?;
+        Ok(projects)
+    }
+
+    /// Poll the project list for the background task. Returns whether it
+    /// changed; an unchanged list (HTTP 304) leaves the cached project info
+    /// and local statuses as they are.
+    async fn refresh_user_projects(&self) -> Result<bool, Error> {
+        let api_info = self.api_info();
+        let changed = match self.api().fetch_projects_if_modified(&api_info).await? {
+            Some(projects) => {
+                if let Err(e) = self.replace_user_projects(projects).await {
+                    // Otherwise the next poll answers 304 and the list is
+                    // never applied.
+                    self.api().forget_projects_listing(&api_info);
+                    return Err(e);
+                }
+                true
+            }
+            None => {
+                *self.last_project_update.lock().unwrap() = chrono::Utc::now();
+                false
+            }
+        };
+        Ok(changed)
+    }
+
+    async fn replace_user_projects(&self, projects: Vec<ProjectInfo>) -> Result<(), Error> {
         self.clear_local_projects();
-        for project in projects.clone() {
+        for project in projects {
             self.update_local_project(project).await?;
         }
         *self.last_project_update.lock().unwrap() = chrono::Utc::now();
-        Ok(projects)
+        Ok(())
     }
 
     /// Advance the loading state machine by one step.
@@ -676,6 +1252,10 @@ impl AppState {
             },
             LoadingState::LoadingProjects => match self.load_user_projects().await {
                 Ok(_) => self.set_loading_state(LoadingState::Ready).await,
+                Err(Error::Unauthorized(_)) => {
+                    self.expire_session(None).await;
+                    self.loading_state()
+                }
                 Err(e) => {
                     // error! (not warn!) so the SentryLogger forwards this hard
                     // initialization failure as an event. This is the failure
@@ -705,6 +1285,7 @@ impl AppState {
 
     async fn background_update_task(app_handle: &AppHandle) {
         let app_state = app_handle.state::<AppState>();
+        app_state.revoke_signed_out_tokens().await;
 
         // Set up filesystem watcher on the projects directory so we only
         // re-check local project statuses when something actually changes
@@ -733,25 +1314,54 @@ impl AppState {
         }
 
         loop {
+            // Nothing to ask SpeleoDB while signed out or signing in again.
+            let signed_in = app_state.loading_state() == LoadingState::Ready;
+
             // Remote API update on a timer
             let last_project_update = *app_state.last_project_update.lock().unwrap();
-            if chrono::Utc::now()
-                .signed_duration_since(last_project_update)
-                .to_std()
-                .unwrap()
-                >= PROJECT_INFO_UPDATE_INTERVAL
+            if signed_in
+                && chrono::Utc::now()
+                    .signed_duration_since(last_project_update)
+                    .to_std()
+                    .unwrap()
+                    >= PROJECT_INFO_UPDATE_INTERVAL
             {
                 trace!("Background task: updating project info from API");
-                match app_state.load_user_projects().await {
-                    Ok(_) => {
+                let refreshed = app_state.refresh_user_projects().await;
+                match app_state.check_session(refreshed).await {
+                    Ok(true) => {
                         app_state.emit_app_state_change().await;
                     }
+                    Ok(false) => {
+                        trace!("Background task: project list not modified");
+                    }
                     Err(e) => {
                         error!("Background task: failed to update project info: {}", e);
                     }
                 }
             }
 
+            let last_mutex_renewal = *app_state.last_mutex_renewal.lock().unwrap();
+            if signed_in
+                && chrono::Utc::now()
+                    .signed_duration_since(last_mutex_renewal)
+                    .to_std()
+                    .unwrap_or_default()
+                    >= PROJECT_MUTEX_RENEWAL_INTERVAL
+            {
+                *app_state.last_mutex_renewal.lock().unwrap() = chrono::Utc::now();
+                trace!("Background task: renewing active project mutex");
+                let renewed = app_state.renew_project_mutex().await;
+                match app_state.check_session(renewed).await {
+                    Ok(true) => app_state.emit_app_state_change().await,
+                    Ok(false) => trace!("Background task: project mutex unchanged"),
+                    Err(e) => {
+                        // Transient failures are retried at the next interval.
+                        error!("Background task: failed to renew project mutex: {}", e);
+                    }
+                }
+            }
+
             // Drain filesystem events — only recheck local status when files changed
             let mut fs_changed = false;
             while fs_rx.try_recv().is_ok() {
@@ -789,9 +1399,37 @@ impl AppState {
     }
 }
 
+/// `Some` when `info` shows that `email` no longer holds the project mutex.
+fn lost_mutex_warning(info: &ProjectInfo, email: Option<&str>) -> Option<MutexWarning> {
+    match &info.active_mutex {
+        Some(mutex) if Some(mutex.user.as_str()) == email => None,
+        other => Some(MutexWarning {
+            project_id: info.id,
+            taken_by: other.as_ref().map(|mutex| mutex.user.clone()),
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{APP_MENU_TITLE, AppMenuItem, AppMenuLayout, app_menu_layout};
+    use super::{
+        APP_MENU_TITLE, AppMenuItem, AppMenuLayout, AppState, ExpiredSession, MutexWarning,
+        ProjectUpload, app_menu_layout, lost_mutex_warning,
+    };
+    use crate::{
+        project_management::ProjectManager,
+        test_support::{
+            FakeSpeleoDb, cleanup_project_dir, install_up_to_date_copy, locked_by_test_user,
+            test_api_info, test_commit, test_project_info,
+        },
+        user_prefs::UserPrefs,
+    };
+    use common::{
+        Error,
+        api_types::{CommitInfo, Permission, ProjectInfo},
+        ui_state::{LoadingState, LocalProjectStatus, ProjectSaveResult},
+    };
+    use std::sync::{Arc, atomic::Ordering};
 
     #[test]
     fn application_menu_title_is_space_separated_title_case() {
@@ -857,4 +1495,405 @@ mod tests {
             );
         }
     }
+
+    fn project_locked_by(user: Option<&str>) -> common::api_types::ProjectInfo {
+        use common::api_types::{ActiveMutex, Permission, ProjectInfo, ProjectType, Visibility};
+        ProjectInfo {
+            id: uuid::Uuid::nil(),
+            name: "Test Project".to_string(),
+            description: String::new(),
+            is_active: true,
+            permission: Permission::ReadAndWrite,
+            active_mutex: user.map(|user| ActiveMutex {
+                user: user.to_string(),
+                creation_date: chrono::DateTime::UNIX_EPOCH,
+                modified_date: chrono::DateTime::UNIX_EPOCH,
+            }),
+            country: "US".to_string(),
+            created_by: "owner@example.com".to_string(),
+            creation_date: chrono::DateTime::UNIX_EPOCH,
+            modified_date: chrono::DateTime::UNIX_EPOCH,
+            latitude: None,
+            longitude: None,
+            fork_from: None,
+            visibility: Visibility::Private,
+            exclude_geojson: false,
+            latest_commit: None,
+            project_type: ProjectType::Compass,
+        }
+    }
+
+    #[test]
+    fn mutex_still_held_raises_no_warning() {
+        let info = project_locked_by(Some("me@example.com"));
+        assert_eq!(lost_mutex_warning(&info, Some("me@example.com")), None);
+    }
+
+    #[test]
+    fn mutex_taken_or_expired_raises_warning() {
+        let taken = project_locked_by(Some("other@example.com"));
+        assert_eq!(
+            lost_mutex_warning(&taken, Some("me@example.com")),
+            Some(MutexWarning {
+                project_id: uuid::Uuid::nil(),
+                taken_by: Some("other@example.com".to_string()),
+            })
+        );
+
+        let expired = project_locked_by(None);
+        assert_eq!(
+            lost_mutex_warning(&expired, Some("me@example.com")),
+            Some(MutexWarning {
+                project_id: uuid::Uuid::nil(),
+                taken_by: None,
+            })
+        );
+    }
+
+    /// A signed-in `AppState` whose SpeleoDB lists `projects`.
+    fn signed_in_state(
+        api: FakeSpeleoDb,
+        projects: Vec<ProjectInfo>,
+    ) -> (AppState, Arc<FakeSpeleoDb>) {
+        *api.projects.lock().unwrap() = projects;
+        let api = Arc::new(api);
+        let state = AppState::with_api(api.clone());
+        *state.user_prefs.lock().unwrap() = UserPrefs::default().with_signed_in(test_api_info());
+        (state, api)
+    }
+
+    /// Open `project_info` as the active project with an up-to-date copy on
+    /// disk, as `set_active_project` leaves it.
+    fn open_project(state: &AppState, project_info: &ProjectInfo) {
+        cleanup_project_dir(project_info.id);
+        install_up_to_date_copy(
+            &ProjectManager::initialize_from_info(project_info.clone()),
+            "original",
+        );
+        state.set_project_info(project_info.clone());
+        *state.active_project.lock().unwrap() = Some(project_info.id);
+        state.start_mutex_renewal(project_info.id);
+    }
+
+    #[tokio::test]
+    async fn refresh_replaces_a_changed_project_list() {
+        let stale = test_project_info(uuid::Uuid::new_v4(), None);
+        let listed = test_project_info(uuid::Uuid::new_v4(), None);
+        let (state, _api) = signed_in_state(FakeSpeleoDb::default(), vec![listed.clone()]);
+        state.set_project_info(stale.clone());
+
+        assert!(state.refresh_user_projects().await.expect("refresh"));
+
+        assert_eq!(state.get_project_info(stale.id), None);
+        assert_eq!(state.get_project_info(listed.id), Some(listed));
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_projects_when_the_list_is_unchanged() {
+        let known = test_project_info(uuid::Uuid::new_v4(), None);
+        let api = FakeSpeleoDb {
+            listing_unchanged: true,
+            ..Default::default()
+        };
+        let (state, _api) = signed_in_state(api, Vec::new());
+        state.set_project_info(known.clone());
+
+        assert!(!state.refresh_user_projects().await.expect("refresh"));
+
+        assert_eq!(state.get_project_info(known.id), Some(known));
+    }
+
+    #[tokio::test]
+    async fn failed_refresh_polls_the_full_list_again() {
+        let project = test_project_info(uuid::Uuid::new_v4(), Some(test_commit("Latest", 1)));
+        install_up_to_date_copy(
+            &ProjectManager::initialize_from_info(project.clone()),
+            "original",
+        );
+        let newer = ProjectInfo {
+            latest_commit: Some(CommitInfo {
+                id: "def456".to_string(),
+                ..test_commit("Newer", 1)
+            }),
+            ..project.clone()
+        };
+        // The out-of-date copy fails to download.
+        let api = FakeSpeleoDb {
+            corrupt_downloads: true,
+            ..Default::default()
+        };
+        let (state, api) = signed_in_state(api, vec![newer]);
+
+        assert!(matches!(
+            state.refresh_user_projects().await,
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        assert!(api.listing_forgotten.load(Ordering::SeqCst));
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn rejected_token_asks_to_sign_in_and_keeps_the_active_project() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let api = FakeSpeleoDb {
+            unauthorized: true,
+            ..Default::default()
+        };
+        let (state, _api) = signed_in_state(api, vec![project.clone()]);
+        open_project(&state, &project);
+
+        let result = state.refresh_user_projects().await;
+        let result = state.check_session(result).await;
+
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+        assert_eq!(state.loading_state(), LoadingState::Unauthenticated);
+        assert!(state.expired_session.lock().unwrap().is_some());
+        assert_eq!(state.get_active_project_id(), Some(project.id));
+        assert_eq!(state.get_project_info(project.id), Some(project.clone()));
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn renewing_a_held_mutex_has_nothing_to_show() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let (state, _api) = signed_in_state(FakeSpeleoDb::default(), vec![project.clone()]);
+        open_project(&state, &project);
+
+        assert!(!state.renew_project_mutex().await.expect("renewal"));
+
+        assert_eq!(*state.held_project_mutex.lock().unwrap(), Some(project.id));
+        assert_eq!(*state.mutex_warning.lock().unwrap(), None);
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn renewal_warns_once_the_mutex_is_taken() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let mut taken = project.clone();
+        taken.active_mutex.as_mut().unwrap().user = "other@example.com".to_string();
+        let (state, _api) = signed_in_state(FakeSpeleoDb::default(), vec![taken]);
+        open_project(&state, &project);
+
+        assert!(state.renew_project_mutex().await.expect("renewal"));
+
+        assert_eq!(*state.held_project_mutex.lock().unwrap(), None);
+        assert_eq!(
+            *state.mutex_warning.lock().unwrap(),
+            Some(MutexWarning {
+                project_id: project.id,
+                taken_by: Some("other@example.com".to_string()),
+            })
+        );
+        // Renewal stops, so a later tick has nothing more to report.
+        assert!(!state.renew_project_mutex().await.expect("renewal"));
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn leaving_a_profile_releases_the_mutex_of_a_clean_project() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let (state, api) = signed_in_state(FakeSpeleoDb::default(), vec![project.clone()]);
+        open_project(&state, &project);
+
+        state
+            .close_project_for_profile_change()
+            .await
+            .expect("a clean project closes");
+
+        assert_eq!(state.get_active_project_id(), None);
+        assert_eq!(*state.held_project_mutex.lock().unwrap(), None);
+        assert_eq!(api.projects.lock().unwrap()[0].active_mutex, None);
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn leaving_a_profile_is_refused_while_the_project_is_busy_or_dirty() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let (state, api) = signed_in_state(FakeSpeleoDb::default(), vec![project.clone()]);
+        open_project(&state, &project);
+
+        *state.project_downloading.lock().unwrap() = true;
+        assert!(matches!(
+            state.close_project_for_profile_change().await,
+            Err(Error::ProjectTransferInProgress(id)) if id == project.id
+        ));
+        *state.project_downloading.lock().unwrap() = false;
+
+        let working_mak = crate::paths::compass_project_working_path(project.id).join("cave.mak");
+        std::fs::write(&working_mak, "edited").expect("edit mak");
+        assert!(matches!(
+            state.close_project_for_profile_change().await,
+            Err(Error::UnsavedChanges(id)) if id == project.id
+        ));
+
+        assert_eq!(state.get_active_project_id(), Some(project.id));
+        assert_eq!(*state.held_project_mutex.lock().unwrap(), Some(project.id));
+        assert!(api.projects.lock().unwrap()[0].active_mutex.is_some());
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn save_uploads_and_leaves_the_project_up_to_date() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let (state, api) = signed_in_state(FakeSpeleoDb::default(), vec![project.clone()]);
+        open_project(&state, &project);
+        let working_mak = crate::paths::compass_project_working_path(project.id).join("cave.mak");
+        std::fs::write(&working_mak, "edited").expect("edit mak");
+        assert_eq!(
+            state.get_active_project_status().unwrap().local_status(),
+            LocalProjectStatus::Dirty
+        );
+
+        let result = state.save_active_project("Edited".to_string()).await;
+
+        assert!(matches!(result, Ok(ProjectSaveResult::Saved)));
+        assert_eq!(*api.uploads.lock().unwrap(), vec!["Edited".to_string()]);
+        let saved = state.get_project_info(project.id).expect("project info");
+        assert_ne!(saved.latest_commit, project.latest_commit);
+        assert_eq!(
+            state.get_active_project_status().unwrap().local_status(),
+            LocalProjectStatus::UpToDate
+        );
+        assert_eq!(
+            std::fs::read_to_string(
+                crate::paths::compass_project_index_path(project.id).join("cave.mak")
+            )
+            .unwrap(),
+            "edited"
+        );
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn save_refused_by_an_expired_token_is_kept_for_after_sign_in() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let api = FakeSpeleoDb {
+            unauthorized: true,
+            ..Default::default()
+        };
+        let (state, _api) = signed_in_state(api, vec![project.clone()]);
+        open_project(&state, &project);
+
+        let result = state.save_active_project("Edited".to_string()).await;
+
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+        assert_eq!(state.loading_state(), LoadingState::Unauthenticated);
+        let expired = state.expired_session.lock().unwrap();
+        assert!(matches!(
+            expired.as_ref().and_then(|session| session.pending_upload.as_ref()),
+            Some(ProjectUpload::LocalChanges { commit_message }) if commit_message == "Edited"
+        ));
+        drop(expired);
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn expired_session_is_resumed_only_once_signed_in() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let (state, api) = signed_in_state(FakeSpeleoDb::default(), vec![project.clone()]);
+        open_project(&state, &project);
+        let profile_id = state
+            .user_prefs()
+            .active_profile()
+            .map(|profile| profile.id());
+        *state.expired_session.lock().unwrap() = Some(ExpiredSession {
+            profile_id,
+            pending_upload: Some(ProjectUpload::LocalChanges {
+                commit_message: "Edited".to_string(),
+            }),
+        });
+
+        state.set_loading_state_sync(LoadingState::LoadingProjects);
+        state.resume_expired_session().await;
+
+        assert!(api.uploads.lock().unwrap().is_empty());
+        assert!(matches!(
+            state.expired_session.lock().unwrap().as_ref()
+                .and_then(|session| session.pending_upload.as_ref()),
+            Some(ProjectUpload::LocalChanges { commit_message }) if commit_message == "Edited"
+        ));
+
+        state.set_loading_state_sync(LoadingState::Ready);
+        state.resume_expired_session().await;
+
+        assert_eq!(*api.uploads.lock().unwrap(), vec!["Edited".to_string()]);
+        assert!(state.expired_session.lock().unwrap().is_none());
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn revert_is_refused_while_compass_is_open() {
+        let project = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let (state, api) = signed_in_state(FakeSpeleoDb::default(), vec![project.clone()]);
+        open_project(&state, &project);
+        *state.compass_pid.lock().unwrap() = Some(4242);
+
+        let result = state
+            .upload_active_project(ProjectUpload::Revert {
+                commit_id: "def456".to_string(),
+            })
+            .await;
+
+        assert!(matches!(result, Err(Error::CompassIsOpen(id)) if id == project.id));
+        assert!(api.uploads.lock().unwrap().is_empty());
+        cleanup_project_dir(project.id);
+    }
+
+    #[tokio::test]
+    async fn save_is_refused_without_edit_permission_or_mutex() {
+        let mut read_only = test_project_info(uuid::Uuid::new_v4(), Some(test_commit("Latest", 1)));
+        read_only.permission = Permission::ReadOnly;
+        let lost = locked_by_test_user(test_project_info(
+            uuid::Uuid::new_v4(),
+            Some(test_commit("Latest", 1)),
+        ));
+        let (state, api) = signed_in_state(
+            FakeSpeleoDb::default(),
+            vec![read_only.clone(), lost.clone()],
+        );
+
+        open_project(&state, &read_only);
+        let result = state.save_active_project("Edited".to_string()).await;
+        assert!(matches!(result, Err(Error::InsufficientPermission(id)) if id == read_only.id));
+
+        open_project(&state, &lost);
+        *state.mutex_warning.lock().unwrap() = Some(MutexWarning {
+            project_id: lost.id,
+            taken_by: None,
+        });
+        let result = state.save_active_project("Edited".to_string()).await;
+        assert!(matches!(result, Err(Error::ProjectMutexLost(id)) if id == lost.id));
+
+        assert!(api.uploads.lock().unwrap().is_empty());
+        cleanup_project_dir(read_only.id);
+        cleanup_project_dir(lost.id);
+    }
 }
@@ -0,0 +1,407 @@
+//! Test infrastructure for the Tauri side.
+//!
+//! Compiled only with `#[cfg(test)]`. Provides `FakeSpeleoDb`, an in-memory
+//! `SpeleoDbApi` for `ProjectManager` and `AppState` tests, and builders for
+//! the project info, commits and on-disk copies they work on.
+
+use crate::{
+    paths::{
+        compass_project_index_path, compass_project_path, compass_project_revisions_path,
+        compass_project_working_path,
+    },
+    project_management::{ProjectManager, SPELEODB_COMPASS_PROJECT_FILE, SpeleoDbProjectRevision},
+};
+use api::{
+    ApiFuture, SpeleoDbApi, TransferProgress, project::CancellationToken, project::DownloadedZip,
+};
+use common::{
+    ApiInfo, Error, OauthToken,
+    api_types::{
+        ActiveMutex, CommitInfo, CommitTreeEntry, InstanceProbe, Page, Permission, ProjectInfo,
+        ProjectMetadata, ProjectPermissions, ProjectType, Visibility,
+    },
+    ui_state::ProjectSaveResult,
+};
+use std::{
+    future::Future,
+    io::Write,
+    path::Path,
+    pin::Pin,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use url::Url;
+use uuid::Uuid;
+
+pub(crate) const TEST_USER: &str = "tester@example.com";
+/// Checksum the fake reports for every download; hashing is covered by the api crate.
+pub(crate) const TEST_ZIP_SHA256: &str =
+    "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+/// Serves fixed ZIPs and a project list, and records uploads instead of
+/// talking to SpeleoDB.
+#[derive(Default)]
+pub(crate) struct FakeSpeleoDb {
+    pub(crate) project_zip: Vec<u8>,
+    pub(crate) revision_zip: Vec<u8>,
+    pub(crate) corrupt_downloads: bool,
+    pub(crate) fail_uploads: bool,
+    /// Answer the project list as unchanged (HTTP 304).
+    pub(crate) listing_unchanged: bool,
+    /// Reject the project list and uploads as if the token had expired.
+    pub(crate) unauthorized: bool,
+    /// Set by `forget_projects_listing`.
+    pub(crate) listing_forgotten: AtomicBool,
+    /// The projects listed and looked up; a successful upload adds a commit.
+    pub(crate) projects: Mutex<Vec<ProjectInfo>>,
+    pub(crate) uploads: Mutex<Vec<String>>,
+}
+
+impl FakeSpeleoDb {
+    fn check_token(&self) -> Result<(), Error> {
+        if self.unauthorized {
+            return Err(Error::Unauthorized("token expired".to_string()));
+        }
+        Ok(())
+    }
+
+    fn serve<'a>(
+        &'a self,
+        zip: &'a [u8],
+        dest: &'a mut (dyn AsyncWrite + Send + Unpin),
+        on_progress: &'a mut TransferProgress<'_>,
+    ) -> ApiFuture<'a, DownloadedZip> {
+        Box::pin(async move {
+            dest.write_all(zip)
+                .await
+                .map_err(|e| Error::FileWrite(e.to_string()))?;
+            dest.flush()
+                .await
+                .map_err(|e| Error::FileWrite(e.to_string()))?;
+            on_progress(zip.len(), Some(zip.len() as u64));
+            if self.corrupt_downloads {
+                return Err(Error::ChecksumMismatch {
+                    expected: "0".repeat(64),
+                    actual: TEST_ZIP_SHA256.to_string(),
+                });
+            }
+            Ok(DownloadedZip {
+                size: zip.len() as u64,
+                sha256: TEST_ZIP_SHA256.to_string(),
+            })
+        })
+    }
+}
+
+fn not_faked<'a, T: Send + 'a>(operation: &str) -> ApiFuture<'a, T> {
+    let message = format!("{operation} is not faked");
+    Box::pin(async move { Err(Error::NotFound(message)) })
+}
+
+impl SpeleoDbApi for FakeSpeleoDb {
+    fn probe_instance(
+        &self,
+        _instance: Url,
+    ) -> Pin<Box<dyn Future<Output = InstanceProbe> + Send + '_>> {
+        Box::pin(async { InstanceProbe::Unreachable("probe_instance is not faked".to_string()) })
+    }
+
+    fn authorize_with_token<'a>(
+        &'a self,
+        _instance: Url,
+        _oauth: &'a OauthToken,
+    ) -> ApiFuture<'a, ApiInfo, String> {
+        Box::pin(async { Err("authorize_with_token is not faked".to_string()) })
+    }
+
+    fn authorize_with_email<'a>(
+        &'a self,
+        _instance: Url,
+        _email: &'a str,
+        _password: &'a str,
+    ) -> ApiFuture<'a, ApiInfo, String> {
+        Box::pin(async { Err("authorize_with_email is not faked".to_string()) })
+    }
+
+    fn revoke_token<'a>(&'a self, _instance: Url, _oauth: &'a OauthToken) -> ApiFuture<'a, ()> {
+        not_faked("revoke_token")
+    }
+
+    fn create_project<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        _name: String,
+        _description: String,
+        _country: String,
+        _latitude: Option<String>,
+        _longitude: Option<String>,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        not_faked("create_project")
+    }
+
+    fn update_project<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        _project_id: Uuid,
+        _metadata: &'a ProjectMetadata,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        not_faked("update_project")
+    }
+
+    fn acquire_project_mutex<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(async move {
+            let mut projects = self.projects.lock().unwrap();
+            let project = projects
+                .iter_mut()
+                .find(|project| project.id == project_id)
+                .ok_or_else(|| Error::NotFound(format!("project {project_id}")))?;
+            match &project.active_mutex {
+                Some(mutex) if mutex.user != TEST_USER => {
+                    Err(Error::ProjectMutexLocked(project_id))
+                }
+                _ => {
+                    *project = locked_by_test_user(project.clone());
+                    Ok(project.clone())
+                }
+            }
+        })
+    }
+
+    fn release_project_mutex<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(async move {
+            let mut projects = self.projects.lock().unwrap();
+            let project = projects
+                .iter_mut()
+                .find(|project| project.id == project_id)
+                .ok_or_else(|| Error::NotFound(format!("project {project_id}")))?;
+            project.active_mutex = None;
+            Ok(project.clone())
+        })
+    }
+
+    fn fetch_projects<'a>(&'a self, _api_info: &'a ApiInfo) -> ApiFuture<'a, Vec<ProjectInfo>> {
+        Box::pin(async move {
+            self.check_token()?;
+            Ok(self.projects.lock().unwrap().clone())
+        })
+    }
+
+    fn fetch_projects_if_modified<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+    ) -> ApiFuture<'a, Option<Vec<ProjectInfo>>> {
+        Box::pin(async move {
+            self.check_token()?;
+            if self.listing_unchanged {
+                return Ok(None);
+            }
+            Ok(Some(self.projects.lock().unwrap().clone()))
+        })
+    }
+
+    fn forget_projects_listing(&self, _api_info: &ApiInfo) {
+        self.listing_forgotten.store(true, Ordering::SeqCst);
+    }
+
+    fn fetch_project_info<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectInfo> {
+        Box::pin(async move {
+            self.projects
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|project| project.id == project_id)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(format!("project {project_id}")))
+        })
+    }
+
+    fn fetch_project_commits<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        _project_id: Uuid,
+        _page: u32,
+    ) -> ApiFuture<'a, Page<CommitInfo>> {
+        not_faked("fetch_project_commits")
+    }
+
+    fn fetch_project_permissions<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        _project_id: Uuid,
+    ) -> ApiFuture<'a, ProjectPermissions> {
+        not_faked("fetch_project_permissions")
+    }
+
+    fn download_project_zip<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        _project_id: Uuid,
+        dest: &'a mut (dyn AsyncWrite + Send + Unpin),
+        on_progress: &'a mut TransferProgress<'_>,
+    ) -> ApiFuture<'a, DownloadedZip> {
+        self.serve(&self.project_zip, dest, on_progress)
+    }
+
+    fn download_project_revision_zip<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        _project_id: Uuid,
+        _commit_id: &'a str,
+        dest: &'a mut (dyn AsyncWrite + Send + Unpin),
+        on_progress: &'a mut TransferProgress<'_>,
+    ) -> ApiFuture<'a, DownloadedZip> {
+        self.serve(&self.revision_zip, dest, on_progress)
+    }
+
+    fn upload_project_zip<'a>(
+        &'a self,
+        _api_info: &'a ApiInfo,
+        project_id: Uuid,
+        commit_message: String,
+        _zip_path: &'a Path,
+        _on_progress: Box<TransferProgress<'static>>,
+        _cancel: &'a CancellationToken,
+    ) -> ApiFuture<'a, ProjectSaveResult> {
+        self.uploads.lock().unwrap().push(commit_message.clone());
+        Box::pin(async move {
+            self.check_token()?;
+            if self.fail_uploads {
+                return Err(Error::NetworkRequest("upload failed".to_string()));
+            }
+            let mut projects = self.projects.lock().unwrap();
+            if let Some(project) = projects.iter_mut().find(|project| project.id == project_id) {
+                let commit_count = self.uploads.lock().unwrap().len();
+                project.latest_commit = Some(CommitInfo {
+                    id: format!("{commit_count:040x}"),
+                    ..test_commit(&commit_message, 1)
+                });
+            }
+            Ok(ProjectSaveResult::Saved)
+        })
+    }
+}
+
+pub(crate) fn test_api_info() -> ApiInfo {
+    ApiInfo::new(
+        ApiInfo::default().instance().clone(),
+        Some(TEST_USER.to_string()),
+        Some(OauthToken::new("0".repeat(40)).unwrap()),
+    )
+}
+
+pub(crate) fn test_compass_toml(project_id: Uuid) -> String {
+    format!(
+        "[speleodb]\nid = \"{project_id}\"\nversion = \"1.0.0\"\n\n\
+         [project]\nmak_file = \"cave.mak\"\ndat_files = []\nplt_files = []\n"
+    )
+}
+
+pub(crate) fn test_project_zip(project_id: Uuid, mak_contents: &str) -> Vec<u8> {
+    let mut cursor = std::io::Cursor::new(Vec::<u8>::new());
+    {
+        let mut zip_writer = zip::ZipWriter::new(&mut cursor);
+        let options = zip::write::SimpleFileOptions::default();
+        zip_writer
+            .start_file(SPELEODB_COMPASS_PROJECT_FILE, options)
+            .expect("zip file should start");
+        zip_writer
+            .write_all(test_compass_toml(project_id).as_bytes())
+            .expect("zip write should succeed");
+        zip_writer
+            .start_file("cave.mak", options)
+            .expect("zip file should start");
+        zip_writer
+            .write_all(mak_contents.as_bytes())
+            .expect("zip write should succeed");
+        zip_writer.finish().expect("zip finish should succeed");
+    }
+    cursor.into_inner()
+}
+
+/// Lay out an up-to-date local copy of `manager`'s latest commit.
+pub(crate) fn install_up_to_date_copy(manager: &ProjectManager, mak_contents: &str) {
+    let project_id = manager.id();
+    for dir in [
+        compass_project_index_path(project_id),
+        compass_project_working_path(project_id),
+    ] {
+        std::fs::create_dir_all(&dir).expect("project dir");
+        std::fs::write(
+            dir.join(SPELEODB_COMPASS_PROJECT_FILE),
+            test_compass_toml(project_id),
+        )
+        .expect("compass.toml");
+        std::fs::write(dir.join("cave.mak"), mak_contents).expect("mak");
+    }
+    SpeleoDbProjectRevision::from(manager.latest_remote_commit().expect("latest commit"))
+        .save_revision_for_project(project_id)
+        .expect("revision file");
+}
+
+pub(crate) fn locked_by_test_user(mut project_info: ProjectInfo) -> ProjectInfo {
+    project_info.active_mutex = Some(ActiveMutex {
+        user: TEST_USER.to_string(),
+        creation_date: chrono::DateTime::UNIX_EPOCH,
+        modified_date: chrono::DateTime::UNIX_EPOCH,
+    });
+    project_info
+}
+
+pub(crate) fn cleanup_project_dir(id: Uuid) {
+    let _ = std::fs::remove_dir_all(compass_project_path(id));
+    let _ = std::fs::remove_dir_all(compass_project_revisions_path(id));
+}
+
+pub(crate) fn test_project_info(id: Uuid, latest_commit: Option<CommitInfo>) -> ProjectInfo {
+    ProjectInfo {
+        id,
+        name: "Test Project".to_string(),
+        description: "Test Description".to_string(),
+        is_active: true,
+        permission: Permission::Admin,
+        active_mutex: None,
+        country: "US".to_string(),
+        created_by: "tester@example.com".to_string(),
+        creation_date: chrono::DateTime::UNIX_EPOCH,
+        modified_date: chrono::DateTime::UNIX_EPOCH,
+        latitude: None,
+        longitude: None,
+        fork_from: None,
+        visibility: Visibility::Private,
+        exclude_geojson: false,
+        latest_commit,
+        project_type: ProjectType::Compass,
+    }
+}
+
+pub(crate) fn test_commit(message: &str, tree_entries: usize) -> CommitInfo {
+    CommitInfo {
+        id: "abc123".to_string(),
+        message: message.to_string(),
+        author_name: "SpeleoDB".to_string(),
+        commit_date: None,
+        dt_since: "now".to_string(),
+        tree: (0..tree_entries)
+            .map(|i| CommitTreeEntry {
+                path: format!("survey{i}.dat"),
+                size: 1024,
+                hash: format!("{i:040x}"),
+            })
+            .collect(),
+    }
+}
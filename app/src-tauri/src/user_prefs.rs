@@ -1,4 +1,4 @@
-use common::{ApiInfo, Error};
+use common::{ApiInfo, ClientSettings, Error, OauthToken, ui_state::ProfileSummary};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -6,8 +6,12 @@ use std::{
     sync::LazyLock,
 };
 use url::Url;
+use uuid::Uuid;
 
-use crate::paths::COMPASS_HOME_DIR;
+use crate::{
+    paths::COMPASS_HOME_DIR,
+    secret_store::{SecretStore, restrict_to_owner, secret_store},
+};
 #[cfg(test)]
 const USER_PREFS_FILE_NAME: &str = "user_prefs_test.json";
 #[cfg(not(test))]
@@ -26,22 +30,147 @@ pub fn user_prefs_file_path() -> &'static Path {
     &USER_PREFS_FILE_PATH_BUFFER
 }
 
+/// A saved account: an instance and the credentials used on it. Each
+/// profile keeps its projects in its own folder (see
+/// `paths::compass_profile_path`) and its token in the secret store.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Profile {
+    id: Uuid,
+    name: String,
+    api_info: ApiInfo,
+}
+
+impl Profile {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn api_info(&self) -> &ApiInfo {
+        &self.api_info
+    }
+
+    pub fn summary(&self) -> ProfileSummary {
+        ProfileSummary {
+            id: self.id,
+            name: self.name.clone(),
+            instance: self.api_info.instance().clone(),
+            email: self.api_info.email().map(str::to_string),
+        }
+    }
+}
+
+/// "email @ host", or just the host when the email is unknown.
+fn default_profile_name(api_info: &ApiInfo) -> String {
+    let host = api_info.instance().host_str().unwrap_or("SpeleoDB");
+    match api_info.email() {
+        Some(email) => format!("{email} @ {host}"),
+        None => host.to_string(),
+    }
+}
+
 /// User preferences structure
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct UserPrefs {
-    api_info: ApiInfo,
+    /// The single account of preferences saved before profiles existed;
+    /// turned into a profile on load and never written back.
+    #[serde(default, rename = "api_info", skip_serializing)]
+    legacy_api_info: Option<ApiInfo>,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    /// Profile signed in to, `None` while signed out.
+    #[serde(default)]
+    active_profile: Option<Uuid>,
+    /// Profiles signed out whose token is still valid on the server, kept
+    /// with their token until it is revoked.
+    #[serde(default)]
+    pending_revocations: Vec<Profile>,
+    /// Proxy, extra root certificates and timeouts of the HTTP client.
+    #[serde(default)]
+    client_settings: ClientSettings,
 }
 
 impl UserPrefs {
+    /// Preferences with one profile for `api_info`, signed in.
     pub fn new(api_info: ApiInfo) -> Self {
-        Self { api_info }
+        Self::default().with_signed_in(api_info)
+    }
+
+    pub fn with_client_settings(mut self, client_settings: ClientSettings) -> Self {
+        self.client_settings = client_settings;
+        self
+    }
+
+    /// Make the profile for `api_info` (same instance and email) active,
+    /// updating its token, or add a new profile if there is none yet.
+    pub fn with_signed_in(mut self, api_info: ApiInfo) -> Self {
+        let existing = self.profiles.iter_mut().find(|profile| {
+            profile.api_info.instance() == api_info.instance()
+                && profile.api_info.email() == api_info.email()
+        });
+        let id = match existing {
+            Some(profile) => {
+                profile.api_info = api_info;
+                profile.id
+            }
+            None => {
+                let profile = Profile {
+                    id: Uuid::new_v4(),
+                    name: default_profile_name(&api_info),
+                    api_info,
+                };
+                let id = profile.id;
+                self.profiles.push(profile);
+                id
+            }
+        };
+        self.active_profile = Some(id);
+        self
+    }
+
+    /// Switch to another saved profile, or to none (the sign-in screen)
+    /// while keeping every profile.
+    pub fn with_active_profile(mut self, profile_id: Option<Uuid>) -> Result<Self, Error> {
+        if let Some(profile_id) = profile_id
+            && !self.profiles.iter().any(|profile| profile.id == profile_id)
+        {
+            return Err(Error::NotFound(format!("Profile {profile_id}")));
+        }
+        self.active_profile = profile_id;
+        Ok(self)
+    }
+
+    /// Sign out of the active profile: it is removed and its token queued
+    /// for revocation, the other profiles are kept.
+    pub fn without_active_profile(mut self) -> Self {
+        if let Some(active) = self.active_profile.take() {
+            let (signed_out, kept) = std::mem::take(&mut self.profiles)
+                .into_iter()
+                .partition(|profile| profile.id == active);
+            self.profiles = kept;
+            self.pending_revocations.extend(
+                signed_out
+                    .into_iter()
+                    .filter(|profile: &Profile| profile.api_info.oauth_token().is_some()),
+            );
+        }
+        self
+    }
+
+    /// Drop a signed-out profile once its token was revoked; saving then
+    /// forgets the token.
+    pub fn without_pending_revocation(mut self, profile_id: Uuid) -> Self {
+        self.pending_revocations
+            .retain(|profile| profile.id != profile_id);
+        self
     }
 
     pub fn load() -> Result<Self, Error> {
         // Try to get credentials from environment variables first (for testing)
         let instance = std::env::var("TEST_SPELEODB_INSTANCE").ok();
         let email = std::env::var("TEST_SPELEODB_EMAIL").ok();
-        let oauth = std::env::var("TEST_SPELEODB_OAUTH").ok();
+        let oauth = std::env::var("TEST_SPELEODB_OAUTH")
+            .ok()
+            .and_then(|oauth| OauthToken::new(oauth).ok());
         if let Some(instance) = instance
             && email.is_some()
             && oauth.is_some()
@@ -52,52 +181,140 @@ impl UserPrefs {
             })?;
             return Ok(UserPrefs::new(ApiInfo::new(instance, email, oauth)));
         }
-        if user_prefs_file_path().exists() {
-            let user_preferences_string = std::fs::read_to_string(user_prefs_file_path())
-                .map_err(|_| Error::ApiInfoRead(user_prefs_file_path().to_path_buf()))?;
-            let prefs: UserPrefs = toml::from_str(&user_preferences_string)
-                .map_err(|e| Error::Deserialization(e.to_string()))?;
-            info!("User preferences loaded successfully");
-            Ok(prefs)
-        } else {
+        Self::load_from(user_prefs_file_path(), secret_store())
+    }
+
+    /// Read the preferences at `path` and attach each profile's token kept
+    /// in `secrets`.
+    ///
+    /// Preferences written before profiles existed hold a single account,
+    /// possibly with its token in plaintext. It becomes the active profile,
+    /// its token moves into `secrets` and the file is rewritten.
+    fn load_from(path: &Path, secrets: &dyn SecretStore) -> Result<Self, Error> {
+        let mut prefs = Self::read(path)?;
+        if let Some(legacy) = prefs.legacy_api_info.take() {
+            // Signed-out preferences only kept their client settings.
+            if legacy.oauth_token().is_some() {
+                info!("Moving the single account of the user preferences into a profile");
+                prefs = prefs.with_signed_in(legacy);
+            }
+            prefs.save_to(path, secrets)?;
+            return Ok(prefs);
+        }
+        for profile in prefs
+            .profiles
+            .iter_mut()
+            .chain(&mut prefs.pending_revocations)
+        {
+            let token = secrets
+                .load(&profile.id.to_string())
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "Stored OAuth token of profile {} is unreadable, signing in again is required: {e}",
+                        profile.id
+                    );
+                    None
+                });
+            profile.api_info = profile.api_info.clone().with_oauth_token(token);
+        }
+        info!("User preferences loaded successfully");
+        Ok(prefs)
+    }
+
+    /// Parse the preferences file as saved, without any tokens attached.
+    fn read(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
             warn!("No user preferences found");
-            Err(Error::NoUserPreferences)
+            return Err(Error::NoUserPreferences);
         }
+        let user_preferences_string =
+            std::fs::read_to_string(path).map_err(|_| Error::ApiInfoRead(path.to_path_buf()))?;
+        toml::from_str(&user_preferences_string).map_err(|e| Error::Deserialization(e.to_string()))
     }
 
+    /// The active profile's connection, or the signed-out default.
     pub fn api_info(&self) -> &ApiInfo {
-        &self.api_info
+        static SIGNED_OUT: LazyLock<ApiInfo> = LazyLock::new(ApiInfo::default);
+        self.active_profile()
+            .map_or(&SIGNED_OUT, |profile| &profile.api_info)
+    }
+
+    pub fn active_profile(&self) -> Option<&Profile> {
+        let active = self.active_profile?;
+        self.profiles.iter().find(|profile| profile.id == active)
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn pending_revocations(&self) -> &[Profile] {
+        &self.pending_revocations
+    }
+
+    /// Every profile whose token is kept in the secret store.
+    fn stored_profiles(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.iter().chain(&self.pending_revocations)
     }
 
-    /// Save a user preferences object to disk in TOML format.
+    pub fn client_settings(&self) -> &ClientSettings {
+        &self.client_settings
+    }
+
+    /// Save the preferences to disk in TOML format, and the OAuth tokens to
+    /// the secret store.
     pub fn save(&self) -> Result<(), Error> {
-        let s = toml::to_string_pretty(self).map_err(|e| Error::Serialization(e.to_string()))?;
-        std::fs::write(user_prefs_file_path(), s)
-            .map_err(|_| Error::ApiInfoWrite(user_prefs_file_path().to_path_buf()))?;
+        self.save_to(user_prefs_file_path(), secret_store())
+    }
 
-        // On Unix, tighten permissions so only the owner can read/write the prefs file.
-        #[cfg(unix)]
+    fn save_to(&self, path: &Path, secrets: &dyn SecretStore) -> Result<(), Error> {
+        // Tokens of profiles removed since the last save go with them.
+        if let Ok(saved) = Self::read(path) {
+            for removed in saved
+                .stored_profiles()
+                .filter(|saved| !self.stored_profiles().any(|p| p.id == saved.id))
+            {
+                secrets.forget(&removed.id.to_string())?;
+            }
+        }
+        let mut without_tokens = self.clone();
+        for profile in without_tokens
+            .profiles
+            .iter_mut()
+            .chain(&mut without_tokens.pending_revocations)
         {
-            use std::os::unix::fs::PermissionsExt;
-            if let Ok(meta) = std::fs::metadata(user_prefs_file_path()) {
-                let mut perms = meta.permissions();
-                // rw------- (owner read/write)
-                perms.set_mode(0o600);
-                std::fs::set_permissions(user_prefs_file_path(), perms)
-                    .map_err(|_| Error::FilePermissionSet)?;
+            let account = profile.id.to_string();
+            let token = profile.api_info.oauth_token();
+            // Leave unchanged tokens alone: a keychain write may prompt.
+            if secrets.load(&account).ok().flatten().as_ref() != token {
+                match token {
+                    Some(token) => secrets.save(&account, token)?,
+                    None => secrets.forget(&account)?,
+                }
             }
+            profile.api_info = profile.api_info.clone().with_oauth_token(None);
         }
+        let s = toml::to_string_pretty(&without_tokens)
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+        std::fs::write(path, s).map_err(|_| Error::ApiInfoWrite(path.to_path_buf()))?;
+        // The file no longer holds any token, but the emails and instances
+        // are still nobody else's business.
+        restrict_to_owner(path)?;
 
         // Log the successful save with full path so the frontend/devs can verify persistence.
-        log::info!(
-            "Preferences successfully saved in {}",
-            user_prefs_file_path().display()
-        );
+        log::info!("Preferences successfully saved in {}", path.display());
 
         Ok(())
     }
 
+    /// Delete the preferences and the tokens of every profile.
+    #[cfg(test)]
     pub fn forget() -> Result<(), Error> {
+        if let Ok(saved) = Self::read(user_prefs_file_path()) {
+            for profile in saved.stored_profiles() {
+                secret_store().forget(&profile.id.to_string())?;
+            }
+        }
         if user_prefs_file_path().exists() {
             std::fs::remove_file(user_prefs_file_path())
                 .map_err(|_| Error::ApiInfoWrite(user_prefs_file_path().to_path_buf()))?;
@@ -109,7 +326,7 @@ impl UserPrefs {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ensure_app_dir_exists;
+    use crate::{ensure_app_dir_exists, paths::compass_home, secret_store::EncryptedFileStore};
 
     #[test]
     #[ignore]
@@ -125,7 +342,7 @@ mod tests {
         let prefs = UserPrefs::new(ApiInfo::new(
             instance_url.clone(),
             Some(TEST_EMAIL.to_string()),
-            Some(OAUTH_TOKEN.to_string()),
+            Some(OauthToken::new(OAUTH_TOKEN).unwrap()),
         ));
 
         // Save preferences
@@ -140,7 +357,7 @@ mod tests {
         let loaded = UserPrefs::load().expect("Expected to load user prefs");
         let api_info = loaded.api_info();
         assert_eq!(api_info.instance(), &instance_url);
-        assert_eq!(api_info.oauth_token().unwrap(), OAUTH_TOKEN);
+        assert_eq!(api_info.oauth_token().unwrap().as_ref(), OAUTH_TOKEN);
     }
 
     #[test]
@@ -185,6 +402,171 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_plaintext_token_is_migrated_to_secret_store() {
+        const OAUTH_TOKEN: &str = "0123456789abcdef0123456789abcdef01234567";
+        std::fs::create_dir_all(compass_home()).expect("compass home");
+        let path = compass_home().join("user_prefs_migration_test.json");
+        let secrets = EncryptedFileStore::new(
+            compass_home().join("credentials_migration_test"),
+            b"machine\0user".to_vec(),
+        );
+        std::fs::write(
+            &path,
+            format!(
+                "[api_info]\ninstance = \"https://www.speleodb.org/\"\n\
+                 email = \"not_real@email.com\"\noauth_token = \"{OAUTH_TOKEN}\"\n"
+            ),
+        )
+        .expect("legacy prefs written");
+
+        let prefs = UserPrefs::load_from(&path, &secrets).expect("legacy prefs should load");
+        assert_eq!(
+            prefs.api_info().oauth_token().map(AsRef::as_ref),
+            Some(OAUTH_TOKEN)
+        );
+        assert!(
+            !std::fs::read_to_string(&path)
+                .unwrap()
+                .contains(OAUTH_TOKEN),
+            "the token must be removed from the plaintext prefs"
+        );
+
+        let reloaded = UserPrefs::load_from(&path, &secrets).expect("migrated prefs should load");
+        assert_eq!(
+            reloaded.api_info().oauth_token(),
+            prefs.api_info().oauth_token()
+        );
+        assert_eq!(reloaded.api_info().email(), Some("not_real@email.com"));
+        assert_eq!(reloaded.profiles().len(), 1);
+
+        let profile = reloaded.active_profile().unwrap().id().to_string();
+        secrets.forget(&profile).expect("forget credentials");
+        std::fs::remove_file(&path).expect("remove prefs");
+    }
+
+    #[test]
+    fn test_profiles_switch_and_sign_out_independently() {
+        let stage = ApiInfo::new(
+            Url::parse("https://stage.speleodb.org/").unwrap(),
+            Some("caver@example.com".to_string()),
+            Some(OauthToken::new("a".repeat(40)).unwrap()),
+        );
+        let self_hosted = ApiInfo::new(
+            Url::parse("https://speleodb.example.edu/").unwrap(),
+            Some("caver@example.com".to_string()),
+            Some(OauthToken::new("b".repeat(40)).unwrap()),
+        );
+        std::fs::create_dir_all(compass_home()).expect("compass home");
+        let path = compass_home().join("user_prefs_profiles_test.json");
+        let secrets = EncryptedFileStore::new(
+            compass_home().join("credentials_profiles_test"),
+            b"machine\0user".to_vec(),
+        );
+
+        let prefs = UserPrefs::new(stage.clone()).with_signed_in(self_hosted.clone());
+        let stage_id = prefs.profiles()[0].id();
+        let self_hosted_id = prefs.profiles()[1].id();
+        assert_eq!(prefs.active_profile().unwrap().id(), self_hosted_id);
+        assert_eq!(
+            prefs.profiles()[0].summary().name,
+            "caver@example.com @ stage.speleodb.org"
+        );
+
+        // Signing in again to a known account refreshes it instead of adding one.
+        let prefs = prefs.with_signed_in(stage.clone());
+        assert_eq!(prefs.profiles().len(), 2);
+        assert_eq!(prefs.active_profile().unwrap().id(), stage_id);
+
+        prefs.save_to(&path, &secrets).expect("save profiles");
+        let loaded = UserPrefs::load_from(&path, &secrets).expect("load profiles");
+        assert_eq!(loaded.api_info().oauth_token(), stage.oauth_token());
+        let switched = loaded
+            .with_active_profile(Some(self_hosted_id))
+            .expect("known profile");
+        assert_eq!(switched.api_info().oauth_token(), self_hosted.oauth_token());
+        assert!(
+            switched
+                .clone()
+                .with_active_profile(Some(Uuid::nil()))
+                .is_err()
+        );
+
+        let signed_out = switched.without_active_profile();
+        assert!(signed_out.active_profile().is_none());
+        assert_eq!(signed_out.profiles().len(), 1);
+        signed_out
+            .save_to(&path, &secrets)
+            .expect("save after sign out");
+        assert!(secrets.load(&stage_id.to_string()).unwrap().is_some());
+
+        // The signed-out token is kept until the server has revoked it.
+        let loaded = UserPrefs::load_from(&path, &secrets).expect("load after sign out");
+        assert_eq!(loaded.pending_revocations().len(), 1);
+        assert_eq!(
+            loaded.pending_revocations()[0].api_info().oauth_token(),
+            self_hosted.oauth_token()
+        );
+        loaded
+            .without_pending_revocation(self_hosted_id)
+            .save_to(&path, &secrets)
+            .expect("save after revocation");
+        assert_eq!(secrets.load(&self_hosted_id.to_string()).unwrap(), None);
+
+        secrets.forget(&stage_id.to_string()).unwrap();
+        std::fs::remove_file(&path).expect("remove prefs");
+    }
+
+    #[test]
+    fn test_save_rewrites_only_changed_tokens() {
+        let api_info = ApiInfo::new(
+            Url::parse("https://stage.speleodb.org/").unwrap(),
+            Some("caver@example.com".to_string()),
+            Some(OauthToken::new("a".repeat(40)).unwrap()),
+        );
+        std::fs::create_dir_all(compass_home()).expect("compass home");
+        let path = compass_home().join("user_prefs_unchanged_test.json");
+        let secrets_dir = compass_home().join("credentials_unchanged_test");
+        let secrets = EncryptedFileStore::new(secrets_dir.clone(), b"machine\0user".to_vec());
+        let prefs = UserPrefs::new(api_info.clone());
+        let account = prefs.active_profile().unwrap().id().to_string();
+        // Every save seals with a fresh salt, so a rewrite changes the file.
+        let sealed = || std::fs::read(secrets_dir.join(format!("{account}.bin"))).unwrap();
+
+        prefs.save_to(&path, &secrets).expect("first save");
+        let first = sealed();
+        prefs.save_to(&path, &secrets).expect("unchanged save");
+        assert_eq!(sealed(), first);
+
+        let renewed = prefs.with_signed_in(
+            api_info.with_oauth_token(Some(OauthToken::new("b".repeat(40)).unwrap())),
+        );
+        renewed
+            .save_to(&path, &secrets)
+            .expect("save renewed token");
+        assert_ne!(sealed(), first);
+
+        secrets.forget(&account).unwrap();
+        std::fs::remove_file(&path).expect("remove prefs");
+    }
+
+    #[test]
+    fn test_prefs_without_client_settings_use_defaults() {
+        let prefs: UserPrefs = toml::from_str(
+            "[api_info]\ninstance = \"https://www.speleodb.org/\"\nemail = \"not_real@email.com\"\n",
+        )
+        .expect("prefs saved before client settings existed should load");
+        assert_eq!(prefs.client_settings(), &ClientSettings::default());
+
+        let proxy_url = Url::parse("http://proxy.example.edu:3128/").unwrap();
+        let prefs = prefs.with_client_settings(ClientSettings {
+            proxy_url: Some(proxy_url.clone()),
+            ..ClientSettings::default()
+        });
+        let reloaded: UserPrefs = toml::from_str(&toml::to_string_pretty(&prefs).unwrap()).unwrap();
+        assert_eq!(reloaded.client_settings().proxy_url, Some(proxy_url));
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_save_user_prefs_sets_permissions() {
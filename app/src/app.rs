@@ -37,6 +37,11 @@ pub fn app() -> Html {
     }
 
     let loading_state = ui_state.loading_state.clone();
+    let expired_profile = ui_state
+        .profiles
+        .iter()
+        .find(|profile| ui_state.session_expired && Some(profile.id) == ui_state.active_profile)
+        .cloned();
     let screen = match loading_state {
         LoadingState::Ready => {
             html! {
@@ -45,7 +50,7 @@ pub fn app() -> Html {
         }
         LoadingState::Unauthenticated => {
             html! {
-               <AuthScreen/>
+               <AuthScreen profiles={ui_state.profiles.clone()} expired_profile={expired_profile} />
             }
         }
         // All other states occur on the loading screen
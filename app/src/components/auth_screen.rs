@@ -1,21 +1,71 @@
-use common::API_BASE_URL;
+use common::{API_BASE_URL, OauthToken, api_types::InstanceProbe, ui_state::ProfileSummary};
+use url::Url;
 use wasm_bindgen_futures::spawn_local;
 use yew::{
-    Callback, FocusEvent, Html, InputEvent, SubmitEvent, TargetCast, function_component, html,
-    use_state,
+    Callback, FocusEvent, Html, InputEvent, Properties, SubmitEvent, TargetCast, UseStateHandle,
+    function_component, html, use_effect_with, use_state,
 };
 
-use crate::speleo_db_controller::SPELEO_DB_CONTROLLER;
+use crate::{
+    components::profile_switcher::{ProfileSwitcher, profile_label},
+    speleo_db_controller::SPELEO_DB_CONTROLLER,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct AuthScreenProps {
+    /// Profiles still signed in, which can be switched back to.
+    pub profiles: Vec<ProfileSummary>,
+    /// Set when the server rejected this profile's token; the form starts
+    /// with its instance and email.
+    pub expired_profile: Option<ProfileSummary>,
+}
+
+/// What to show under the instance field for a probe of `instance`, and
+/// whether it is good news.
+fn probe_status(instance: &Url, probe: &InstanceProbe) -> (bool, String) {
+    match probe {
+        InstanceProbe::Available(capabilities) => {
+            let mut status = match &capabilities.version {
+                Some(version) => format!("SpeleoDB {version} found"),
+                None => "SpeleoDB found".to_string(),
+            };
+            if !capabilities.features.is_empty() {
+                status.push_str(&format!(" ({})", capabilities.features.join(", ")));
+            }
+            if instance.scheme() != "https" {
+                status.push_str("; the connection is not encrypted");
+            }
+            (true, status)
+        }
+        InstanceProbe::NotSpeleoDb => (
+            false,
+            "This address answers, but not as a SpeleoDB server".to_string(),
+        ),
+        InstanceProbe::TlsError(e) => (
+            false,
+            format!("The server's certificate is not trusted: {e}"),
+        ),
+        InstanceProbe::Unreachable(e) => (false, format!("The server can't be reached: {e}")),
+    }
+}
 
 #[function_component(AuthScreen)]
-pub fn auth_screen() -> Html {
+pub fn auth_screen(
+    AuthScreenProps {
+        profiles,
+        expired_profile,
+    }: &AuthScreenProps,
+) -> Html {
     // Fields
-    let instance = use_state(|| API_BASE_URL.to_string());
-    // Kinda dorky to force type inference
-    let email = use_state(|| {
-        let initial_state: Option<String> = None;
-        initial_state
+    let instance = use_state(|| match expired_profile {
+        Some(profile) => profile.instance.as_str().trim_end_matches('/').to_string(),
+        None => API_BASE_URL.to_string(),
     });
+    // The instance last committed (on load and when leaving the field) and
+    // the probe result for it; a result for another instance is stale.
+    let probe_target = use_state(|| (*instance).clone());
+    let probe: UseStateHandle<Option<(String, InstanceProbe)>> = use_state(|| None);
+    let email = use_state(|| expired_profile.as_ref().and_then(|p| p.email.clone()));
     let password = use_state(|| {
         let initial_state: Option<String> = None;
         initial_state
@@ -33,6 +83,20 @@ pub fn auth_screen() -> Html {
     // Silent mode for validation errors (true on startup/auto-login, false on interaction)
     let validation_silent = use_state(|| true);
 
+    {
+        let probe = probe.clone();
+        use_effect_with((*probe_target).clone(), move |target| {
+            let target = target.clone();
+            if let Ok(url) = target.parse::<Url>() {
+                spawn_local(async move {
+                    if let Ok(result) = SPELEO_DB_CONTROLLER.probe_instance(&url).await {
+                        probe.set(Some((target, result)));
+                    }
+                });
+            }
+        });
+    }
+
     let close_error = {
         let show_error = show_error.clone();
         let error_is_403 = error_is_403.clone();
@@ -57,9 +121,9 @@ pub fn auth_screen() -> Html {
             e.prevent_default();
             validation_silent.set(false);
             // quick validation
-            let oauth_ok = oauth.as_deref().is_some_and(|oauth| {
-                oauth.len() == 40 && oauth.chars().all(|c| c.is_ascii_hexdigit())
-            });
+            let oauth_ok = oauth
+                .as_deref()
+                .is_some_and(|oauth| OauthToken::new(oauth).is_ok());
 
             let pass_ok = email.as_deref().is_some_and(|email| {
                 password.as_deref().is_some_and(|_password| {
@@ -135,10 +199,12 @@ pub fn auth_screen() -> Html {
         let email = email.clone();
         let password = password.clone();
         let oauth = oauth.clone();
+        let probe_target = probe_target.clone();
         let show_error = show_error.clone();
         let error_msg = error_msg.clone();
         Callback::from(move |_| {
             instance.set("https://www.speleoDB.org".to_string());
+            probe_target.set("https://www.speleoDB.org".to_string());
             email.set(None);
             password.set(None);
             oauth.set(None);
@@ -160,6 +226,7 @@ pub fn auth_screen() -> Html {
 
     let on_instance_blur = {
         let instance = instance.clone();
+        let probe_target = probe_target.clone();
         Callback::from(move |e: FocusEvent| {
             let input: web_sys::HtmlInputElement = e.target_dyn_into().unwrap();
             let mut value = input.value();
@@ -169,6 +236,9 @@ pub fn auth_screen() -> Html {
 
                 instance.set(value.parse().unwrap());
             }
+            if !value.is_empty() {
+                probe_target.set(value);
+            }
         })
     };
 
@@ -214,7 +284,7 @@ pub fn auth_screen() -> Html {
         if val.is_empty() {
             return true;
         } // empty is ok
-        val.len() == 40 && val.chars().all(|c| c.is_ascii_hexdigit())
+        OauthToken::new(val).is_ok()
     };
 
     // Derived UI state
@@ -242,9 +312,26 @@ pub fn auth_screen() -> Html {
     let email_missing_password = has_email && !has_password && !has_oauth;
     let password_missing_email = has_password && !has_email && !has_oauth;
 
+    let instance_status = match (*probe_target).parse::<Url>() {
+        Err(_) => Some((false, "Instance URL is invalid".to_string())),
+        Ok(url) => match &*probe {
+            Some((target, result)) if *target == *probe_target => Some(probe_status(&url, result)),
+            _ => None,
+        },
+    };
+
     html! {
         <main class="container">
             <h1>{"SpeleoDB - Compass Sidecar"}</h1>
+            <ProfileSwitcher profiles={profiles.clone()} active_profile={None} />
+            if let Some(profile) = expired_profile {
+                <p class="field-error">
+                    {format!(
+                        "The session of {} has expired. Sign in again to continue; the open project and its unsaved changes are kept.",
+                        profile_label(profile)
+                    )}
+                </p>
+            }
 
             <div class="row">
                 <img src="public/speleodb_long.png" class="logo speleodb" alt="SpeleoDB logo"/>
@@ -257,13 +344,18 @@ pub fn auth_screen() -> Html {
                         id="instance"
                         type="text"
                         class={if false { "full invalid" } else { "full" }}
+                        // Only filled in when signing in again; otherwise the
+                        // default instance shows as the placeholder.
+                        value={expired_profile.is_some().then(|| (*instance).clone())}
                         oninput={on_instance_input}
                         onblur={on_instance_blur}
                         placeholder={API_BASE_URL}
                     />
-                    { if false {
-                        html!{ <span class="field-error">{"Must start with http:// or https://"}</span> }
-                    } else { html!{} } }
+                    { match instance_status {
+                        Some((true, status)) => html!{ <span class="field-ok">{status}</span> },
+                        Some((false, status)) => html!{ <span class="field-error">{status}</span> },
+                        None => html!{ <span class="field-ok" style="color: #6b7280;">{"Checking the instance..."}</span> },
+                    } }
                 </div>
 
                 <div class="accent-bar" aria-hidden="true" />
@@ -358,3 +450,36 @@ pub fn auth_screen() -> Html {
         </main>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ServerCapabilities;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn probe_status_describes_the_server() {
+        let https = Url::parse("https://www.speleodb.org").unwrap();
+        let http = Url::parse("http://localhost:8000").unwrap();
+        let capabilities = ServerCapabilities {
+            version: Some("2.4.0".to_string()),
+            features: vec!["project_history".to_string()],
+        };
+        let available = InstanceProbe::Available(capabilities);
+
+        assert_eq!(
+            probe_status(&https, &available),
+            (true, "SpeleoDB 2.4.0 found (project_history)".to_string())
+        );
+        let (ok, status) = probe_status(&http, &available);
+        assert!(ok);
+        assert!(status.ends_with("not encrypted"));
+        assert!(!probe_status(&https, &InstanceProbe::NotSpeleoDb).0);
+        assert!(!probe_status(&https, &InstanceProbe::TlsError("expired".to_string())).0);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::speleo_db_controller::SPELEO_DB_CONTROLLER;
+use common::api_types::ProjectMetadata;
 use log::{error, info};
 use std::collections::BTreeMap;
 use wasm_bindgen::JsCast;
@@ -7,6 +8,46 @@ use web_sys::HtmlInputElement;
 use web_sys::HtmlTextAreaElement;
 use yew::prelude::*;
 
+/// Countries as `(code, name)`, sorted by name.
+pub(crate) fn sorted_countries() -> Vec<(String, String)> {
+    let json = include_str!("assets/countries.json");
+    let countries_map: BTreeMap<String, String> = serde_json::from_str(json).unwrap_or_default();
+    let mut countries_vec: Vec<(String, String)> = countries_map.into_iter().collect();
+    // Sort by country name (value) instead of code (key)
+    countries_vec.sort_by(|a, b| a.1.cmp(&b.1));
+    countries_vec
+}
+
+/// Check the project form before it is sent, returning the message to show.
+/// Shared by the create and edit modals.
+pub(crate) fn validate_project_metadata(metadata: &ProjectMetadata) -> Result<(), String> {
+    if metadata.name.trim().is_empty() {
+        return Err("Project name is required".to_string());
+    }
+    if metadata.name.len() > 255 {
+        return Err("Project name must be less than 255 characters".to_string());
+    }
+    if metadata.description.trim().is_empty() {
+        return Err("Description is required".to_string());
+    }
+    if metadata.country.is_empty() {
+        return Err("Please select a country".to_string());
+    }
+    // Validate Lat/Lon if provided
+    let is_invalid_number = |value: &Option<String>| {
+        value
+            .as_deref()
+            .is_some_and(|v| !v.is_empty() && v.parse::<f64>().is_err())
+    };
+    if is_invalid_number(&metadata.latitude) {
+        return Err("Latitude must be a valid number".to_string());
+    }
+    if is_invalid_number(&metadata.longitude) {
+        return Err("Longitude must be a valid number".to_string());
+    }
+    Ok(())
+}
+
 #[derive(Properties, PartialEq, Clone)]
 pub struct CreateProjectModalProps {
     pub on_close: Callback<()>,
@@ -23,16 +64,7 @@ pub fn create_project_modal(props: &CreateProjectModalProps) -> Html {
     let error_message = use_state(|| None::<String>);
     let is_submitting = use_state(|| false);
 
-    // Load countries and sort by name
-    let countries: Vec<(String, String)> = {
-        let json = include_str!("assets/countries.json");
-        let countries_map: BTreeMap<String, String> =
-            serde_json::from_str(json).unwrap_or_default();
-        let mut countries_vec: Vec<(String, String)> = countries_map.into_iter().collect();
-        // Sort by country name (value) instead of code (key)
-        countries_vec.sort_by(|a, b| a.1.cmp(&b.1));
-        countries_vec
-    };
+    let countries = sorted_countries();
 
     let on_submit = {
         let name = name.clone();
@@ -52,32 +84,14 @@ pub fn create_project_modal(props: &CreateProjectModalProps) -> Html {
             let lat_val = (*latitude).clone();
             let lon_val = (*longitude).clone();
 
-            // Validation
-            if name_val.trim().is_empty() {
-                error_message.set(Some("Project name is required".to_string()));
-                return;
-            }
-            if name_val.len() > 255 {
-                error_message.set(Some(
-                    "Project name must be less than 255 characters".to_string(),
-                ));
-                return;
-            }
-            if desc_val.trim().is_empty() {
-                error_message.set(Some("Description is required".to_string()));
-                return;
-            }
-            if country_val.is_empty() {
-                error_message.set(Some("Please select a country".to_string()));
-                return;
-            }
-            // Validate Lat/Lon if provided
-            if !lat_val.is_empty() && lat_val.parse::<f64>().is_err() {
-                error_message.set(Some("Latitude must be a valid number".to_string()));
-                return;
-            }
-            if !lon_val.is_empty() && lon_val.parse::<f64>().is_err() {
-                error_message.set(Some("Longitude must be a valid number".to_string()));
+            if let Err(message) = validate_project_metadata(&ProjectMetadata {
+                name: name_val.clone(),
+                description: desc_val.clone(),
+                country: country_val.clone(),
+                latitude: Some(lat_val.clone()),
+                longitude: Some(lon_val.clone()),
+            }) {
+                error_message.set(Some(message));
                 return;
             }
 
@@ -288,3 +302,55 @@ pub fn create_project_modal(props: &CreateProjectModalProps) -> Html {
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn valid_metadata() -> ProjectMetadata {
+        ProjectMetadata {
+            name: "Sistema Ox Bel Ha".to_string(),
+            description: "Underwater cave system".to_string(),
+            country: "MX".to_string(),
+            latitude: Some("20.5".to_string()),
+            longitude: None,
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn validation_accepts_complete_metadata_with_optional_coordinates() {
+        assert_eq!(validate_project_metadata(&valid_metadata()), Ok(()));
+        let no_coordinates = ProjectMetadata {
+            latitude: Some(String::new()),
+            ..valid_metadata()
+        };
+        assert_eq!(validate_project_metadata(&no_coordinates), Ok(()));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn validation_reports_the_first_invalid_field() {
+        let blank_name = ProjectMetadata {
+            name: "  ".to_string(),
+            ..valid_metadata()
+        };
+        assert_eq!(
+            validate_project_metadata(&blank_name),
+            Err("Project name is required".to_string())
+        );
+        let bad_longitude = ProjectMetadata {
+            longitude: Some("west".to_string()),
+            ..valid_metadata()
+        };
+        assert_eq!(
+            validate_project_metadata(&bad_longitude),
+            Err("Longitude must be a valid number".to_string())
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use crate::components::create_project_modal::{sorted_countries, validate_project_metadata};
+use crate::speleo_db_controller::SPELEO_DB_CONTROLLER;
+use common::api_types::ProjectMetadata;
+use log::{error, info};
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct EditProjectModalProps {
+    pub project_id: Uuid,
+    /// Current values, used to fill the form.
+    pub metadata: ProjectMetadata,
+    pub on_close: Callback<()>,
+}
+
+#[function_component(EditProjectModal)]
+pub fn edit_project_modal(props: &EditProjectModalProps) -> Html {
+    let name = use_state(|| props.metadata.name.clone());
+    let description = use_state(|| props.metadata.description.clone());
+    let country = use_state(|| props.metadata.country.clone());
+    let latitude = use_state(|| props.metadata.latitude.clone().unwrap_or_default());
+    let longitude = use_state(|| props.metadata.longitude.clone().unwrap_or_default());
+
+    let error_message = use_state(|| None::<String>);
+    let is_submitting = use_state(|| false);
+
+    let countries = sorted_countries();
+
+    let on_submit = {
+        let project_id = props.project_id;
+        let on_close = props.on_close.clone();
+        let name = name.clone();
+        let description = description.clone();
+        let country = country.clone();
+        let latitude = latitude.clone();
+        let longitude = longitude.clone();
+        let error_message = error_message.clone();
+        let is_submitting = is_submitting.clone();
+
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            let metadata = ProjectMetadata {
+                name: (*name).clone(),
+                description: (*description).clone(),
+                country: (*country).clone(),
+                latitude: Some((*latitude).clone()).filter(|lat| !lat.is_empty()),
+                longitude: Some((*longitude).clone()).filter(|lon| !lon.is_empty()),
+            };
+            if let Err(message) = validate_project_metadata(&metadata) {
+                error_message.set(Some(message));
+                return;
+            }
+
+            let on_close = on_close.clone();
+            let error_message = error_message.clone();
+            let is_submitting = is_submitting.clone();
+
+            is_submitting.set(true);
+            error_message.set(None);
+
+            spawn_local(async move {
+                match SPELEO_DB_CONTROLLER
+                    .update_project(project_id, &metadata)
+                    .await
+                {
+                    Ok(()) => {
+                        info!("Project details updated successfully");
+                        is_submitting.set(false);
+                        on_close.emit(());
+                    }
+                    Err(e) => {
+                        error!("Error updating project details: {}", e);
+                        is_submitting.set(false);
+                        error_message.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="modal" style="
+            position: fixed;
+            top: 0;
+            left: 0;
+            width: 100vw;
+            height: 100vh;
+            background-color: rgba(0, 0, 0, 0.5);
+            display: flex;
+            align-items: center;
+            justify-content: center;
+            z-index: 1000;
+        ">
+            <div class="modal-card" style="
+                background-color: rgba(41, 62, 112, 1);
+                color: #f6f6f6;
+                border-radius: 12px;
+                padding: 24px;
+                max-width: 600px;
+                width: 90%;
+                box-shadow: 0 10px 25px rgba(0, 0, 0, 0.2);
+                max-height: 90vh;
+                overflow-y: auto;
+            ">
+                <h2 style="margin-top: 0; margin-bottom: 20px; color: #f6f6f6;">{"Edit Project Details"}</h2>
+
+                {
+                    if let Some(msg) = &*error_message {
+                        html! {
+                            <div style="
+                                padding: 12px;
+                                background-color: #fee2e2;
+                                border: 1px solid #ef4444;
+                                border-radius: 6px;
+                                margin-bottom: 16px;
+                                color: #b91c1c;
+                            ">
+                                {msg}
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+
+                <form onsubmit={on_submit}>
+                    <div style="margin-bottom: 16px;">
+                        <label style="display: block; margin-bottom: 4px; font-weight: 500; color: #f6f6f6;">{"Project Name *"}</label>
+                        <input
+                            type="text"
+                            value={(*name).clone()}
+                            oninput={Callback::from(move |e: InputEvent| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                name.set(input.value());
+                            })}
+                            style="width: 100%; padding: 8px; border: 1px solid transparent; border-radius: 8px; box-sizing: border-box; font-family: inherit; font-size: 14px; background-color: rgb(205, 205, 205); color: #313131;"
+                            disabled={*is_submitting}
+                        />
+                    </div>
+
+                    <div style="margin-bottom: 16px;">
+                        <label style="display: block; margin-bottom: 4px; font-weight: 500; color: #f6f6f6;">{"Description *"}</label>
+                        <textarea
+                            value={(*description).clone()}
+                            oninput={Callback::from(move |e: InputEvent| {
+                                let input: HtmlTextAreaElement = e.target_unchecked_into();
+                                description.set(input.value());
+                            })}
+                            style="width: 100%; padding: 8px; border: 1px solid transparent; border-radius: 8px; min-height: 80px; box-sizing: border-box; font-family: inherit; font-size: 14px; resize: vertical; background-color: rgb(205, 205, 205); color: #313131;"
+                            disabled={*is_submitting}
+                        />
+                    </div>
+
+                    <div style="margin-bottom: 16px;">
+                        <label style="display: block; margin-bottom: 4px; font-weight: 500; color: #f6f6f6;">{"Country *"}</label>
+                        <select
+                            onchange={
+                                let country = country.clone();
+                                Callback::from(move |e: Event| {
+                                    if let Some(select) = e.target().and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok()) {
+                                        country.set(select.value());
+                                    }
+                                })
+                            }
+                            style="width: 100%; padding: 8px; border: 1px solid transparent; border-radius: 8px; box-sizing: border-box; font-family: inherit; font-size: 14px; background-color: rgb(205, 205, 205); color: #313131;"
+                            disabled={*is_submitting}
+                        >
+                            {
+                                for countries.iter().map(|(code, name)| {
+                                    html! {
+                                        <option value={code.clone()} selected={*code == *country}>{name}</option>
+                                    }
+                                })
+                            }
+                        </select>
+                    </div>
+
+                    <div style="margin-bottom: 24px;">
+                        <div style="display: grid; grid-template-columns: 1fr 1fr; gap: 12px;">
+                            <div>
+                                <label style="display: block; margin-bottom: 4px; font-weight: 500; color: #f6f6f6;">{"Latitude"}</label>
+                                <input
+                                    type="text"
+                                    value={(*latitude).clone()}
+                                    oninput={Callback::from(move |e: InputEvent| {
+                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                        latitude.set(input.value());
+                                    })}
+                                    style="width: 100%; padding: 8px; border: 1px solid transparent; border-radius: 8px; box-sizing: border-box; font-family: inherit; font-size: 14px; background-color: rgb(205, 205, 205); color: #313131;"
+                                    placeholder="e.g. 45.1234"
+                                    disabled={*is_submitting}
+                                />
+                            </div>
+                            <div>
+                                <label style="display: block; margin-bottom: 4px; font-weight: 500; color: #f6f6f6;">{"Longitude"}</label>
+                                <input
+                                    type="text"
+                                    value={(*longitude).clone()}
+                                    oninput={Callback::from(move |e: InputEvent| {
+                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                        longitude.set(input.value());
+                                    })}
+                                    style="width: 100%; padding: 8px; border: 1px solid transparent; border-radius: 8px; box-sizing: border-box; font-family: inherit; font-size: 14px; background-color: rgb(205, 205, 205); color: #313131;"
+                                    placeholder="e.g. -93.5678"
+                                    disabled={*is_submitting}
+                                />
+                            </div>
+                        </div>
+                    </div>
+
+                    <div style="display: flex; justify-content: flex-end; gap: 12px;">
+                        <button
+                            type="button"
+                            onclick={props.on_close.reform(|_| ())}
+                            style="
+                                padding: 8px 16px;
+                                border: 1px solid #d1d5db;
+                                border-radius: 6px;
+                                background-color: white;
+                                color: #374151;
+                                cursor: pointer;
+                            "
+                            disabled={*is_submitting}
+                        >
+                            {"Cancel"}
+                        </button>
+                        <button
+                            type="submit"
+                            style="
+                                padding: 8px 16px;
+                                border: none;
+                                border-radius: 6px;
+                                background-color: #2563eb;
+                                color: white;
+                                cursor: pointer;
+                                font-weight: 500;
+                            "
+                            disabled={*is_submitting}
+                        >
+                            {if *is_submitting { "Saving..." } else { "Save Changes" }}
+                        </button>
+                    </div>
+                </form>
+            </div>
+        </div>
+    }
+}
@@ -1,7 +1,10 @@
 use common::ui_state::UiState;
 use yew::{Html, Properties, classes, function_component, html};
 
-use crate::components::{project_details::ProjectDetails, project_listing::ProjectListing};
+use crate::components::{
+    profile_switcher::ProfileSwitcher, project_details::ProjectDetails,
+    project_listing::ProjectListing,
+};
 
 #[derive(Properties, PartialEq)]
 pub struct MainLayoutProps {
@@ -36,11 +39,15 @@ pub fn main_layout(MainLayoutProps { ui_state }: &MainLayoutProps) -> Html {
                 <div>
                     <h1 class={classes!("vertically-centered-text")} >{"SpeleoDB Compass Sidecar"}</h1>
                 </div>
+                // Switching closes the open project, so only offer it from the list.
+                if ui_state.selected_project_id.is_none() {
+                    <ProfileSwitcher profiles={ui_state.profiles.clone()} active_profile={ui_state.active_profile} />
+                }
             </header>
             <section style="width:100%;">
                 {
                     if let Some((selected_project, email)) = selected_project_info {
-                        html!{ <ProjectDetails project={selected_project} user_email={email} compass_open={ui_state.compass_open} project_downloading={ui_state.project_downloading} /> }
+                        html!{ <ProjectDetails project={selected_project} user_email={email} compass_open={ui_state.compass_open} project_downloading={ui_state.project_downloading} project_download_progress={ui_state.project_download_progress} project_upload_progress={ui_state.project_upload_progress} mutex_warning={ui_state.mutex_warning.clone()} /> }
                     } else {
                         html!{ <ProjectListing  ui_state={ui_state.clone()}/> }
                     }
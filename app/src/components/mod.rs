@@ -1,8 +1,10 @@
 pub mod auth_screen;
 pub mod create_project_modal;
+pub mod edit_project_modal;
 pub mod loading_screen;
 pub mod main_layout;
 pub mod modal;
+pub mod profile_switcher;
 pub mod project_details;
 pub mod project_listing;
 pub mod project_listing_item;
@@ -0,0 +1,126 @@
+use common::ui_state::ProfileSummary;
+use log::error;
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+use crate::speleo_db_controller::SPELEO_DB_CONTROLLER;
+
+#[derive(Properties, PartialEq)]
+pub struct ProfileSwitcherProps {
+    pub profiles: Vec<ProfileSummary>,
+    /// `None` on the sign-in screen.
+    pub active_profile: Option<Uuid>,
+}
+
+/// The profile's name, followed by its instance unless the name already
+/// mentions it.
+pub(crate) fn profile_label(profile: &ProfileSummary) -> String {
+    match profile.instance.host_str() {
+        Some(host) if !profile.name.contains(host) => format!("{} ({host})", profile.name),
+        _ => profile.name.clone(),
+    }
+}
+
+#[function_component(ProfileSwitcher)]
+pub fn profile_switcher(
+    ProfileSwitcherProps {
+        profiles,
+        active_profile,
+    }: &ProfileSwitcherProps,
+) -> Html {
+    let switch_error: UseStateHandle<Option<String>> = use_state(|| None);
+    if profiles.is_empty() {
+        return html! {};
+    }
+
+    // The empty value stands for "no profile": the sign-in screen, where
+    // another account can be added.
+    let onchange = {
+        let active_profile = *active_profile;
+        let switch_error = switch_error.clone();
+        Callback::from(move |e: Event| {
+            let Some(select) = e
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::HtmlSelectElement>().ok())
+            else {
+                return;
+            };
+            let profile_id = select.value().parse::<Uuid>().ok();
+            if profile_id == active_profile {
+                return;
+            }
+            let switch_error = switch_error.clone();
+            switch_error.set(None);
+            spawn_local(async move {
+                if let Err(e) = SPELEO_DB_CONTROLLER.switch_profile(profile_id).await {
+                    error!("Failed to switch profile: {}", e);
+                    // Still on the old profile: show it selected again.
+                    select.set_value(&active_profile.map(|id| id.to_string()).unwrap_or_default());
+                    switch_error.set(Some(e));
+                }
+            });
+        })
+    };
+
+    html! {
+        <label style="display:flex; align-items:center; gap:8px;">
+            <span>{"Account"}</span>
+            <select {onchange} style="padding: 6px 8px; border-radius: 8px; font-family: inherit; font-size: 14px;">
+                {
+                    for profiles.iter().map(|profile| html! {
+                        <option
+                            value={profile.id.to_string()}
+                            selected={Some(profile.id) == *active_profile}
+                        >
+                            {profile_label(profile)}
+                        </option>
+                    })
+                }
+                <option value="" selected={active_profile.is_none()}>{"Add account..."}</option>
+            </select>
+            {
+                if let Some(err) = &*switch_error {
+                    html! {
+                        <span style="color: #dc2626; font-size: 14px;">
+                            {format!("Can't switch account: {err}")}
+                        </span>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </label>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn profile(name: &str) -> ProfileSummary {
+        ProfileSummary {
+            id: Uuid::nil(),
+            name: name.to_string(),
+            instance: Url::parse("https://www.speleodb.org").unwrap(),
+            email: Some("me@example.com".to_string()),
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn profile_label_names_the_instance_once() {
+        assert_eq!(
+            profile_label(&profile("me@example.com @ www.speleodb.org")),
+            "me@example.com @ www.speleodb.org"
+        );
+        assert_eq!(profile_label(&profile("Work")), "Work (www.speleodb.org)");
+    }
+}
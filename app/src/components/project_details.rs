@@ -8,14 +8,16 @@
 //! [ ] Investigate making files read-only when in read-only mode
 //! [ ] Show whether Compass is being tracked open on Windows
 
+use crate::Error;
+use crate::components::edit_project_modal::EditProjectModal;
 use crate::components::modal::{Modal, ModalType};
 use crate::speleo_db_controller::SPELEO_DB_CONTROLLER;
 use crate::ui_constants::{COLOR_ALARM, COLOR_GOOD, COLOR_WARN, FONT_COLOR_BLUE};
-#[cfg(any(target_arch = "wasm32", test))]
-use common::SERVER_TIME_ZONE;
-use common::api_types::{CommitInfo, ProjectSaveResult};
-use common::ui_state::{LocalProjectStatus, ProjectStatus};
-use log::info;
+use chrono::{DateTime, Utc};
+use common::api_types::{CommitInfo, Permission, ProjectPermissions, ProjectSaveResult};
+use common::ui_state::{LocalProjectStatus, MutexWarning, ProjectStatus};
+use log::{error, info};
+use uuid::Uuid;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 use yew_icons::{Icon, IconData};
@@ -26,6 +28,10 @@ pub struct ProjectDetailsProps {
     pub user_email: String,
     pub compass_open: bool,
     pub project_downloading: bool,
+    pub project_download_progress: Option<u8>,
+    pub project_upload_progress: Option<u8>,
+    /// Set when the background renewal found the project's lock gone.
+    pub mutex_warning: Option<MutexWarning>,
 }
 
 #[cfg(test)]
@@ -89,32 +95,58 @@ mod tests {
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), test)]
-    fn commit_relative_time_is_sanitized_when_in_future() {
-        assert_eq!(normalize_commit_relative_time("in 4 hours"), "just now");
+    fn project_download_message_reflects_progress() {
+        assert_eq!(
+            project_download_message(None),
+            "Downloading and extracting project..."
+        );
+        assert_eq!(
+            project_download_message(Some(42)),
+            "Downloading project (42%)..."
+        );
+        assert_eq!(project_download_message(Some(100)), "Extracting project...");
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), test)]
-    fn commit_relative_time_keeps_past_times() {
+    fn project_upload_message_reflects_progress() {
         assert_eq!(
-            normalize_commit_relative_time("2 minutes ago"),
-            "2 minutes ago"
+            project_upload_message(None),
+            "Uploading your Compass project to SpeleoDB. This can take a moment."
+        );
+        assert_eq!(
+            project_upload_message(Some(42)),
+            "Uploading your Compass project to SpeleoDB (42%)..."
+        );
+        assert_eq!(
+            project_upload_message(Some(100)),
+            "Upload complete, waiting for SpeleoDB to record the commit..."
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn short_commit_id_truncates_long_ids_only() {
+        assert_eq!(
+            short_commit_id("0123456789abcdef0123456789abcdef01234567"),
+            "01234567"
         );
+        assert_eq!(short_commit_id("abc"), "abc");
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), test)]
-    fn commit_date_candidates_keep_explicit_offset() {
-        let candidates = commit_date_parse_candidates("2026-02-26T01:02:43-05:00");
-        assert_eq!(candidates, vec!["2026-02-26T01:02:43-05:00".to_string()]);
+    fn commit_relative_time_is_sanitized_when_in_future() {
+        assert_eq!(normalize_commit_relative_time("in 4 hours"), "just now");
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), test)]
-    fn commit_date_candidates_add_server_timezone_offsets() {
-        let candidates = commit_date_parse_candidates("2026-02-26T01:02:43");
-        assert!(candidates.contains(&"2026-02-26T01:02:43-05:00".to_string()));
-        assert!(candidates.contains(&"2026-02-26T01:02:43-04:00".to_string()));
+    fn commit_relative_time_keeps_past_times() {
+        assert_eq!(
+            normalize_commit_relative_time("2 minutes ago"),
+            "2 minutes ago"
+        );
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -155,6 +187,65 @@ mod tests {
         assert!(!state.show_upload_success);
         assert!(state.show_no_changes_modal);
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn mutex_warning_message_names_the_new_holder() {
+        let taken = MutexWarning {
+            project_id: Uuid::nil(),
+            taken_by: Some("other@example.com".to_string()),
+        };
+        assert!(mutex_warning_message(&taken).starts_with("other@example.com has taken the lock"));
+        let lost = MutexWarning {
+            project_id: Uuid::nil(),
+            taken_by: None,
+        };
+        assert!(mutex_warning_message(&lost).contains("expired or was released"));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn project_access_splits_editors_from_readers() {
+        use common::api_types::{TeamPermission, UserPermission};
+
+        let permissions = ProjectPermissions {
+            users: vec![
+                UserPermission {
+                    user: "owner@example.com".to_string(),
+                    level: Permission::Admin,
+                },
+                UserPermission {
+                    user: "visitor@example.com".to_string(),
+                    level: Permission::WebViewer,
+                },
+            ],
+            teams: vec![TeamPermission {
+                team: "Dive Team".to_string(),
+                level: Permission::ReadAndWrite,
+            }],
+        };
+        let (editors, readers) = split_project_access(&permissions);
+        assert_eq!(
+            editors,
+            vec![
+                AccessEntry {
+                    who: "owner@example.com".to_string(),
+                    level: "Admin".to_string(),
+                },
+                AccessEntry {
+                    who: "Dive Team (team)".to_string(),
+                    level: "Read & write".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            readers,
+            vec![AccessEntry {
+                who: "visitor@example.com".to_string(),
+                level: "Web viewer".to_string(),
+            }]
+        );
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -187,6 +278,22 @@ fn should_disable_project_action_buttons(compass_open: bool, busy: bool) -> bool
     compass_open || busy
 }
 
+fn project_download_message(progress_percent: Option<u8>) -> String {
+    match progress_percent {
+        Some(100) => "Extracting project...".to_string(),
+        Some(percent) => format!("Downloading project ({percent}%)..."),
+        None => "Downloading and extracting project...".to_string(),
+    }
+}
+
+fn project_upload_message(progress_percent: Option<u8>) -> String {
+    match progress_percent {
+        Some(100) => "Upload complete, waiting for SpeleoDB to record the commit...".to_string(),
+        Some(percent) => format!("Uploading your Compass project to SpeleoDB ({percent}%)..."),
+        None => "Uploading your Compass project to SpeleoDB. This can take a moment.".to_string(),
+    }
+}
+
 fn normalize_commit_relative_time(relative_time: &str) -> String {
     let trimmed = relative_time.trim();
     if trimmed.to_ascii_lowercase().starts_with("in ") {
@@ -196,86 +303,411 @@ fn normalize_commit_relative_time(relative_time: &str) -> String {
     }
 }
 
-/// Helper for `commit_date_parse_candidates` / `format_commit_date_in_local_timezone`.
-/// Only consumed on `wasm32` (production) and under `cfg(test)` (tests run on
-/// both targets). Gating prevents a `dead_code` warning on native builds where
-/// neither caller is compiled in.
-#[cfg(any(target_arch = "wasm32", test))]
-fn has_explicit_timezone(timestamp: &str) -> bool {
-    let trimmed = timestamp.trim();
-    if trimmed.ends_with('Z') || trimmed.ends_with('z') {
-        return true;
-    }
-    if trimmed.len() >= 6 {
-        let suffix = &trimmed[trimmed.len() - 6..];
-        if (suffix.starts_with('+') || suffix.starts_with('-'))
-            && &suffix[3..4] == ":"
-            && suffix[1..3].chars().all(|c| c.is_ascii_digit())
-            && suffix[4..6].chars().all(|c| c.is_ascii_digit())
-        {
-            return true;
-        }
-    }
-    if trimmed.len() >= 5 {
-        let suffix = &trimmed[trimmed.len() - 5..];
-        if (suffix.starts_with('+') || suffix.starts_with('-'))
-            && suffix[1..].chars().all(|c| c.is_ascii_digit())
-        {
-            return true;
-        }
-    }
-    false
+/// `datetime` in the user's time zone and locale.
+#[cfg(target_arch = "wasm32")]
+fn format_in_local_timezone(datetime: DateTime<Utc>) -> Option<String> {
+    let millis = wasm_bindgen::JsValue::from_f64(datetime.timestamp_millis() as f64);
+    let local_display =
+        js_sys::Date::new(&millis).to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED);
+    let local_display: String = local_display.into();
+    let local_display = local_display.trim();
+    (!local_display.is_empty()).then(|| local_display.to_string())
 }
 
-#[cfg(any(target_arch = "wasm32", test))]
-fn commit_date_parse_candidates(commit_date: &str) -> Vec<String> {
-    let trimmed = commit_date.trim();
-    if trimmed.is_empty() {
-        return vec![];
-    }
-    if has_explicit_timezone(trimmed) {
-        return vec![trimmed.to_string()];
+#[cfg(not(target_arch = "wasm32"))]
+fn format_in_local_timezone(_datetime: DateTime<Utc>) -> Option<String> {
+    None
+}
+
+fn display_commit_time(commit: &CommitInfo) -> String {
+    if let Some(commit_date) = commit.commit_date
+        && let Some(local_display) = format_in_local_timezone(commit_date)
+    {
+        return local_display;
     }
+    normalize_commit_relative_time(&commit.dt_since)
+}
+
+/// Abbreviated commit id for display; the full id goes in a tooltip.
+fn short_commit_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
 
-    let mut candidates = vec![trimmed.to_string()];
-    if matches!(SERVER_TIME_ZONE, "US/Eastern" | "America/New_York") {
-        candidates.push(format!("{trimmed}-05:00"));
-        candidates.push(format!("{trimmed}-04:00"));
+fn permission_label(level: &Permission) -> String {
+    match level {
+        Permission::Admin => "Admin".to_string(),
+        Permission::ReadAndWrite => "Read & write".to_string(),
+        Permission::ReadOnly => "Read only".to_string(),
+        Permission::WebViewer => "Web viewer".to_string(),
+        Permission::Unknown(other) => other.clone(),
     }
-    candidates
 }
 
-#[cfg(target_arch = "wasm32")]
-fn format_commit_date_in_local_timezone(commit_date: &str) -> Option<String> {
-    for candidate in commit_date_parse_candidates(commit_date) {
-        let millis = js_sys::Date::parse(&candidate);
-        if !millis.is_finite() {
-            continue;
+#[derive(Clone, Debug, PartialEq)]
+struct AccessEntry {
+    who: String,
+    level: String,
+}
+
+/// Split everyone with access into those who can lock and upload the project
+/// and those who can only read it. Teams follow users.
+fn split_project_access(permissions: &ProjectPermissions) -> (Vec<AccessEntry>, Vec<AccessEntry>) {
+    let users = permissions.users.iter().map(|p| (p.user.clone(), &p.level));
+    let teams = permissions
+        .teams
+        .iter()
+        .map(|p| (format!("{} (team)", p.team), &p.level));
+    users
+        .chain(teams)
+        .map(|(who, level)| {
+            (
+                level.allows_editing(),
+                AccessEntry {
+                    who,
+                    level: permission_label(level),
+                },
+            )
+        })
+        .fold(
+            (Vec::new(), Vec::new()),
+            |(mut editors, mut readers), (can_edit, entry)| {
+                if can_edit {
+                    editors.push(entry);
+                } else {
+                    readers.push(entry);
+                }
+                (editors, readers)
+            },
+        )
+}
+
+#[derive(Properties, PartialEq)]
+struct ProjectAccessProps {
+    project_id: Uuid,
+}
+
+/// Read-only list of the users and teams with access to a project.
+#[function_component(ProjectAccess)]
+fn project_access(ProjectAccessProps { project_id }: &ProjectAccessProps) -> Html {
+    let permissions: UseStateHandle<Option<ProjectPermissions>> = use_state(|| None);
+    let load_error: UseStateHandle<Option<String>> = use_state(|| None);
+
+    {
+        let permissions = permissions.clone();
+        let load_error = load_error.clone();
+        use_effect_with(*project_id, move |project_id| {
+            let project_id = *project_id;
+            spawn_local(async move {
+                match SPELEO_DB_CONTROLLER
+                    .fetch_project_permissions(project_id)
+                    .await
+                {
+                    Ok(fetched) => {
+                        permissions.set(Some(fetched));
+                        load_error.set(None);
+                    }
+                    Err(e) => load_error.set(Some(e.to_string())),
+                }
+            });
+        });
+    }
+
+    let render_group = |title: &str, entries: Vec<AccessEntry>| {
+        if entries.is_empty() {
+            return html! {};
         }
-        let parsed_date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(millis));
-        let local_display =
-            parsed_date.to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED);
-        let local_display: String = local_display.into();
-        let local_display = local_display.trim();
-        if !local_display.is_empty() {
-            return Some(local_display.to_string());
+        html! {
+            <div style="margin-bottom: 12px;">
+                <div style="font-size: 13px; font-weight: 600; color: #374151; margin-bottom: 4px;">{title}</div>
+                <ul style="list-style: none; margin: 0; padding: 0;">
+                    { for entries.into_iter().map(|entry| html! {
+                        <li style="display: flex; justify-content: space-between; padding: 4px 0; border-bottom: 1px solid #f3f4f6; font-size: 14px;">
+                            <span style="color: #1f2937;">{entry.who}</span>
+                            <span style="color: #6b7280;">{entry.level}</span>
+                        </li>
+                    }) }
+                </ul>
+            </div>
         }
+    };
+
+    html! {
+        <div style="margin-top: 24px; padding-top: 24px; border-top: 1px solid #e5e7eb;">
+            <h3 style="margin-bottom: 12px;">{"Access"}</h3>
+            {
+                if let Some(err) = &*load_error {
+                    html! {
+                        <p style="color: #dc2626; font-size: 14px;">
+                            {format!("Failed to load permissions: {err}")}
+                        </p>
+                    }
+                } else if let Some(permissions) = &*permissions {
+                    let (editors, readers) = split_project_access(permissions);
+                    html! {
+                        <>
+                            {render_group("Can edit", editors)}
+                            {render_group("Read only", readers)}
+                        </>
+                    }
+                } else {
+                    html! {
+                        <p style="color: #6b7280; font-size: 14px;">{"Loading permissions..."}</p>
+                    }
+                }
+            }
+        </div>
     }
-    None
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn format_commit_date_in_local_timezone(_commit_date: &str) -> Option<String> {
-    None
+#[derive(Properties, PartialEq)]
+struct CommitHistoryProps {
+    project_id: Uuid,
+    /// Reloads the history whenever the project's latest commit changes.
+    latest_commit_id: Option<String>,
+    /// Whether older commits may be reverted to right now.
+    can_revert: bool,
+    on_revert: Callback<String>,
 }
 
-fn display_commit_time(commit: &CommitInfo) -> String {
-    if let Some(commit_date) = commit.commit_date.as_deref()
-        && let Some(local_display) = format_commit_date_in_local_timezone(commit_date)
+/// Commit history of a project, newest first, one page at a time.
+#[function_component(CommitHistory)]
+fn commit_history(
+    CommitHistoryProps {
+        project_id,
+        latest_commit_id,
+        can_revert,
+        on_revert,
+    }: &CommitHistoryProps,
+) -> Html {
+    let commits: UseStateHandle<Vec<CommitInfo>> = use_state(Vec::new);
+    let next_page: UseStateHandle<Option<u32>> = use_state(|| None);
+    let loading = use_state(|| false);
+    let load_error: UseStateHandle<Option<String>> = use_state(|| None);
+    // Commit currently being downloaded for a read-only open, if any.
+    let opening: UseStateHandle<Option<String>> = use_state(|| None);
+    // Commit whose "Revert" button was clicked and awaits confirmation.
+    let confirm_revert: UseStateHandle<Option<String>> = use_state(|| None);
+
     {
-        return local_display;
+        let commits = commits.clone();
+        let next_page = next_page.clone();
+        let loading = loading.clone();
+        let load_error = load_error.clone();
+        use_effect_with(
+            (*project_id, latest_commit_id.clone()),
+            move |(project_id, _)| {
+                let project_id = *project_id;
+                loading.set(true);
+                spawn_local(async move {
+                    match SPELEO_DB_CONTROLLER
+                        .fetch_project_commits(project_id, 1)
+                        .await
+                    {
+                        Ok(page) => {
+                            next_page.set(page.has_next().then_some(2));
+                            commits.set(page.results);
+                            load_error.set(None);
+                        }
+                        Err(e) => load_error.set(Some(e.to_string())),
+                    }
+                    loading.set(false);
+                });
+            },
+        );
+    }
+
+    let on_load_more = {
+        let project_id = *project_id;
+        let commits = commits.clone();
+        let next_page = next_page.clone();
+        let loading = loading.clone();
+        let load_error = load_error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(page_number) = *next_page else {
+                return;
+            };
+            let commits = commits.clone();
+            let next_page = next_page.clone();
+            let loading = loading.clone();
+            let load_error = load_error.clone();
+            loading.set(true);
+            spawn_local(async move {
+                match SPELEO_DB_CONTROLLER
+                    .fetch_project_commits(project_id, page_number)
+                    .await
+                {
+                    Ok(page) => {
+                        next_page.set(page.has_next().then_some(page_number + 1));
+                        let mut all = (*commits).clone();
+                        all.extend(page.results);
+                        commits.set(all);
+                        load_error.set(None);
+                    }
+                    Err(e) => load_error.set(Some(e.to_string())),
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    let on_open_revision = {
+        let project_id = *project_id;
+        let opening = opening.clone();
+        let load_error = load_error.clone();
+        Callback::from(move |commit_id: String| {
+            if opening.is_some() {
+                return;
+            }
+            let opening = opening.clone();
+            let load_error = load_error.clone();
+            opening.set(Some(commit_id.clone()));
+            spawn_local(async move {
+                if let Err(e) = SPELEO_DB_CONTROLLER
+                    .open_project_revision(project_id, &commit_id)
+                    .await
+                {
+                    load_error.set(Some(format!("Failed to open revision: {e}")));
+                }
+                opening.set(None);
+            });
+        })
+    };
+
+    html! {
+        <div style="margin-top: 24px; padding-top: 24px; border-top: 1px solid #e5e7eb;">
+            <h3 style="margin-bottom: 12px;">{"History"}</h3>
+            {
+                if commits.is_empty() && !*loading && load_error.is_none() {
+                    html! {
+                        <p style="color: #6b7280; font-size: 14px;">{"No commits yet."}</p>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <ul style="list-style: none; margin: 0; padding: 0;">
+                { for commits.iter().map(|commit| {
+                    let is_opening = opening.as_deref() == Some(commit.id.as_str());
+                    let on_open = {
+                        let on_open_revision = on_open_revision.clone();
+                        let commit_id = commit.id.clone();
+                        Callback::from(move |_: MouseEvent| on_open_revision.emit(commit_id.clone()))
+                    };
+                    let is_latest = latest_commit_id.as_deref() == Some(commit.id.as_str());
+                    let revert_controls = if !*can_revert || is_latest {
+                        html! {}
+                    } else if confirm_revert.as_deref() == Some(commit.id.as_str()) {
+                        let on_confirm = {
+                            let on_revert = on_revert.clone();
+                            let confirm_revert = confirm_revert.clone();
+                            let commit_id = commit.id.clone();
+                            Callback::from(move |_: MouseEvent| {
+                                confirm_revert.set(None);
+                                on_revert.emit(commit_id.clone());
+                            })
+                        };
+                        let on_cancel = {
+                            let confirm_revert = confirm_revert.clone();
+                            Callback::from(move |_: MouseEvent| confirm_revert.set(None))
+                        };
+                        html! {
+                            <>
+                                <button
+                                    onclick={on_confirm}
+                                    title="Upload this revision as a new commit"
+                                    style="background-color: #dc2626; color: white; border: none; padding: 4px 10px; border-radius: 4px; cursor: pointer; font-size: 12px;"
+                                >
+                                    {"Confirm revert"}
+                                </button>
+                                <button
+                                    onclick={on_cancel}
+                                    style="background-color: white; color: #374151; border: 1px solid #d1d5db; padding: 4px 10px; border-radius: 4px; cursor: pointer; font-size: 12px;"
+                                >
+                                    {"Cancel"}
+                                </button>
+                            </>
+                        }
+                    } else {
+                        let on_ask = {
+                            let confirm_revert = confirm_revert.clone();
+                            let commit_id = commit.id.clone();
+                            Callback::from(move |_: MouseEvent| confirm_revert.set(Some(commit_id.clone())))
+                        };
+                        html! {
+                            <button
+                                onclick={on_ask}
+                                title="Make this revision the latest one again"
+                                style="background-color: white; color: #dc2626; border: 1px solid #fca5a5; padding: 4px 10px; border-radius: 4px; cursor: pointer; font-size: 12px;"
+                            >
+                                {"Revert"}
+                            </button>
+                        }
+                    };
+                    html! {
+                        <li style="display: flex; align-items: center; gap: 12px; padding: 8px 0; border-bottom: 1px solid #f3f4f6;">
+                            <div style="flex: 1;">
+                                <div style="font-size: 14px; color: #1f2937;">{&commit.message}</div>
+                                <div style="font-size: 12px; color: #6b7280; margin-top: 2px;">
+                                    {format!("{} · {} · ", commit.author_name, display_commit_time(commit))}
+                                    <code title={commit.id.clone()}>{short_commit_id(&commit.id)}</code>
+                                </div>
+                            </div>
+                            <button
+                                onclick={on_open}
+                                disabled={opening.is_some()}
+                                title="Open this revision read-only, without touching your working copy"
+                                style="background-color: white; color: #374151; border: 1px solid #d1d5db; padding: 4px 10px; border-radius: 4px; cursor: pointer; font-size: 12px;"
+                            >
+                                {if is_opening { "Opening..." } else { "Open read-only" }}
+                            </button>
+                            {revert_controls}
+                        </li>
+                    }
+                }) }
+            </ul>
+            {
+                if let Some(err) = &*load_error {
+                    html! {
+                        <p style="margin-top: 8px; color: #dc2626; font-size: 14px;">
+                            {format!("Failed to load history: {err}")}
+                        </p>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            {
+                if *loading {
+                    html! {
+                        <p style="margin-top: 8px; color: #6b7280; font-size: 14px;">{"Loading history..."}</p>
+                    }
+                } else if next_page.is_some() {
+                    html! {
+                        <button
+                            onclick={on_load_more}
+                            style="margin-top: 8px; background-color: white; color: #374151; border: 1px solid #d1d5db; padding: 6px 12px; border-radius: 4px; cursor: pointer;"
+                        >
+                            {"Load older commits"}
+                        </button>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
+
+fn mutex_warning_message(warning: &MutexWarning) -> String {
+    match &warning.taken_by {
+        Some(user) => format!(
+            "{user} has taken the lock on this project. Saving is disabled; go back and \
+             reopen the project once they are done."
+        ),
+        None => "The lock on this project has expired or was released. Saving is disabled; \
+                 go back and reopen the project to lock it again."
+            .to_string(),
     }
-    normalize_commit_relative_time(&commit.dt_since)
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -319,7 +751,12 @@ fn save_completion_state(upload_result: ProjectSaveResult) -> SaveCompletionStat
     }
 }
 
-fn render_processing_overlay(title: &str, message: &str) -> Html {
+fn render_processing_overlay(
+    title: &str,
+    message: &str,
+    progress_percent: Option<u8>,
+    on_cancel: Option<Callback<MouseEvent>>,
+) -> Html {
     html! {
         <div class="modal" style="
             position: fixed;
@@ -358,6 +795,39 @@ fn render_processing_overlay(title: &str, message: &str) -> Html {
                     animation: spin 1s linear infinite;
                     margin: 0 auto;
                 " />
+                {
+                    if let Some(percent) = progress_percent {
+                        html! {
+                            <div style="
+                                height: 6px;
+                                background-color: #e5e7eb;
+                                border-radius: 3px;
+                                overflow: hidden;
+                                margin-top: 20px;
+                            ">
+                                <div style={format!(
+                                    "height: 100%; width: {percent}%; background-color: #3b82f6; transition: width 0.2s;"
+                                )} />
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if let Some(on_cancel) = on_cancel {
+                        html! {
+                            <button
+                                onclick={on_cancel}
+                                style="margin-top: 20px; background-color: white; color: #374151; border: 1px solid #d1d5db; padding: 8px 16px; border-radius: 4px; cursor: pointer;"
+                            >
+                                {"Cancel"}
+                            </button>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
             </div>
         </div>
     }
@@ -370,6 +840,9 @@ pub fn project_details(
         user_email,
         compass_open,
         project_downloading,
+        project_download_progress,
+        project_upload_progress,
+        mutex_warning,
     }: &ProjectDetailsProps,
 ) -> Html {
     let is_dirty = project.is_dirty();
@@ -383,6 +856,7 @@ pub fn project_details(
     let show_no_changes_modal = use_state(|| false);
     let show_empty_project_modal = use_state(|| false);
     let show_discard_confirm_modal = use_state(|| false);
+    let show_edit_modal = use_state(|| false);
     let reimport_flow_state = use_state(cancel_reimport_flow);
     let reimport_message = use_state(String::new);
     let reimport_message_error = use_state(|| false);
@@ -395,7 +869,7 @@ pub fn project_details(
         && project.active_mutex().as_ref().unwrap().user == *user_email;
     let is_readonly = project.active_mutex().is_some()
         && &project.active_mutex().as_ref().unwrap().user != user_email
-        || project.permission() == "READ_ONLY";
+        || !project.permission().allows_editing();
     let busy = *uploading || *discarding || *reimporting || downloading;
     let disable_project_action_buttons = should_disable_project_action_buttons(*compass_open, busy);
     let download_complete = use_state(|| false);
@@ -525,6 +999,39 @@ pub fn project_details(
         })
     };
 
+    // Revert Handler: uploads an older revision as a new commit
+    let on_revert = {
+        let uploading = uploading.clone();
+        let show_upload_success = show_upload_success.clone();
+        let show_no_changes_modal = show_no_changes_modal.clone();
+        let error_message = error_message.clone();
+        Callback::from(move |commit_id: String| {
+            info!("Reverting project to revision {commit_id}");
+            let uploading = uploading.clone();
+            let show_upload_success = show_upload_success.clone();
+            let show_no_changes_modal = show_no_changes_modal.clone();
+            let error_message = error_message.clone();
+            uploading.set(true);
+            error_message.set(None);
+            spawn_local(async move {
+                match SPELEO_DB_CONTROLLER.revert_project(&commit_id).await {
+                    Ok(upload_result) => {
+                        let completion = save_completion_state(upload_result);
+                        show_upload_success.set(completion.show_upload_success);
+                        show_no_changes_modal.set(completion.show_no_changes_modal);
+                    }
+                    Err(Error::Cancelled) => {
+                        info!("Revert to revision {commit_id} cancelled");
+                    }
+                    Err(e) => {
+                        error_message.set(Some(format!("Failed to revert project: {}", e)));
+                    }
+                }
+                uploading.set(false);
+            });
+        })
+    };
+
     // Save Project Handler
     let on_save = {
         let project_id = project.id();
@@ -573,8 +1080,11 @@ pub fn project_details(
                         show_upload_success.set(completion.show_upload_success);
                         show_no_changes_modal.set(completion.show_no_changes_modal);
                     }
+                    Err(Error::Cancelled) => {
+                        info!("Save of project {project_id} cancelled");
+                    }
                     Err(e) => {
-                        upload_error.set(Some(format!("Failed to zip project: {}", e)));
+                        upload_error.set(Some(format!("Failed to save project: {}", e)));
                         uploading.set(false);
                         return;
                     }
@@ -770,8 +1280,51 @@ pub fn project_details(
                 html! {}
             }}
 
-            <h2><strong>{"Project: "}</strong>{&project.name()}</h2>
+            <div style="display: flex; align-items: center; justify-content: space-between; gap: 12px;">
+                <h2><strong>{"Project: "}</strong>{&project.name()}</h2>
+                {if project.can_edit_metadata() {
+                    let show_edit_modal = show_edit_modal.clone();
+                    html! {
+                        <button
+                            onclick={Callback::from(move |_| show_edit_modal.set(true))}
+                            disabled={busy}
+                            style="padding: 6px 12px; border: 1px solid #d1d5db; border-radius: 6px; background-color: white; color: #374151; cursor: pointer;"
+                        >
+                            {"Edit details"}
+                        </button>
+                    }
+                } else {
+                    html! {}
+                }}
+            </div>
+            {if *show_edit_modal {
+                let show_edit_modal = show_edit_modal.clone();
+                html! {
+                    <EditProjectModal
+                        project_id={project.id()}
+                        metadata={project.metadata()}
+                        on_close={Callback::from(move |_: ()| show_edit_modal.set(false))}
+                    />
+                }
+            } else {
+                html! {}
+            }}
             <p style="color: #6b7280; font-size: 14px;">{format!("ID: {}", project.id())}</p>
+            {
+                if let Some(warning) = mutex_warning.as_ref().filter(|w| w.project_id == project.id()) {
+                    html! {
+                        <div style={format!(
+                            "padding: 10px 14px; margin: 8px 0; border-radius: 6px; \
+                             background-color: #fef2f2; border-left: 3px solid {COLOR_ALARM}; \
+                             color: #991b1b; font-size: 14px;"
+                        )}>
+                            {mutex_warning_message(warning)}
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
 
             // Project status indicator
             <div style={format!(
@@ -839,8 +1392,28 @@ pub fn project_details(
                                 margin: 0 auto 16px;
                             " />
                             <p style="color: #4b5563; font-size: 16px;">
-                                {"Downloading and extracting project..."}
+                                {project_download_message(*project_download_progress)}
                             </p>
+                            {
+                                if let Some(percent) = project_download_progress {
+                                    html! {
+                                        <div style="
+                                            height: 6px;
+                                            background-color: #e5e7eb;
+                                            border-radius: 3px;
+                                            overflow: hidden;
+                                            max-width: 320px;
+                                            margin: 0 auto;
+                                        ">
+                                            <div style={format!(
+                                                "height: 100%; width: {percent}%; background-color: #3b82f6; transition: width 0.2s;"
+                                            )} />
+                                        </div>
+                                    }
+                                } else {
+                                    html! {}
+                                }
+                            }
                         </div>
                     }
                 } else if let Some(err) = &*error_message {
@@ -951,6 +1524,13 @@ pub fn project_details(
                     html!{<></>}
                 }
             }
+            <ProjectAccess project_id={project.id()} />
+            <CommitHistory
+                project_id={project.id()}
+                latest_commit_id={project.latest_commit().map(|commit| commit.id.clone())}
+                can_revert={locked_by_user && !is_readonly && !is_dirty && !disable_project_action_buttons}
+                on_revert={on_revert}
+            />
             {
             if *show_readonly_modal {
                 return html! {
@@ -1238,10 +1818,24 @@ pub fn project_details(
                     Some(ProcessingOverlayKind::Importing) => render_processing_overlay(
                         "Importing Project",
                         "Processing Compass files and syncing the project. This can take a moment.",
+                        None,
+                        None,
                     ),
                     Some(ProcessingOverlayKind::Saving) => render_processing_overlay(
                         "Saving Project",
-                        "Uploading your Compass project to SpeleoDB. This can take a moment.",
+                        &project_upload_message(*project_upload_progress),
+                        *project_upload_progress,
+                        // Once the whole body is sent the server may already be
+                        // committing, so cancelling is no longer offered.
+                        (*project_upload_progress != Some(100)).then(|| {
+                            Callback::from(|_: MouseEvent| {
+                                spawn_local(async {
+                                    if let Err(e) = SPELEO_DB_CONTROLLER.cancel_save_project().await {
+                                        error!("Failed to cancel project save: {e}");
+                                    }
+                                });
+                            })
+                        }),
                     ),
                     None => html! {},
                 }
@@ -1,5 +1,6 @@
 use crate::components::create_project_modal::CreateProjectModal;
 use crate::components::project_listing_item::ProjectListingItem;
+use chrono::{DateTime, Utc};
 use common::ui_state::{ProjectStatus, UiState};
 use std::cmp::Ordering;
 use yew::prelude::*;
@@ -24,11 +25,9 @@ fn cmp_project_name(a: &str, b: &str) -> Ordering {
     a.to_lowercase().cmp(&b.to_lowercase())
 }
 
-/// Descending comparator over ISO-8601 `modified_date` strings. Lexical
-/// order matches chronological order for fixed-width ISO-8601 timestamps,
-/// which is the format the backend emits (see `state.rs`).
-fn cmp_modified_date_desc(a: &str, b: &str) -> Ordering {
-    b.cmp(a)
+/// Descending comparator over `modified_date`, most recent first.
+fn cmp_modified_date_desc(a: DateTime<Utc>, b: DateTime<Utc>) -> Ordering {
+    b.cmp(&a)
 }
 
 fn sort_projects(mode: SortMode, projects: &mut [ProjectStatus]) {
@@ -184,15 +183,20 @@ mod tests {
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), test)]
     fn modified_date_sort_is_descending() {
-        let newer = "2026-04-27T10:00:00Z";
-        let older = "2026-04-20T10:00:00Z";
+        // Chronological, not lexical: the newer one sorts first as a string.
+        let newer = timestamp("2026-04-20T23:00:00-05:00");
+        let older = timestamp("2026-04-21T01:00:00Z");
         assert_eq!(cmp_modified_date_desc(newer, older), Ordering::Less);
         assert_eq!(cmp_modified_date_desc(older, newer), Ordering::Greater);
         assert_eq!(cmp_modified_date_desc(newer, newer), Ordering::Equal);
     }
 
+    fn timestamp(timestamp: &str) -> DateTime<Utc> {
+        common::server_time::parse(timestamp).unwrap()
+    }
+
     fn make_project(name: &str, modified: &str) -> ProjectStatus {
-        use common::api_types::{ProjectInfo, ProjectType};
+        use common::api_types::{Permission, ProjectInfo, ProjectType, Visibility};
         use common::ui_state::LocalProjectStatus;
         use uuid::Uuid;
         ProjectStatus::new(
@@ -202,16 +206,16 @@ mod tests {
                 name: name.to_string(),
                 description: String::new(),
                 is_active: true,
-                permission: "READ_AND_WRITE".to_string(),
+                permission: Permission::ReadAndWrite,
                 active_mutex: None,
                 country: "US".to_string(),
                 created_by: "tester".to_string(),
-                creation_date: modified.to_string(),
-                modified_date: modified.to_string(),
+                creation_date: timestamp(modified),
+                modified_date: timestamp(modified),
                 latitude: None,
                 longitude: None,
                 fork_from: None,
-                visibility: "PUBLIC".to_string(),
+                visibility: Visibility::Public,
                 exclude_geojson: false,
                 latest_commit: None,
                 project_type: ProjectType::Compass,
@@ -259,13 +263,13 @@ mod tests {
             make_project("ALPHA", "2026-04-27T00:00:00Z"),
         ];
         sort_projects(SortMode::Name, &mut projects);
-        let order: Vec<&str> = projects.iter().map(|p| p.modified_date()).collect();
+        let order: Vec<DateTime<Utc>> = projects.iter().map(|p| p.modified_date()).collect();
         assert_eq!(
             order,
             vec![
-                "2026-04-01T00:00:00Z",
-                "2026-04-15T00:00:00Z",
-                "2026-04-27T00:00:00Z",
+                timestamp("2026-04-01T00:00:00Z"),
+                timestamp("2026-04-15T00:00:00Z"),
+                timestamp("2026-04-27T00:00:00Z"),
             ],
             "case-equal names should retain their incoming order"
         );
@@ -1,4 +1,4 @@
-use common::ui_state::ProjectStatus;
+use common::ui_state::{Permission, ProjectStatus};
 use wasm_bindgen_futures::spawn_local;
 use yew::{Callback, Html, Properties, classes, function_component, html};
 use yew_icons::{Icon, IconData};
@@ -40,8 +40,8 @@ pub fn project_listing_item_layout(
     });
     let project_permission = project.permission();
     let permission_color = match project_permission {
-        "ADMIN" => color_warn,
-        "READ_AND_WRITE" => color_blue,
+        Permission::Admin => color_warn,
+        Permission::ReadAndWrite => color_blue,
         _ => color_grey,
     };
     let lock_color;
@@ -105,7 +105,7 @@ pub fn project_listing_item_layout(
                 <span style={format!("padding: 4px 8px; border-radius: 4px; background-color: {}; color: white; font-size: 12px; font-weight: bold;",
                     permission_color
                 )}>
-                    { project_permission }
+                    { project_permission.as_str() }
                 </span>
                 <span style={format!("padding: 4px 8px; border-radius: 4px; background-color: {}; color: white; font-size: 12px; font-weight: bold;", lock_color)}>
                     { lock_status }
@@ -6,6 +6,9 @@ pub enum Error {
     Command(String),
     #[error("Failed to parse JSON: {0}")]
     Serde(String),
+    /// The backend aborted the command because the user cancelled it.
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 impl From<serde_wasm_bindgen::Error> for Error {
@@ -23,6 +26,9 @@ impl From<JsValue> for Error {
         }
 
         let backend_error = serde_wasm_bindgen::from_value::<common::Error>(e.clone());
+        if let Ok(common::Error::Cancelled) = backend_error {
+            return Self::Cancelled;
+        }
         if let Ok(error) = backend_error {
             return Self::Command(format_backend_error(&error));
         }
@@ -1,5 +1,7 @@
 // WASM controller now delegates network calls to native Tauri backend.
 use crate::{Error, invoke};
+use common::OauthToken;
+use common::api_types::{CommitInfo, InstanceProbe, Page, ProjectMetadata, ProjectPermissions};
 use common::ui_state::ProjectSaveResult;
 use log::{error, info};
 use once_cell::sync::Lazy;
@@ -51,6 +53,16 @@ impl SpeleoDBController {
             .map_err(|e| e.to_string())
     }
 
+    /// Check `instance` before signing in to it.
+    pub async fn probe_instance(&self, instance: &Url) -> Result<InstanceProbe, Error> {
+        #[derive(Serialize)]
+        struct Args<'a> {
+            instance: &'a Url,
+        }
+
+        invoke("probe_instance", &Args { instance }).await
+    }
+
     pub async fn authenticate(
         &self,
         email: Option<&str>,
@@ -60,9 +72,9 @@ impl SpeleoDBController {
     ) -> Result<(), String> {
         // Validate instance URL
 
-        info!("token: {oauth:?}");
         // Validation: either oauth token (40 hex) OR email+password
-        let oauth_ok = oauth.is_some_and(validate_oauth);
+        let oauth = oauth.and_then(|oauth| OauthToken::new(oauth).ok());
+        let oauth_ok = oauth.is_some();
         let pass_ok = email.is_some_and(|email| {
             password.is_some_and(|password| validate_email_password(email, password))
         });
@@ -77,7 +89,7 @@ impl SpeleoDBController {
         struct NativeArgs<'a> {
             email: Option<&'a str>,
             password: Option<&'a str>,
-            oauth: Option<&'a str>,
+            oauth: Option<OauthToken>,
             instance: &'a Url,
         }
 
@@ -110,7 +122,7 @@ impl SpeleoDBController {
         &self,
         project_id: Uuid,
         commit_message: &str,
-    ) -> Result<ProjectSaveResult, String> {
+    ) -> Result<ProjectSaveResult, Error> {
         #[derive(Serialize)]
         #[serde(rename_all = "camelCase")]
         struct Args<'a> {
@@ -122,10 +134,101 @@ impl SpeleoDBController {
             commit_message,
         };
 
-        let result: ProjectSaveResult = invoke("save_project", &args)
-            .await
-            .map_err(|e| e.to_string())?;
-        Ok(result)
+        invoke("save_project", &args).await
+    }
+
+    /// Upload the revision recorded by `commit_id` as a new commit of the
+    /// active project. Cancellable with `cancel_save_project`.
+    pub async fn revert_project(&self, commit_id: &str) -> Result<ProjectSaveResult, Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Args<'a> {
+            commit_id: &'a str,
+        }
+
+        invoke("revert_project", &Args { commit_id }).await
+    }
+
+    /// Abort an in-flight `save_project` upload. The pending `save_project`
+    /// call then resolves with `Error::Cancelled`, though the server may
+    /// still commit a ZIP that was already sent in full.
+    pub async fn cancel_save_project(&self) -> Result<bool, Error> {
+        invoke("cancel_save_project", &()).await
+    }
+
+    /// Check out `commit_id` into a read-only folder and open it.
+    pub async fn open_project_revision(
+        &self,
+        project_id: Uuid,
+        commit_id: &str,
+    ) -> Result<(), Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Args<'a> {
+            project_id: Uuid,
+            commit_id: &'a str,
+        }
+
+        invoke(
+            "open_project_revision",
+            &Args {
+                project_id,
+                commit_id,
+            },
+        )
+        .await
+    }
+
+    pub async fn fetch_project_commits(
+        &self,
+        project_id: Uuid,
+        page: u32,
+    ) -> Result<Page<CommitInfo>, Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Args {
+            project_id: Uuid,
+            page: u32,
+        }
+
+        invoke("fetch_project_commits", &Args { project_id, page }).await
+    }
+
+    /// Users and teams with access to `project_id`, with their levels.
+    pub async fn fetch_project_permissions(
+        &self,
+        project_id: Uuid,
+    ) -> Result<ProjectPermissions, Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Args {
+            project_id: Uuid,
+        }
+
+        invoke("fetch_project_permissions", &Args { project_id }).await
+    }
+
+    /// Replace the name, description and location of `project_id`.
+    pub async fn update_project(
+        &self,
+        project_id: Uuid,
+        metadata: &ProjectMetadata,
+    ) -> Result<(), Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Args<'a> {
+            project_id: Uuid,
+            metadata: &'a ProjectMetadata,
+        }
+
+        invoke(
+            "update_project",
+            &Args {
+                project_id,
+                metadata,
+            },
+        )
+        .await
     }
 
     pub async fn discard_changes(&self) -> Result<(), String> {
@@ -172,6 +275,20 @@ impl SpeleoDBController {
         Ok(())
     }
 
+    /// Switch to another saved profile, or with `None` to the sign-in screen
+    /// to add an account.
+    pub async fn switch_profile(&self, profile_id: Option<Uuid>) -> Result<(), String> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Args {
+            profile_id: Option<Uuid>,
+        }
+
+        invoke::<_, ()>("switch_profile", &Args { profile_id })
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     pub async fn clear_active_project(&self) -> Result<(), String> {
         let _: () = invoke("clear_active_project", &()).await.unwrap();
         Ok(())
@@ -211,11 +328,6 @@ impl SpeleoDBController {
 
 pub static SPELEO_DB_CONTROLLER: Lazy<SpeleoDBController> = Lazy::new(|| SpeleoDBController {});
 
-/// Validate an OAuth token: exactly 40 hex characters.
-pub fn validate_oauth(oauth: &str) -> bool {
-    oauth.len() == 40 && oauth.chars().all(|c| c.is_ascii_hexdigit())
-}
-
 /// Validate email+password: email must contain a single `@` and a `.` in the domain and password non-empty.
 pub fn validate_email_password(email: &str, password: &str) -> bool {
     if email.is_empty() || password.is_empty() {
@@ -229,56 +341,6 @@ pub fn validate_email_password(email: &str, password: &str) -> bool {
 mod tests {
     use super::*;
 
-    // OAuth validation tests
-    #[test]
-    fn oauth_valid_examples() {
-        assert!(validate_oauth("0123456789abcdef0123456789abcdef01234567"));
-        assert!(!validate_oauth(&"g".repeat(40)));
-        assert!(!validate_oauth("short"));
-    }
-
-    #[test]
-    fn oauth_uppercase_hex_valid() {
-        assert!(validate_oauth("0123456789ABCDEF0123456789ABCDEF01234567"));
-    }
-
-    #[test]
-    fn oauth_mixed_case_valid() {
-        assert!(validate_oauth("0123456789aBcDeF0123456789AbCdEf01234567"));
-    }
-
-    #[test]
-    fn oauth_invalid_length_too_short() {
-        assert!(!validate_oauth("0123456789abcdef"));
-    }
-
-    #[test]
-    fn oauth_invalid_length_too_long() {
-        assert!(!validate_oauth("0123456789abcdef0123456789abcdef012345678"));
-    }
-
-    #[test]
-    fn oauth_empty_string() {
-        assert!(!validate_oauth(""));
-    }
-
-    #[test]
-    fn oauth_non_hex_chars() {
-        assert!(!validate_oauth("0123456789abcdef0123456789abcdef0123456g"));
-        assert!(!validate_oauth("0123456789abcdef0123456789abcdef0123456 "));
-        assert!(!validate_oauth("0123456789abcdef0123456789abcdef0123456-"));
-    }
-
-    #[test]
-    fn oauth_all_zeros() {
-        assert!(validate_oauth("0000000000000000000000000000000000000000"));
-    }
-
-    #[test]
-    fn oauth_all_fs() {
-        assert!(validate_oauth("ffffffffffffffffffffffffffffffffffffffff"));
-    }
-
     // Email/password validation tests
     #[test]
     fn email_password_validation() {
@@ -1,22 +1,81 @@
-use crate::API_BASE_URL;
+use crate::{API_BASE_URL, Error};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use url::Url;
 
-// TODO:: Add newtype to encapsulate Oauth token validaiton
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+const OAUTH_TOKEN_LEN: usize = 40;
+
+/// A SpeleoDB API token: exactly 40 hexadecimal characters.
+///
+/// Only constructible through [`OauthToken::new`] (or deserialization, which
+/// validates the same way). `Debug` and `Display` never print the token, so
+/// it can't end up in logs by accident; read it with `as_ref()` where it
+/// has to go on the wire.
+#[derive(Clone, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct OauthToken(String);
 
+impl OauthToken {
+    pub fn new(token: impl Into<String>) -> Result<Self, Error> {
+        let token = token.into();
+        if token.len() == OAUTH_TOKEN_LEN && token.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(Self(token))
+        } else {
+            Err(Error::InvalidOauthToken)
+        }
+    }
+}
+
 impl AsRef<str> for OauthToken {
     fn as_ref(&self) -> &str {
         &self.0
     }
 }
 
+impl TryFrom<String> for OauthToken {
+    type Error = Error;
+
+    fn try_from(token: String) -> Result<Self, Self::Error> {
+        Self::new(token)
+    }
+}
+
+impl From<OauthToken> for String {
+    fn from(token: OauthToken) -> Self {
+        token.0
+    }
+}
+
+impl fmt::Debug for OauthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OauthToken(<redacted>)")
+    }
+}
+
+impl fmt::Display for OauthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+/// What an instance reported about itself when it was probed: its version
+/// and the optional features it has turned on. Servers that predate the
+/// `server/info/` endpoint report neither.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ServerCapabilities {
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ApiInfo {
     instance: Url,
     email: Option<String>,
-    oauth_token: Option<String>,
+    oauth_token: Option<OauthToken>,
+    #[serde(default)]
+    capabilities: ServerCapabilities,
 }
 
 impl Default for ApiInfo {
@@ -25,16 +84,18 @@ impl Default for ApiInfo {
             instance: API_BASE_URL.parse().unwrap(),
             email: None,
             oauth_token: None,
+            capabilities: ServerCapabilities::default(),
         }
     }
 }
 
 impl ApiInfo {
-    pub fn new(instance: Url, email: Option<String>, oauth_token: Option<String>) -> Self {
+    pub fn new(instance: Url, email: Option<String>, oauth_token: Option<OauthToken>) -> Self {
         Self {
             instance,
             email,
             oauth_token,
+            capabilities: ServerCapabilities::default(),
         }
     }
 
@@ -46,7 +107,94 @@ impl ApiInfo {
         self.email.as_deref()
     }
 
-    pub fn oauth_token(&self) -> Option<&str> {
-        self.oauth_token.as_deref()
+    pub fn oauth_token(&self) -> Option<&OauthToken> {
+        self.oauth_token.as_ref()
+    }
+
+    pub fn with_oauth_token(mut self, oauth_token: Option<OauthToken>) -> Self {
+        self.oauth_token = oauth_token;
+        self
+    }
+
+    /// Version and optional features the instance reported at sign-in.
+    pub fn capabilities(&self) -> &ServerCapabilities {
+        &self.capabilities
+    }
+
+    pub fn with_capabilities(mut self, capabilities: ServerCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    #[test]
+    fn oauth_token_accepts_40_hex_characters_of_any_case() {
+        for token in [
+            TOKEN,
+            "0123456789ABCDEF0123456789ABCDEF01234567",
+            "0123456789aBcDeF0123456789AbCdEf01234567",
+            "0000000000000000000000000000000000000000",
+            "ffffffffffffffffffffffffffffffffffffffff",
+        ] {
+            assert_eq!(OauthToken::new(token).unwrap().as_ref(), token);
+        }
+    }
+
+    #[test]
+    fn oauth_token_rejects_wrong_length_or_non_hex() {
+        for token in [
+            "",
+            "short",
+            "0123456789abcdef",
+            "0123456789abcdef0123456789abcdef012345678",
+            "0123456789abcdef0123456789abcdef0123456g",
+            "0123456789abcdef0123456789abcdef0123456 ",
+            "0123456789abcdef0123456789abcdef0123456-",
+            &"g".repeat(40),
+        ] {
+            assert_eq!(OauthToken::new(token), Err(Error::InvalidOauthToken));
+        }
+    }
+
+    #[test]
+    fn oauth_token_is_redacted_when_formatted() {
+        let api_info = ApiInfo::new(
+            API_BASE_URL.parse().unwrap(),
+            None,
+            Some(OauthToken::new(TOKEN).unwrap()),
+        );
+        assert!(!format!("{api_info:?}").contains(TOKEN));
+        assert!(!api_info.oauth_token().unwrap().to_string().contains(TOKEN));
+    }
+
+    #[test]
+    fn oauth_token_serde_round_trips_and_validates() {
+        let token = OauthToken::new(TOKEN).unwrap();
+        let json = serde_json::to_string(&token).unwrap();
+        assert_eq!(json, format!("\"{TOKEN}\""));
+        assert_eq!(serde_json::from_str::<OauthToken>(&json).unwrap(), token);
+        assert!(serde_json::from_str::<OauthToken>("\"not-a-token\"").is_err());
+    }
+
+    #[test]
+    fn api_info_without_capabilities_deserializes() {
+        let json = format!(r#"{{"instance":"{API_BASE_URL}","email":null,"oauth_token":null}}"#);
+        let api_info: ApiInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(api_info.capabilities(), &ServerCapabilities::default());
+
+        let capabilities = ServerCapabilities {
+            version: Some("2.4.0".to_string()),
+            features: vec!["project_history".to_string()],
+        };
+        let api_info = api_info.with_capabilities(capabilities.clone());
+        let json = serde_json::to_string(&api_info).unwrap();
+        let api_info: ApiInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(api_info.capabilities(), &capabilities);
     }
 }
@@ -1,11 +1,16 @@
+use crate::{ServerCapabilities, server_time};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ActiveMutex {
     pub user: String,
-    pub creation_date: String,
-    pub modified_date: String,
+    #[serde(with = "server_time")]
+    pub creation_date: DateTime<Utc>,
+    #[serde(with = "server_time")]
+    pub modified_date: DateTime<Utc>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -14,38 +19,229 @@ pub struct ProjectInfo {
     pub name: String,
     pub description: String,
     pub is_active: bool,
-    pub permission: String,
+    pub permission: Permission,
     pub active_mutex: Option<ActiveMutex>,
     pub country: String,
     pub created_by: String,
-    pub creation_date: String,
-    pub modified_date: String,
+    #[serde(with = "server_time")]
+    pub creation_date: DateTime<Utc>,
+    #[serde(with = "server_time")]
+    pub modified_date: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub latitude: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub longitude: Option<f64>,
     pub fork_from: Option<String>,
-    pub visibility: String,
+    pub visibility: Visibility,
     pub exclude_geojson: bool,
     pub latest_commit: Option<CommitInfo>,
     #[serde(rename = "type")]
     pub project_type: ProjectType,
 }
 
+impl ProjectInfo {
+    /// Only project admins may change name, description and location.
+    pub fn can_edit_metadata(&self) -> bool {
+        self.permission == Permission::Admin
+    }
+
+    pub fn metadata(&self) -> ProjectMetadata {
+        ProjectMetadata {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            country: self.country.clone(),
+            latitude: self.latitude.map(|lat| lat.to_string()),
+            longitude: self.longitude.map(|lon| lon.to_string()),
+        }
+    }
+}
+
+/// The user-editable fields of a project. Coordinates are kept as entered;
+/// `None` (or an empty string) clears them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectMetadata {
+    pub name: String,
+    pub description: String,
+    pub country: String,
+    pub latitude: Option<String>,
+    pub longitude: Option<String>,
+}
+
+/// Access level on a project. Levels this client doesn't know are kept
+/// as `Unknown` and grant nothing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum Permission {
+    WebViewer,
+    ReadOnly,
+    ReadAndWrite,
+    Admin,
+    Unknown(String),
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::WebViewer => "WEB_VIEWER",
+            Self::ReadOnly => "READ_ONLY",
+            Self::ReadAndWrite => "READ_AND_WRITE",
+            Self::Admin => "ADMIN",
+            Self::Unknown(level) => level,
+        }
+    }
+
+    /// Whether the holder may lock the project and upload changes.
+    pub fn allows_editing(&self) -> bool {
+        matches!(self, Self::Admin | Self::ReadAndWrite)
+    }
+}
+
+impl From<String> for Permission {
+    fn from(level: String) -> Self {
+        match level.as_str() {
+            "WEB_VIEWER" => Self::WebViewer,
+            "READ_ONLY" => Self::ReadOnly,
+            "READ_AND_WRITE" => Self::ReadAndWrite,
+            "ADMIN" => Self::Admin,
+            _ => Self::Unknown(level),
+        }
+    }
+}
+
+impl From<Permission> for String {
+    fn from(permission: Permission) -> Self {
+        permission.as_str().to_string()
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who can see a project on the SpeleoDB website.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum Visibility {
+    Private,
+    Public,
+    Unknown(String),
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Private => "PRIVATE",
+            Self::Public => "PUBLIC",
+            Self::Unknown(visibility) => visibility,
+        }
+    }
+}
+
+impl From<String> for Visibility {
+    fn from(visibility: String) -> Self {
+        match visibility.as_str() {
+            "PRIVATE" => Self::Private,
+            "PUBLIC" => Self::Public,
+            _ => Self::Unknown(visibility),
+        }
+    }
+}
+
+impl From<Visibility> for String {
+    fn from(visibility: Visibility) -> Self {
+        visibility.as_str().to_string()
+    }
+}
+
+/// Access of one user to a project.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserPermission {
+    /// Email of the user.
+    pub user: String,
+    pub level: Permission,
+}
+
+/// Access a team grants to all of its members.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TeamPermission {
+    /// Name of the team.
+    pub team: String,
+    pub level: Permission,
+}
+
+/// Everyone with access to a project, directly or through a team.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProjectPermissions {
+    pub users: Vec<UserPermission>,
+    pub teams: Vec<TeamPermission>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct CommitInfo {
     pub id: String,
     pub message: String,
     pub author_name: String,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub commit_date: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "server_time::option"
+    )]
+    pub commit_date: Option<DateTime<Utc>>,
     pub dt_since: String,
+    /// Files in the project at this commit. Lets the sidecar tell what a
+    /// remote revision contains without downloading its ZIP. Not sent to the
+    /// UI, which has no use for it.
     #[serde(default, skip_serializing)]
     pub tree: Vec<CommitTreeEntry>,
 }
 
+/// One entry of a commit's tree. Every field may be missing (directories
+/// come without a size or hash), so a partial entry never fails the commit
+/// or the project it belongs to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommitTreeEntry {
+    /// Path from the project root, `/`-separated.
+    #[serde(default)]
+    pub path: String,
+    /// Size in bytes.
+    #[serde(default)]
+    pub size: u64,
+    /// Hash of the file contents, as reported by the server.
+    #[serde(default)]
+    pub hash: String,
+}
+
+/// One page of a paginated listing (`count` / `next` / `previous` /
+/// `results`). `next` and `previous` are absolute URLs and are `None` at
+/// either end of the listing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub count: u64,
+    pub next: Option<String>,
+    pub previous: Option<String>,
+    pub results: Vec<T>,
+}
+
+impl<T> Page<T> {
+    pub fn has_next(&self) -> bool {
+        self.next.is_some()
+    }
+}
+
+/// Outcome of probing an instance URL before signing in to it.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct CommitTreeEntry {}
+pub enum InstanceProbe {
+    /// A SpeleoDB server answering the v2 API.
+    Available(ServerCapabilities),
+    /// Something answered, but not the SpeleoDB v2 API.
+    NotSpeleoDb,
+    /// The TLS handshake failed, e.g. an untrusted or expired certificate.
+    TlsError(String),
+    /// No answer: DNS, connection or timeout failure, or a server error.
+    Unreachable(String),
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ProjectSaveResult {
@@ -180,6 +376,88 @@ dt_since = "just now"
         );
     }
 
+    #[test]
+    fn commit_page_deserializes_last_page() {
+        let json = r#"{
+            "count": 26,
+            "next": null,
+            "previous": "https://www.speleodb.org/api/v2/projects/x/commits/?page=1",
+            "results": [{
+                "id": "abc123",
+                "message": "Initial commit",
+                "author_name": "Test User",
+                "dt_since": "2 days ago"
+            }]
+        }"#;
+
+        let page: Page<CommitInfo> =
+            serde_json::from_str(json).expect("commit page should deserialize");
+        assert_eq!(page.count, 26);
+        assert!(!page.has_next());
+        assert!(page.results[0].tree.is_empty());
+        assert_eq!(page.results[0].id, "abc123");
+    }
+
+    #[test]
+    fn commit_info_deserializes_tree_entries() {
+        let json = r#"{
+            "id": "abc123",
+            "message": "Add survey data",
+            "author_name": "Test User",
+            "dt_since": "just now",
+            "tree": [
+                {"path": "cave.mak", "size": 412, "hash": "0f4c1b5e"},
+                {"path": "data/cave.dat", "size": 90210, "hash": "9a7d22c3"}
+            ]
+        }"#;
+
+        let commit: CommitInfo =
+            serde_json::from_str(json).expect("commit with a tree should deserialize");
+        assert_eq!(
+            commit.tree[1],
+            CommitTreeEntry {
+                path: "data/cave.dat".to_string(),
+                size: 90210,
+                hash: "9a7d22c3".to_string(),
+            }
+        );
+        let serialized = serde_json::to_string(&commit).unwrap();
+        assert!(
+            !serialized.contains("tree"),
+            "the tree should not be sent to the UI"
+        );
+    }
+
+    #[test]
+    fn project_info_deserializes_partial_tree_entries() {
+        let json = project_json("COMPASS").replace(
+            r#""latest_commit": null"#,
+            r#""latest_commit": {
+                "id": "abc123",
+                "message": "Add survey data",
+                "author_name": "Test User",
+                "dt_since": "just now",
+                "tree": [
+                    {"path": "data"},
+                    {"path": "data/cave.dat", "size": 90210, "hash": "9a7d22c3"}
+                ]
+            }"#,
+        );
+
+        let info: ProjectInfo = serde_json::from_str(&json)
+            .expect("a partial tree entry must not fail the whole project");
+        let tree = &info.latest_commit.expect("latest commit").tree;
+        assert_eq!(
+            tree[0],
+            CommitTreeEntry {
+                path: "data".to_string(),
+                size: 0,
+                hash: String::new(),
+            }
+        );
+        assert_eq!(tree[1].size, 90210);
+    }
+
     #[test]
     fn project_type_deserializes_compass_as_supported() {
         let project: ProjectInfo = serde_json::from_str(&project_json("COMPASS"))
@@ -210,4 +488,62 @@ dt_since = "just now"
             serde_json::to_string(&ProjectType::Compass).expect("ProjectType should serialize");
         assert_eq!(serialized, "\"COMPASS\"");
     }
+
+    #[test]
+    fn only_admins_can_edit_metadata() {
+        let mut project: ProjectInfo =
+            serde_json::from_str(&project_json("COMPASS")).expect("project should deserialize");
+        assert!(project.can_edit_metadata());
+        for permission in [
+            Permission::ReadAndWrite,
+            Permission::ReadOnly,
+            Permission::WebViewer,
+        ] {
+            project.permission = permission.clone();
+            assert!(!project.can_edit_metadata(), "{permission} must not edit");
+        }
+    }
+
+    #[test]
+    fn only_admins_and_writers_can_edit() {
+        assert!(Permission::Admin.allows_editing());
+        assert!(Permission::ReadAndWrite.allows_editing());
+        assert!(!Permission::ReadOnly.allows_editing());
+        assert!(!Permission::WebViewer.allows_editing());
+        assert!(!Permission::from(String::new()).allows_editing());
+    }
+
+    #[test]
+    fn permission_and_visibility_keep_unknown_values() {
+        let mut project: ProjectInfo =
+            serde_json::from_str(&project_json("COMPASS")).expect("project should deserialize");
+        assert_eq!(project.permission, Permission::Admin);
+        assert_eq!(project.visibility, Visibility::Private);
+
+        project.permission = Permission::from("OWNER".to_string());
+        project.visibility = Visibility::from("UNLISTED".to_string());
+        let json = serde_json::to_value(&project).unwrap();
+        assert_eq!(json["permission"], "OWNER");
+        assert_eq!(json["visibility"], "UNLISTED");
+        let project: ProjectInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(project.permission, Permission::Unknown("OWNER".to_string()));
+        assert!(!project.permission.allows_editing());
+    }
+
+    #[test]
+    fn project_metadata_carries_coordinates_as_text() {
+        let mut project: ProjectInfo =
+            serde_json::from_str(&project_json("COMPASS")).expect("project should deserialize");
+        project.latitude = Some(45.1234);
+        assert_eq!(
+            project.metadata(),
+            ProjectMetadata {
+                name: "South Pole Cave".to_string(),
+                description: "South end of equator pond, Chaz".to_string(),
+                country: "US".to_string(),
+                latitude: Some("45.1234".to_string()),
+                longitude: None,
+            }
+        );
+    }
 }
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// Network settings of the HTTP client used for every SpeleoDB request.
+///
+/// Project ZIP downloads and uploads override `request_timeout_secs` with
+/// their own, size-dependent timeouts; the connect timeout applies to them too.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    /// HTTP(S) proxy all requests go through. When unset, the system proxy
+    /// configuration (`HTTPS_PROXY` and friends) is used.
+    pub proxy_url: Option<Url>,
+    /// PEM bundle of certificates trusted in addition to the system roots,
+    /// e.g. the internal CA of a self-hosted instance.
+    pub root_certificates_pem: Option<String>,
+    pub connect_timeout_secs: u64,
+    pub request_timeout_secs: u64,
+    /// How many times an idempotent request is repeated after a transient
+    /// failure. `0` disables retries; uploads are never retried.
+    pub max_retries: u32,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            root_certificates_pem: None,
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+impl ClientSettings {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let settings: ClientSettings =
+            toml::from_str("proxy_url = \"http://proxy.example.edu:3128/\"").unwrap();
+        assert_eq!(
+            settings,
+            ClientSettings {
+                proxy_url: Some("http://proxy.example.edu:3128/".parse().unwrap()),
+                ..ClientSettings::default()
+            }
+        );
+        assert_eq!(settings.request_timeout(), Duration::from_secs(10));
+        assert_eq!(settings.max_retries, 2);
+    }
+}
@@ -8,6 +8,8 @@ use uuid::Uuid;
 pub enum Error {
     #[error("No auth token set")]
     NoAuthToken,
+    #[error("Invalid OAuth token: expected 40 hexadecimal characters")]
+    InvalidOauthToken,
     #[error("Project directory already exists at {0}")]
     ProjectAlreadyExists(PathBuf),
     #[error("Project not found: {0}")]
@@ -43,8 +45,12 @@ pub enum Error {
     EmptyProjectDirectory(uuid::Uuid),
     #[error("Network request error: {0}")]
     NetworkRequest(String),
+    #[error("Invalid network settings: {0}")]
+    InvalidClientSettings(String),
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
     #[error("Resource not found: {0}")]
     NotFound(String),
     #[error("Unprocessable entity: {0}")]
@@ -61,6 +67,18 @@ pub enum Error {
     NoProjectData(Uuid),
     #[error("Project mutex already locked for project {0}")]
     ProjectMutexLocked(Uuid),
+    #[error("Lost the mutex on project {0}; reopen the project before saving")]
+    ProjectMutexLost(Uuid),
+    #[error("Project {0} has unsaved local changes")]
+    UnsavedChanges(Uuid),
+    #[error("Project {0} is still being downloaded or saved")]
+    ProjectTransferInProgress(Uuid),
+    #[error("Close Compass before replacing the working copy of project {0}")]
+    CompassIsOpen(Uuid),
+    #[error("Insufficient permission for project {0}")]
+    InsufficientPermission(Uuid),
+    #[error("Downloaded archive is corrupt: expected SHA-256 {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
     #[error("Zip File Error: {0}")]
     ZipFile(String),
     #[error("Os Command Error: {0}")]
@@ -73,6 +91,24 @@ pub enum Error {
     CompassProject(String),
     #[error("No app handle available")]
     NoAppHandle,
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl Error {
+    /// Whether the server refused the request itself (an HTTP 4xx answer),
+    /// so sending it again can't succeed.
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            Self::Unauthorized(_)
+            | Self::Forbidden(_)
+            | Self::NotFound(_)
+            | Self::Unprocessable(_)
+            | Self::Conflict(_) => true,
+            Self::Api { status, .. } => (400..500).contains(status),
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -97,4 +133,24 @@ mod tests {
             format!("Project mutex already locked for project {id}")
         );
     }
+
+    #[test]
+    fn only_4xx_answers_are_client_errors() {
+        assert!(Error::NotFound("gone".to_string()).is_client_error());
+        assert!(
+            Error::Api {
+                status: 405,
+                message: "Method Not Allowed".to_string(),
+            }
+            .is_client_error()
+        );
+        assert!(
+            !Error::Api {
+                status: 503,
+                message: "Service Unavailable".to_string(),
+            }
+            .is_client_error()
+        );
+        assert!(!Error::NetworkRequest("timed out".to_string()).is_client_error());
+    }
 }
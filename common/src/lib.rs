@@ -1,8 +1,11 @@
 mod api_info;
 pub mod api_types;
+mod client_settings;
+pub mod server_time;
 pub mod ui_state;
 
-pub use api_info::{ApiInfo, OauthToken};
+pub use api_info::{ApiInfo, OauthToken, ServerCapabilities};
+pub use client_settings::ClientSettings;
 mod error;
 pub use error::Error;
 
@@ -11,4 +14,5 @@ pub const API_BASE_URL: &str = "https://stage.speleodb.org";
 #[cfg(not(debug_assertions))]
 pub const API_BASE_URL: &str = "https://www.speleodb.org";
 
-pub const SERVER_TIME_ZONE: &str = "US/Eastern";
+/// Time zone of naive timestamps sent by the server; see [`server_time`].
+pub const SERVER_TIME_ZONE: chrono_tz::Tz = chrono_tz::US::Eastern;
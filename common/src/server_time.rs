@@ -0,0 +1,157 @@
+//! Timestamps in SpeleoDB API payloads.
+//!
+//! The server mostly sends RFC 3339 timestamps with an offset, but some come
+//! back naive, in the server's local time. Both are read into
+//! `DateTime<Utc>` here, with [`SERVER_TIME_ZONE`] applied to naive ones, so
+//! nothing after deserialization has to guess a time zone. Use as
+//! `#[serde(with = "server_time")]`, or `server_time::option` for optional
+//! fields. Timestamps are written back as RFC 3339 in UTC.
+
+use chrono::{DateTime, NaiveDateTime, Offset, SecondsFormat, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::SERVER_TIME_ZONE;
+
+const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// Parse a timestamp as sent by the server. A naive timestamp is taken as
+/// server local time; see `from_server_local_time`.
+pub fn parse(timestamp: &str) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(datetime.to_utc());
+    }
+    NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(timestamp, format).ok())
+        .and_then(from_server_local_time)
+}
+
+/// In the hour repeated when daylight saving time ends, the earlier instant
+/// wins. A time skipped when it starts is read with the offset from before
+/// the jump, which shifts it forward past the gap.
+fn from_server_local_time(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    if let Some(datetime) = SERVER_TIME_ZONE.from_local_datetime(&naive).earliest() {
+        return Some(datetime.to_utc());
+    }
+    let offset_before = SERVER_TIME_ZONE
+        .offset_from_local_datetime(&(naive - TimeDelta::days(1)))
+        .earliest()?
+        .fix();
+    offset_before
+        .from_local_datetime(&naive)
+        .single()
+        .map(|datetime| datetime.to_utc())
+}
+
+fn format(datetime: &DateTime<Utc>) -> String {
+    datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+pub fn serialize<S: Serializer>(
+    datetime: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format(datetime))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let timestamp = String::deserialize(deserializer)?;
+    parse(&timestamp).ok_or_else(|| de::Error::custom(format!("invalid timestamp: {timestamp}")))
+}
+
+/// `server_time` for `Option<DateTime<Utc>>` fields.
+pub mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        datetime: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        datetime.as_ref().map(format).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|timestamp| {
+                parse(&timestamp)
+                    .ok_or_else(|| de::Error::custom(format!("invalid timestamp: {timestamp}")))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().to_utc()
+    }
+
+    #[test]
+    fn explicit_offsets_are_kept() {
+        assert_eq!(
+            parse("2026-02-27T10:08:23.160083-05:00"),
+            Some(utc("2026-02-27T15:08:23.160083Z"))
+        );
+        assert_eq!(
+            parse("2026-01-01T00:00:00Z"),
+            Some(utc("2026-01-01T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn naive_timestamps_use_the_server_time_zone() {
+        // Standard time in winter, daylight saving time in summer.
+        assert_eq!(
+            parse("2026-02-26T01:02:43"),
+            Some(utc("2026-02-26T06:02:43Z"))
+        );
+        assert_eq!(
+            parse("2026-07-04 12:00:00.5"),
+            Some(utc("2026-07-04T16:00:00.5Z"))
+        );
+    }
+
+    #[test]
+    fn naive_timestamps_around_daylight_saving_changes_resolve() {
+        // Skipped when clocks jump from 02:00 EST to 03:00 EDT.
+        assert_eq!(
+            parse("2026-03-08T02:30:00"),
+            Some(utc("2026-03-08T07:30:00Z"))
+        );
+        // Repeated when clocks fall back from 02:00 EDT to 01:00 EST.
+        assert_eq!(
+            parse("2026-11-01T01:30:00"),
+            Some(utc("2026-11-01T05:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn invalid_timestamps_are_rejected() {
+        for timestamp in ["", "yesterday", "2026-02-30T00:00:00Z", "2026-02-26"] {
+            assert_eq!(parse(timestamp), None, "{timestamp:?}");
+        }
+    }
+
+    #[test]
+    fn timestamps_serialize_as_utc_and_round_trip() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Dated {
+            #[serde(with = "crate::server_time")]
+            at: DateTime<Utc>,
+            #[serde(default, with = "crate::server_time::option")]
+            maybe: Option<DateTime<Utc>>,
+        }
+
+        let dated: Dated = serde_json::from_str(r#"{"at":"2026-02-26T01:02:43"}"#).unwrap();
+        assert_eq!(dated.maybe, None);
+        let json = serde_json::to_string(&dated).unwrap();
+        assert_eq!(json, r#"{"at":"2026-02-26T06:02:43Z","maybe":null}"#);
+        assert_eq!(serde_json::from_str::<Dated>(&json).unwrap(), dated);
+        assert!(serde_json::from_str::<Dated>(r#"{"at":"soon"}"#).is_err());
+    }
+}
@@ -1,8 +1,13 @@
 // Re-export api types used directly in the UI
-pub use crate::api_types::{ActiveMutex, ProjectInfo, ProjectSaveResult, ProjectType};
+pub use crate::api_types::{
+    ActiveMutex, Permission, ProjectInfo, ProjectMetadata, ProjectSaveResult, ProjectType,
+    Visibility,
+};
 
 use crate::Error;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use url::Url;
 use uuid::Uuid;
 
 /// The status of a local project in relation to its remote counterpart.
@@ -53,18 +58,26 @@ impl ProjectStatus {
         &self.info.name
     }
 
-    pub fn modified_date(&self) -> &str {
-        &self.info.modified_date
+    pub fn modified_date(&self) -> DateTime<Utc> {
+        self.info.modified_date
     }
 
     pub fn active_mutex(&self) -> &Option<ActiveMutex> {
         &self.info.active_mutex
     }
 
-    pub fn permission(&self) -> &str {
+    pub fn permission(&self) -> &Permission {
         &self.info.permission
     }
 
+    pub fn can_edit_metadata(&self) -> bool {
+        self.info.can_edit_metadata()
+    }
+
+    pub fn metadata(&self) -> ProjectMetadata {
+        self.info.metadata()
+    }
+
     pub fn latest_commit(&self) -> Option<&crate::api_types::CommitInfo> {
         self.info.latest_commit.as_ref()
     }
@@ -148,6 +161,25 @@ pub enum Platform {
     Linux,
 }
 
+/// Raised when the periodic lock renewal finds that the active project's
+/// mutex is no longer ours. Saving is refused until the project is reopened.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MutexWarning {
+    pub project_id: Uuid,
+    /// Who holds the lock now, or `None` if nobody does (it expired or an
+    /// admin released it).
+    pub taken_by: Option<String>,
+}
+
+/// A saved account: which instance, as whom. The token stays in the sidecar.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ProfileSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub instance: Url,
+    pub email: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UiState {
     pub loading_state: LoadingState,
@@ -157,7 +189,18 @@ pub struct UiState {
     pub selected_project_id: Option<Uuid>,
     pub compass_open: bool,
     pub project_downloading: bool,
+    /// Download progress of the active project, if the size is known.
+    pub project_download_progress: Option<u8>,
+    /// Upload progress of the active project while it is being saved.
+    pub project_upload_progress: Option<u8>,
+    pub mutex_warning: Option<MutexWarning>,
     pub update_notification: Option<UpdateNotification>,
+    /// Every saved account, for the profile switcher.
+    pub profiles: Vec<ProfileSummary>,
+    pub active_profile: Option<Uuid>,
+    /// The active profile's token was rejected; signing in again resumes
+    /// where the user was.
+    pub session_expired: bool,
 }
 
 impl UiState {
@@ -185,7 +228,13 @@ impl UiState {
             selected_project_id: selected_project,
             compass_open,
             project_downloading,
+            project_download_progress: None,
+            project_upload_progress: None,
+            mutex_warning: None,
             update_notification,
+            profiles: Vec::new(),
+            active_profile: None,
+            session_expired: false,
         }
     }
 }